@@ -0,0 +1,19 @@
+// ParquetGrip - A high-performance Parquet file viewer.
+// Copyright (c) 2026 Edward (wansanai)
+// SPDX-License-Identifier: MIT
+
+//! Exposes the resolved versions of the main dependencies to the About dialog.
+
+fn main() {
+    println!("cargo:rerun-if-changed=Cargo.lock");
+    let lock = std::fs::read_to_string("Cargo.lock").unwrap_or_default();
+    for (name, var) in [("egui", "PG_EGUI_VERSION"), ("eframe", "PG_EFRAME_VERSION"), ("egui_dock", "PG_EGUI_DOCK_VERSION"), ("duckdb", "PG_DUCKDB_CRATE_VERSION")] {
+        let version = lock.split("[[package]]").find_map(|pkg| {
+            let mut lines = pkg.lines().map(str::trim);
+            (lines.any(|l| l == format!("name = \"{}\"", name)))
+                .then(|| pkg.lines().map(str::trim).find_map(|l| l.strip_prefix("version = \"")?.strip_suffix('"').map(str::to_string)))
+                .flatten()
+        });
+        println!("cargo:rustc-env={}={}", var, version.as_deref().unwrap_or("unknown"));
+    }
+}
@@ -2,118 +2,1526 @@
 // Copyright (c) 2026 Edward (wansanai)
 // SPDX-License-Identifier: MIT
 
-use duckdb::{Connection, Result};
-use std::sync::{Arc, Mutex};
+use duckdb::arrow::array::{Array, ArrayRef, AsArray};
+use duckdb::arrow::datatypes::{self as arrow_types, DataType};
+use duckdb::arrow::record_batch::RecordBatch;
+use duckdb::arrow::util::display::{ArrayFormatter, FormatOptions};
+use duckdb::{Connection, InterruptHandle, Result};
+use serde::{Deserialize, Serialize};
+use std::cell::{OnceCell, RefCell};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::JoinHandle;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc;
+
+/// Issued by the UI for each grid load so late answers to superseded requests can be told apart.
+pub type RequestId = u64;
 
 #[derive(Debug, Clone)]
 pub enum BackendMessage {
     FileOpened { path: String },
-    Schema { path: String, columns: Vec<String> },
-    QueryData { path: String, rows: Vec<Vec<String>>, sql: String },
-    RowCount { path: String, count: usize, sql: String },
+    /// Opening `path` hit a sharing violation and is waiting to try again; `attempt` counts from 1.
+    FileBusy { path: String, attempt: usize, attempts: usize },
+    Schema { path: String, fields: Vec<SchemaField> },
+    /// A page's rows. A streamed load first sends its rows in chunks with `complete` unset, then the whole page.
+    /// `columns` come from the statement itself, so they're right even for zero rows or computed expressions.
+    /// `truncated` means the row cap stopped it before the rows ran out.
+    QueryData { request: RequestId, path: String, columns: Vec<String>, batch: Arc<RecordBatch>, sql: String, truncated: bool, complete: bool },
+    RowCount { request: RequestId, path: String, count: usize, sql: String },
     SqlLog { path: String, sql: String },
-    Error { path: Option<String>, message: String, sql: Option<String> },
+    /// Outcome of checking a live filter before it's applied; `error` is why it didn't bind.
+    FilterChecked { request: RequestId, path: String, error: Option<String> },
+    RowGroups { path: String, groups: Vec<RowGroupInfo>, sql: String },
+    /// `request` is set when the error answers a grid load, so only that load's tab is marked failed.
+    Error { request: Option<RequestId>, path: Option<String>, message: String, sql: Option<String> },
+    CellValue { path: String, row: usize, column: String, value: Option<String> },
+    ColumnValues { path: String, column: String, values: Vec<Cell>, sql: String, truncated: bool },
+    ClipboardText { path: String, text: String, note: String },
+    /// A file was loaded into (or dropped from) an in-memory table; `error` is why loading it failed.
+    MemoryCache { path: String, cached: bool, sql: String, error: Option<String> },
+    /// Sent while a grid query for `request` is still running. `percent` is `None` when DuckDB can't say
+    /// how far along it is, which for now is always: duckdb-rs doesn't hand out the connection handle that
+    /// `duckdb_query_progress` needs.
+    Progress { request: RequestId, path: String, percent: Option<f32>, elapsed: std::time::Duration },
+    ExportProgress { job_id: usize, rows_done: usize, bytes_written: u64 },
+    /// Split exports: parts finished out of the expected total.
+    ExportFiles { job_id: usize, done: usize, total: usize },
+    ExportDone { job_id: usize, rows: usize, bytes_written: u64, file: String },
+    /// `cancelled` when the user stopped the export, rather than it running into an error.
+    ExportFailed { job_id: usize, message: String, cancelled: bool },
+    /// A native file dialog opened under `request` closed; `paths` is empty when it was cancelled.
+    FileDialog { request: RequestId, paths: Vec<std::path::PathBuf> },
+    /// Bytes DuckDB holds in memory and has spilled to disk, and the process's resident memory where known.
+    MemoryUsage { duckdb: u64, spilled: u64, process: Option<u64> },
+}
+
+/// Where one Parquet row group sits in the file, from `parquet_metadata`.
+#[derive(Debug, Clone)]
+pub struct RowGroupInfo {
+    pub first_row: usize,
+    pub rows: usize,
+    /// Uncompressed size as recorded in the footer.
+    pub bytes: u64,
+}
+
+/// Per-column statistics from DuckDB's `SUMMARIZE`, one row per column of the file.
+#[derive(Debug, Clone)]
+pub struct ProfileReport {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<Option<String>>>,
+    pub row_count: usize,
+    pub sql: String,
+}
+
+/// Separates a friendly error message from DuckDB's original text, which the UI shows under "Details".
+pub const ERROR_DETAILS_SEPARATOR: &str = "\n\nDetails: ";
+
+/// A streamed page load sends what it has once this many rows or this much time have built up.
+const STREAM_CHUNK_ROWS: usize = 1000;
+const STREAM_CHUNK_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
+
+/// Opening a file that's locked by its writer is retried this many times, this far apart.
+const OPEN_BUSY_ATTEMPTS: usize = 5;
+const OPEN_BUSY_DELAY: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// How many files' schema and row count are remembered across sessions.
+const FILE_FACTS_KEPT: usize = 200;
+
+/// Spill files untouched this long are taken for a crashed session's leftovers; younger ones may belong to
+/// another window still running.
+const STALE_SPILL_AGE: std::time::Duration = std::time::Duration::from_secs(24 * 60 * 60);
+
+/// A file's schema and unfiltered row count, valid while its modification time and size stay the same.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileFacts {
+    path: String,
+    modified: u64,
+    size: u64,
+    fields: Option<Vec<SchemaField>>,
+    rows: Option<usize>,
+}
+
+impl FileFacts {
+    /// The canonical path, modification time (ns) and size of a single local file; `None` for URLs, globs
+    /// and merged files, which can change without the stamp showing it.
+    fn stamp(path: &str) -> Option<(String, u64, u64)> {
+        if source_files(path).len() != 1 || path.contains(['*', '?']) { return None; }
+        let canonical = std::fs::canonicalize(path).ok()?;
+        let meta = std::fs::metadata(&canonical).ok().filter(|m| m.is_file())?;
+        let modified = meta.modified().ok()?.duration_since(std::time::UNIX_EPOCH).ok()?.as_nanos() as u64;
+        Some((canonical.to_string_lossy().into_owned(), modified, meta.len()))
+    }
+}
+
+/// The row cap until the settings say otherwise.
+pub const DEFAULT_ROW_CAP: usize = 50_000;
+
+/// Hard ceiling on the row cap, so no setting lets one in-memory result exhaust memory.
+pub const MAX_ROW_CAP: usize = 5_000_000;
+
+/// One column as reported by `DESCRIBE`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchemaField {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub data_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nullable: Option<bool>,
+}
+
+/// A column, or a field reached inside one, as the filter builder offers it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldPath {
+    /// How it reads to the user, e.g. `address.city` or `tags[1]`.
+    pub label: String,
+    /// The DuckDB expression for it, e.g. `"address"."city"` or `"tags"[1]`.
+    pub sql: String,
+    pub data_type: String,
+}
+
+/// Structs and lists nested deeper than this aren't offered in the filter builder.
+const FIELD_PATH_DEPTH: usize = 4;
+
+/// The column followed by every struct field and first list element under it, worked out from the type
+/// `DESCRIBE` reports, e.g. `STRUCT(city VARCHAR, tags VARCHAR[])`.
+pub fn field_paths(field: &SchemaField) -> Vec<FieldPath> {
+    let mut out = Vec::new();
+    walk_type(field.name.clone(), quote_ident(&field.name), &field.data_type, 0, &mut out);
+    out
+}
+
+fn walk_type(label: String, sql: String, data_type: &str, depth: usize, out: &mut Vec<FieldPath>) {
+    let data_type = data_type.trim();
+    out.push(FieldPath { label: label.clone(), sql: sql.clone(), data_type: data_type.to_string() });
+    if depth >= FIELD_PATH_DEPTH { return; }
+    // A list (`T[]`) or fixed-size array (`T[3]`); DuckDB lists count from 1
+    if let Some(open) = data_type.strip_suffix(']').and_then(|t| t.rfind('[').filter(|&i| t[i + 1..].bytes().all(|b| b.is_ascii_digit()))) {
+        walk_type(format!("{}[1]", label), format!("{}[1]", sql), &data_type[..open], depth + 1, out);
+    } else if let Some(members) = data_type.strip_prefix("STRUCT(").and_then(|t| t.strip_suffix(')')) {
+        for member in split_top_level(members) {
+            let member = member.trim();
+            let (name, rest) = match member.strip_prefix('"') {
+                Some(quoted) => {
+                    // A doubled quote inside the name stands for one
+                    let (mut name, mut chars, mut end) = (String::new(), quoted.char_indices().peekable(), quoted.len());
+                    while let Some((i, c)) = chars.next() {
+                        if c != '"' { name.push(c); }
+                        else if chars.next_if(|(_, d)| *d == '"').is_some() { name.push('"'); }
+                        else { end = i + 1; break; }
+                    }
+                    (name, &quoted[end..])
+                }
+                None => member.split_once(' ').map_or((member.to_string(), ""), |(n, t)| (n.to_string(), t)),
+            };
+            if rest.trim().is_empty() { continue; }
+            walk_type(format!("{}.{}", label, name), format!("{}.{}", sql, quote_ident(&name)), rest, depth + 1, out);
+        }
+    }
+}
+
+/// Splits a type's member list, or a sort, at the commas that aren't inside parentheses or quotes.
+pub fn split_top_level(text: &str) -> Vec<&str> {
+    let (mut parts, mut depth, mut quoted, mut start) = (Vec::new(), 0i32, false, 0);
+    for (i, c) in text.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            '(' if !quoted => depth += 1,
+            ')' if !quoted => depth -= 1,
+            ',' if !quoted && depth == 0 => { parts.push(&text[start..i]); start = i + 1; }
+            _ => {}
+        }
+    }
+    parts.push(&text[start..]);
+    parts
+}
+
+/// An owned, typed result value for consumers that need more than the grid's display string.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cell {
+    Null,
+    Bool(bool),
+    Int(i128),
+    Float(f64),
+    /// Kept as text so no precision is lost.
+    Decimal(String),
+    Text(String),
+    Date(NaiveDate),
+    Timestamp(chrono::DateTime<Utc>),
+    Blob(Vec<u8>),
+    /// Times, intervals and nested values, in their display form.
+    Other(String),
+}
+
+impl Cell {
+    pub fn is_numeric(&self) -> bool {
+        matches!(self, Cell::Int(_) | Cell::Float(_) | Cell::Decimal(_))
+    }
+
+    /// The same text the grid shows, `None` for NULL.
+    pub fn display(&self) -> Option<String> {
+        match self {
+            Cell::Null => None,
+            Cell::Bool(b) => Some(b.to_string()),
+            Cell::Int(i) => Some(i.to_string()),
+            Cell::Float(f) => Some(f.to_string()),
+            Cell::Decimal(s) | Cell::Text(s) | Cell::Other(s) => Some(s.clone()),
+            Cell::Date(d) => Some(d.format("%Y-%m-%d").to_string()),
+            Cell::Timestamp(dt) => Some(timestamp_with_zone(*dt, None)),
+            Cell::Blob(b) => Some(format!("<blob {} bytes>", b.len())),
+        }
+    }
+}
+
+impl From<ValueRef<'_>> for Cell {
+    fn from(v: ValueRef<'_>) -> Self {
+        match v {
+            ValueRef::Null => Cell::Null,
+            ValueRef::Boolean(b) => Cell::Bool(b),
+            ValueRef::TinyInt(i) => Cell::Int(i.into()),
+            ValueRef::SmallInt(i) => Cell::Int(i.into()),
+            ValueRef::Int(i) => Cell::Int(i.into()),
+            ValueRef::BigInt(i) => Cell::Int(i.into()),
+            ValueRef::HugeInt(i) => Cell::Int(i),
+            ValueRef::UTinyInt(i) => Cell::Int(i.into()),
+            ValueRef::USmallInt(i) => Cell::Int(i.into()),
+            ValueRef::UInt(i) => Cell::Int(i.into()),
+            ValueRef::UBigInt(i) => Cell::Int(i.into()),
+            // Only the top half of UHUGEINT's range is past i128
+            ValueRef::UHugeInt(i) => i128::try_from(i).map_or_else(|_| Cell::Decimal(i.to_string()), Cell::Int),
+            ValueRef::Float(f) => Cell::Float(f.into()),
+            ValueRef::Double(f) => Cell::Float(f),
+            ValueRef::Decimal(d) => Cell::Decimal(d.to_string()),
+            ValueRef::Text(t) => Cell::Text(String::from_utf8_lossy(t).into_owned()),
+            ValueRef::Blob(b) => Cell::Blob(b.to_vec()),
+            ValueRef::Date32(d) => date32_to_date(d).map_or_else(|| Cell::Other(value_ref_to_string(v)), Cell::Date),
+            ValueRef::Timestamp(u, t) => timestamp_to_datetime(u, t).map_or_else(|| Cell::Other(value_ref_to_string(v)), Cell::Timestamp),
+            v => Cell::Other(value_ref_to_string(v)),
+        }
+    }
+}
+
+/// Collects a streamed result into owned typed rows.
+struct CellCollector {
+    columns: Vec<String>,
+    rows: Vec<Vec<Cell>>,
+}
+
+impl RowSink for CellCollector {
+    fn begin(&mut self, columns: &[String]) -> Result<(), String> {
+        self.columns = columns.to_vec();
+        Ok(())
+    }
+
+    fn row(&mut self, values: &[ValueRef<'_>]) -> Result<(), String> {
+        self.rows.push(values.iter().map(|v| Cell::from(*v)).collect());
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// One page of results kept as Arrow columns; a row is formatted into display strings the first time it's read.
+/// Equal strings share one allocation, so a categorical column holds each distinct value once per page.
+#[derive(Debug, Clone, Default)]
+pub struct PageData {
+    batch: Option<Arc<RecordBatch>>,
+    rows: Vec<OnceCell<Vec<Arc<str>>>>,
+    strings: RefCell<HashSet<Arc<str>>>,
+    ranges: OnceCell<Vec<Option<(f64, f64)>>>,
+}
+
+impl PageData {
+    pub fn new(batch: Arc<RecordBatch>) -> Self {
+        let rows = (0..batch.num_rows()).map(|_| OnceCell::new()).collect();
+        Self { batch: Some(batch), rows, strings: RefCell::default(), ranges: OnceCell::new() }
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Whether column `col` holds numbers, going by its Arrow type.
+    pub fn is_numeric_column(&self, col: usize) -> bool {
+        self.batch.as_ref().is_some_and(|b| col < b.num_columns() && b.column(col).data_type().is_numeric())
+    }
+
+    pub fn row(&self, i: usize) -> Option<&[Arc<str>]> {
+        let batch = self.batch.as_ref()?;
+        let cell = self.rows.get(i)?;
+        Some(cell.get_or_init(|| batch.columns().iter().map(|c| self.intern(array_value_to_string(c, i))).collect()))
+    }
+
+    fn intern(&self, value: String) -> Arc<str> {
+        let mut strings = self.strings.borrow_mut();
+        if let Some(shared) = strings.get(value.as_str()) { return shared.clone(); }
+        let value: Arc<str> = value.into();
+        strings.insert(value.clone());
+        value
+    }
+
+    /// Adds rows streamed in after the ones already held; rows formatted so far keep their strings.
+    pub fn append(&mut self, batch: Arc<RecordBatch>) {
+        let Some(current) = &self.batch else { *self = Self::new(batch); return };
+        let Ok(joined) = duckdb::arrow::compute::concat_batches(&current.schema(), [current.as_ref(), batch.as_ref()]) else { return };
+        self.rows.extend((0..batch.num_rows()).map(|_| OnceCell::new()));
+        self.batch = Some(Arc::new(joined));
+        self.ranges = OnceCell::new();
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &[Arc<str>]> {
+        (0..self.len()).filter_map(|i| self.row(i))
+    }
+
+    /// Smallest and largest value of a numeric column on this page; `None` for other columns or when there's
+    /// nothing finite to go by.
+    pub fn numeric_range(&self, col: usize) -> Option<(f64, f64)> {
+        let batch = self.batch.as_ref()?;
+        self.ranges.get_or_init(|| batch.columns().iter().map(numeric_range).collect()).get(col).copied().flatten()
+    }
+
+    /// Whether any cell was cut short by the fetch cap, so copying the page needs it fetched in full.
+    pub fn has_capped_cells(&self) -> bool {
+        self.iter().any(|r| r.iter().any(|c| is_capped(c)))
+    }
+
+    /// Bytes held by the Arrow buffers, not counting rows already formatted.
+    pub fn memory_size(&self) -> usize {
+        self.batch.as_ref().map_or(0, |b| b.get_array_memory_size())
+    }
+
+    /// Bytes taken by the rows formatted so far, and what they would take if every cell had its own string.
+    pub fn formatted_size(&self) -> (usize, usize) {
+        let cells: usize = self.rows.iter().filter_map(|r| r.get()).map(|r| r.len() * size_of::<Arc<str>>()).sum();
+        let shared: usize = self.strings.borrow().iter().map(|s| s.len() + 2 * size_of::<usize>()).sum();
+        let unshared: usize = self.rows.iter().filter_map(|r| r.get()).flatten().map(|s| s.len() + 2 * size_of::<usize>()).sum();
+        (cells + shared, cells + unshared)
+    }
+}
+
+fn numeric_range(array: &ArrayRef) -> Option<(f64, f64)> {
+    if !array.data_type().is_numeric() { return None; }
+    let floats = duckdb::arrow::compute::cast(array, &DataType::Float64).ok()?;
+    let floats = floats.as_primitive::<arrow_types::Float64Type>();
+    let (min, max) = (duckdb::arrow::compute::min(floats)?, duckdb::arrow::compute::max(floats)?);
+    (min.is_finite() && max.is_finite()).then_some((min, max))
+}
+
+/// Receives a query result one row at a time, so exports never hold the whole result in memory.
+pub trait RowSink {
+    fn begin(&mut self, columns: &[String]) -> Result<(), String>;
+    fn row(&mut self, values: &[ValueRef<'_>]) -> Result<(), String>;
+    /// Flushes whatever the sink buffered once the last row has been written.
+    fn finish(&mut self) -> Result<(), String>;
+    /// How many of the rows it was given the sink wrote, for one that leaves some out; `None` when it wrote all.
+    fn rows_kept(&self) -> Option<usize> {
+        None
+    }
+}
+
+/// Shared by the UI and an export thread. The flag is checked between rows; the interrupt also aborts
+/// a query DuckDB is still sorting or scanning before the first row arrives.
+#[derive(Default)]
+pub struct ExportCancel {
+    cancelled: AtomicBool,
+    interrupt: Mutex<Option<Arc<InterruptHandle>>>,
+}
+
+impl ExportCancel {
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+        if let Some(h) = self.interrupt.lock().ok().and_then(|h| h.clone()) {
+            h.interrupt();
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// A filtered/sorted slice of one file, as used by the streaming export path.
+#[derive(Clone, Default)]
+pub struct QuerySpec {
+    pub path: String,
+    /// Select list; `None` means every column.
+    pub projection: Option<String>,
+    pub filter: Option<String>,
+    pub sort: Option<String>,
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+}
+
+impl QuerySpec {
+    /// The query, reading the tab's view or in-memory copy like the grid does.
+    pub fn sql(&self, backend: &Backend) -> String {
+        let mut query = Backend::select_columns_sql(&backend.source(&self.path), self.projection.as_deref().unwrap_or("*"), self.filter.as_deref(), self.sort.as_deref());
+        if let Some(l) = self.limit {
+            query.push_str(&format!(" LIMIT {}", l));
+        }
+        if let Some(o) = self.offset {
+            query.push_str(&format!(" OFFSET {}", o));
+        }
+        query
+    }
+}
+
+/// Threads the backend runs grid loads on, so a restored session doesn't start one thread per tab.
+const WORKER_THREADS: usize = 4;
+
+/// How many finished jobs Help > Performance keeps, oldest dropped first.
+const PERF_RECORDS_KEPT: usize = 500;
+
+/// One backend job as listed in Help > Performance.
+#[derive(Debug, Clone)]
+pub struct PerfRecord {
+    pub kind: &'static str,
+    pub path: String,
+    /// The last query the job ran, with its literals replaced by `?`.
+    pub sql: String,
+    pub started: std::time::SystemTime,
+    pub duration: std::time::Duration,
+    pub rows: Option<usize>,
+    /// Why the job failed or never ran; `None` when it succeeded.
+    pub failure: Option<String>,
+}
+
+/// Timings of finished jobs. Nothing is recorded until the performance panel is first opened.
+#[derive(Default)]
+struct PerfLog {
+    recording: AtomicBool,
+    records: Mutex<VecDeque<PerfRecord>>,
+}
+
+impl PerfLog {
+    fn push(&self, record: PerfRecord) {
+        let Ok(mut records) = self.records.lock() else { return };
+        records.push_back(record);
+        while records.len() > PERF_RECORDS_KEPT { records.pop_front(); }
+    }
+}
+
+thread_local! {
+    /// The job running on this worker while it's being recorded; backend calls add what they learn to it.
+    static CURRENT_JOB: RefCell<Option<PerfRecord>> = const { RefCell::new(None) };
+}
+
+/// A queued job being recorded. Dropped without running, it was superseded or its tab closed.
+struct PerfTicket {
+    log: Arc<PerfLog>,
+    record: Option<PerfRecord>,
+}
+
+impl PerfTicket {
+    fn run(mut self, job: impl FnOnce()) {
+        let Some(mut record) = self.record.take() else { return };
+        let started = std::time::Instant::now();
+        record.started = std::time::SystemTime::now();
+        CURRENT_JOB.with_borrow_mut(|j| *j = Some(record));
+        job();
+        if let Some(mut record) = CURRENT_JOB.with_borrow_mut(Option::take) {
+            record.duration = started.elapsed();
+            self.log.push(record);
+        }
+    }
+}
+
+impl Drop for PerfTicket {
+    fn drop(&mut self) {
+        if let Some(mut record) = self.record.take() { record.failure = Some("Cancelled before it started".to_string()); self.log.push(record); }
+    }
+}
+
+/// Notes the query the current job runs, for the performance panel; does nothing unless it's recording.
+fn note_sql(sql: &str) {
+    CURRENT_JOB.with_borrow_mut(|j| if let Some(j) = j { j.sql = sql_shape(sql); });
+}
+
+fn note_rows(rows: usize) {
+    CURRENT_JOB.with_borrow_mut(|j| if let Some(j) = j { j.rows = Some(rows); });
+}
+
+/// Notes why the current job failed; its first line is what the performance panel shows.
+pub fn note_failure(message: &str) {
+    CURRENT_JOB.with_borrow_mut(|j| if let Some(j) = j { j.failure = Some(message.lines().next().unwrap_or_default().to_string()); });
+}
+
+/// A query with its string and number literals replaced by `?` and its whitespace collapsed, so runs of
+/// the same query line up however their values differ.
+fn sql_shape(sql: &str) -> String {
+    let (mut out, mut chars, mut in_word) = (String::new(), sql.chars().peekable(), false);
+    while let Some(c) = chars.next() {
+        if c == '\'' {
+            while let Some(d) = chars.next() { if d == '\'' { if chars.peek() == Some(&'\'') { chars.next(); } else { break; } } }
+            out.push('?');
+        } else if c.is_ascii_digit() && !in_word {
+            while chars.peek().is_some_and(|d| d.is_ascii_digit() || *d == '.') { chars.next(); }
+            out.push('?');
+        } else if c.is_whitespace() {
+            if !out.ends_with(' ') { out.push(' '); }
+        } else {
+            out.push(c);
+        }
+        in_word = c.is_alphanumeric() || c == '_';
+    }
+    out.trim().chars().take(300).collect()
+}
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// Longest quitting waits for workers to finish their interrupted jobs.
+const SHUTDOWN_WAIT: std::time::Duration = std::time::Duration::from_secs(2);
+type PathConn = (Arc<Mutex<Connection>>, Arc<InterruptHandle>);
+/// Connections jobs opened for themselves, by id: the path each works on, its interrupt handle and whether it was stopped.
+type JobConns = Arc<Mutex<HashMap<u64, (String, Arc<InterruptHandle>, Arc<AtomicBool>)>>>;
+
+/// A job's own connection, listed in `Backend::job_conns` while it lives so closing its tab or quitting
+/// can interrupt it like a query on the tab's connection.
+struct JobConn {
+    conn: Connection,
+    id: u64,
+    stopped: Arc<AtomicBool>,
+    registry: JobConns,
+}
+
+impl JobConn {
+    /// Whether closing the tab or quitting interrupted this connection's query.
+    fn stopped(&self) -> bool {
+        self.stopped.load(Ordering::Relaxed)
+    }
+}
+
+impl std::ops::Deref for JobConn {
+    type Target = Connection;
+    fn deref(&self) -> &Connection { &self.conn }
+}
+
+impl Drop for JobConn {
+    fn drop(&mut self) {
+        if let Ok(mut conns) = self.registry.lock() { conns.remove(&self.id); }
+    }
+}
+
+#[derive(Default)]
+struct JobQueue {
+    /// Each job is tagged with the file it works on, plus a kind when a newer one should replace it.
+    jobs: VecDeque<(String, Option<&'static str>, Job)>,
+    shutdown: bool,
+}
+
+/// A fixed set of worker threads, started on the first job.
+#[derive(Default)]
+struct WorkerPool {
+    queue: Arc<(Mutex<JobQueue>, Condvar)>,
+    workers: Mutex<Vec<JoinHandle<()>>>,
+}
+
+impl WorkerPool {
+    fn submit(&self, path: &str, kind: Option<&'static str>, job: Job) {
+        if let Ok(mut workers) = self.workers.lock() {
+            while workers.len() < WORKER_THREADS {
+                let queue = self.queue.clone();
+                workers.push(std::thread::spawn(move || Self::work(&queue)));
+            }
+        }
+        let (lock, ready) = &*self.queue;
+        let Ok(mut q) = lock.lock() else { return };
+        if q.shutdown { return; }
+        // A newer request for the same thing takes the older one's place in line
+        if let Some(slot) = kind.and_then(|k| q.jobs.iter_mut().find(|(p, queued, _)| p == path && *queued == Some(k))) {
+            slot.2 = job;
+            return;
+        }
+        q.jobs.push_back((path.to_string(), kind, job));
+        ready.notify_one();
+    }
+
+    fn work(queue: &(Mutex<JobQueue>, Condvar)) {
+        let (lock, ready) = queue;
+        loop {
+            let job = {
+                let Ok(mut q) = lock.lock() else { return };
+                loop {
+                    if q.shutdown { return; }
+                    if let Some((_, _, job)) = q.jobs.pop_front() { break job; }
+                    q = match ready.wait(q) { Ok(q) => q, Err(_) => return };
+                }
+            };
+            job();
+        }
+    }
+
+    /// Drops every queued job for `path` that hasn't started.
+    fn cancel_path(&self, path: &str) {
+        if let Ok(mut q) = self.queue.0.lock() {
+            q.jobs.retain(|(p, _, _)| p != path);
+        }
+    }
+
+    /// `interrupt` is repeated until every worker is idle, since jobs queued on the same connection's lock
+    /// would otherwise each start their query after the previous one was stopped. A worker stuck past
+    /// `SHUTDOWN_WAIT` is left behind rather than holding up the exit.
+    fn shutdown(&self, interrupt: impl Fn()) {
+        let (lock, ready) = &*self.queue;
+        if let Ok(mut q) = lock.lock() {
+            q.shutdown = true;
+            q.jobs.clear();
+        }
+        ready.notify_all();
+        let workers = self.workers.lock().map(|mut w| std::mem::take(&mut *w)).unwrap_or_default();
+        let started = std::time::Instant::now();
+        while !workers.iter().all(|w| w.is_finished()) {
+            if started.elapsed() > SHUTDOWN_WAIT { return; }
+            interrupt();
+            std::thread::sleep(std::time::Duration::from_millis(20));
+        }
+        for w in workers { let _ = w.join(); }
+    }
+}
+
+/// How often the watchdog looks at the grid queries in flight.
+const WATCH_TICK: std::time::Duration = std::time::Duration::from_millis(250);
+/// How long a grid query runs before the watchdog starts reporting its progress.
+const SLOW_QUERY: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// A grid query the watchdog reports progress for and interrupts once it passes `limit`.
+struct Watched {
+    request: RequestId,
+    path: String,
+    started: std::time::Instant,
+    limit: Option<std::time::Duration>,
+    handle: Arc<InterruptHandle>,
+    progress: Option<mpsc::Sender<BackendMessage>>,
+    timed_out: bool,
+}
+
+#[derive(Default)]
+struct WatchList {
+    queries: HashMap<u64, Watched>,
+    next: u64,
+    /// Whether the watchdog thread is up; it stops once nothing is left to watch.
+    running: bool,
+}
+
+/// One thread shared by every grid query in flight. Queries faster than `SLOW_QUERY` are done before it
+/// looks at them; only slower ones get progress reports and the timeout.
+#[derive(Default)]
+struct Watchdog {
+    list: Arc<Mutex<WatchList>>,
+}
+
+impl Watchdog {
+    fn watch(&self, query: Watched) -> u64 {
+        let Ok(mut list) = self.list.lock() else { return 0 };
+        list.next += 1;
+        let id = list.next;
+        list.queries.insert(id, query);
+        if !list.running {
+            list.running = true;
+            let shared = self.list.clone();
+            std::thread::spawn(move || Self::run(&shared));
+        }
+        id
+    }
+
+    /// Stops watching query `id` and says whether it was timed out. Both happen under the list's lock, so a
+    /// query is either finished or timed out, and no progress for it is sent afterwards.
+    fn finish(&self, id: u64) -> bool {
+        self.list.lock().ok().and_then(|mut l| l.queries.remove(&id)).is_some_and(|w| w.timed_out)
+    }
+
+    fn run(list: &Mutex<WatchList>) {
+        loop {
+            std::thread::sleep(WATCH_TICK);
+            let Ok(mut list) = list.lock() else { return };
+            if list.queries.is_empty() { list.running = false; return; }
+            for w in list.queries.values_mut().filter(|w| !w.timed_out) {
+                let elapsed = w.started.elapsed();
+                if elapsed < SLOW_QUERY { continue; }
+                if w.limit.is_some_and(|l| elapsed >= l) {
+                    w.timed_out = true;
+                    w.handle.interrupt();
+                } else if let Some(tx) = &w.progress {
+                    let _ = tx.send(BackendMessage::Progress { request: w.request, path: w.path.clone(), percent: None, elapsed });
+                }
+            }
+        }
+    }
+}
+
+/// DuckDB resource settings for the shared database; `None` leaves DuckDB's own default.
+#[derive(Clone, Default, Debug, PartialEq)]
+pub struct EngineConfig {
+    pub memory_limit: Option<String>,
+    pub threads: Option<usize>,
+    pub temp_directory: Option<String>,
+    /// SET, RESET and PRAGMA statements run on every new connection after the settings above.
+    pub session_sql: Option<String>,
+}
+
+impl EngineConfig {
+    /// Checks the values before any of them reach DuckDB, so a typo can't leave half of them applied.
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(limit) = &self.memory_limit {
+            if parse_size(&normalize_size(limit)).is_none() { return Err(format!("Memory limit \"{}\" isn't a size like 4GB or 512 MiB", limit)); }
+        }
+        if self.threads == Some(0) { return Err("Threads must be at least 1".to_string()); }
+        if let Some(dir) = &self.temp_directory {
+            let dir = std::path::Path::new(dir);
+            // DuckDB creates the directory itself, but only one level deep
+            if !dir.is_dir() && !dir.parent().is_some_and(|p| p.is_dir()) { return Err(format!("Temp directory {} doesn't exist", dir.display())); }
+        }
+        // Anything else could read or write files behind the user's back every time a tab connects
+        for statement in self.session_sql.as_deref().map(split_statements).unwrap_or_default() {
+            let keyword = statement.split_whitespace().next().unwrap_or("").to_uppercase();
+            if !["SET", "RESET", "PRAGMA"].contains(&keyword.as_str()) { return Err(format!("Session SQL only runs SET, RESET and PRAGMA statements, not \"{}\"", statement)); }
+        }
+        Ok(())
+    }
+
+    /// Where sorts and joins too big for the memory limit spill to: the directory set, else a per-user cache folder.
+    pub fn spill_directory(&self) -> Option<PathBuf> {
+        self.temp_directory.as_ref().map(PathBuf::from).or_else(default_spill_directory)
+    }
+
+    /// The database-wide settings, which every connection to it sees as soon as they are set.
+    fn global_sql(&self) -> String {
+        let mut sql = match &self.memory_limit { Some(l) => format!("SET memory_limit = {};", quote_literal(&normalize_size(l))), None => "RESET memory_limit;".to_string() };
+        sql += &match self.threads { Some(n) => format!("SET threads = {};", n), None => "RESET threads;".to_string() };
+        sql += &match self.spill_directory() { Some(d) => format!("SET temp_directory = {};", quote_literal(&d.to_string_lossy())), None => "RESET temp_directory;".to_string() };
+        sql
+    }
+
+    /// The session SQL, which only reaches the connection it runs on; empty when there is none.
+    fn session_sql(&self) -> String {
+        self.session_sql.as_deref().map(split_statements).unwrap_or_default().iter().map(|s| format!("{};", s)).collect()
+    }
+}
+
+/// Splits a script at semicolons outside quotes, dropping `--` comments and empty statements.
+fn split_statements(sql: &str) -> Vec<String> {
+    let (mut statements, mut current, mut quote) = (Vec::new(), String::new(), None);
+    let mut chars = sql.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (None, '-') if chars.peek() == Some(&'-') => {
+                // The comment runs to the end of the line; the newline still separates the words around it
+                while chars.next_if(|&c| c != '\n').is_some() {}
+                continue;
+            }
+            (None, '\'' | '"') => quote = Some(c),
+            (Some(q), _) if c == q => quote = None,
+            (None, ';') => { statements.push(std::mem::take(&mut current)); continue; }
+            _ => {}
+        }
+        current.push(c);
+    }
+    statements.push(current);
+    statements.into_iter().map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect()
+}
+
+/// The per-user cache folder spill files go to by default; DuckDB's own default is relative to wherever the app was started.
+pub fn default_spill_directory() -> Option<PathBuf> {
+    let var = |name: &str| std::env::var_os(name).filter(|v| !v.is_empty()).map(PathBuf::from);
+    let base = if cfg!(windows) { var("LOCALAPPDATA") }
+        else if cfg!(target_os = "macos") { var("HOME").map(|h| h.join("Library").join("Caches")) }
+        else { var("XDG_CACHE_HOME").or_else(|| var("HOME").map(|h| h.join(".cache"))) };
+    base.map(|b| b.join("ParquetGrip").join("spill"))
+}
+
+/// Deletes the DuckDB spill files in `dir` not modified for `age`, returning how many went.
+fn remove_stale_spill_files(dir: &Path, age: std::time::Duration) -> Result<usize, String> {
+    let entries = std::fs::read_dir(dir).map_err(|e| e.to_string())?;
+    let stale = |e: &std::fs::DirEntry| e.metadata().and_then(|m| m.modified()).ok().and_then(|t| t.elapsed().ok()).is_some_and(|idle| idle >= age);
+    let leftovers = entries.filter_map(|e| e.ok()).filter(|e| e.file_name().to_string_lossy().starts_with("duckdb_temp_") && stale(e));
+    Ok(leftovers.filter(|e| std::fs::remove_file(e.path()).is_ok()).count())
+}
+
+/// Free bytes on the disk holding `dir`, or its nearest existing parent.
+pub fn free_space(dir: &Path) -> Option<u64> {
+    let dir = dir.ancestors().find(|d| d.exists())?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+        let c_path = std::ffi::CString::new(dir.as_os_str().as_bytes()).ok()?;
+        let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+        if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 { return None; }
+        // The field types differ between platforms
+        #[allow(clippy::unnecessary_cast)]
+        let free = stat.f_bavail as u64 * stat.f_frsize as u64;
+        Some(free)
+    }
+    #[cfg(windows)]
+    {
+        use std::os::windows::ffi::OsStrExt;
+        #[link(name = "kernel32")]
+        extern "system" { fn GetDiskFreeSpaceExW(dir: *const u16, free_to_caller: *mut u64, total: *mut u64, total_free: *mut u64) -> i32; }
+        let wide: Vec<u16> = dir.as_os_str().encode_wide().chain(Some(0)).collect();
+        let mut free = 0u64;
+        (unsafe { GetDiskFreeSpaceExW(wide.as_ptr(), &mut free, std::ptr::null_mut(), std::ptr::null_mut()) } != 0).then_some(free)
+    }
+    #[cfg(not(any(unix, windows)))]
+    { let _ = dir; None }
+}
+
+/// Memory the app's process holds in RAM, where the platform reports it.
+pub fn resident_memory() -> Option<u64> {
+    #[cfg(target_os = "linux")]
+    {
+        // The second field is resident pages
+        let statm = std::fs::read_to_string("/proc/self/statm").ok()?;
+        let pages: u64 = statm.split_whitespace().nth(1)?.parse().ok()?;
+        let page_size = u64::try_from(unsafe { libc::sysconf(libc::_SC_PAGESIZE) }).ok()?;
+        Some(pages * page_size)
+    }
+    #[cfg(target_os = "macos")]
+    {
+        let mut info: libc::proc_taskinfo = unsafe { std::mem::zeroed() };
+        let size = std::mem::size_of::<libc::proc_taskinfo>() as libc::c_int;
+        let got = unsafe { libc::proc_pidinfo(libc::getpid(), libc::PROC_PIDTASKINFO, 0, &mut info as *mut _ as *mut libc::c_void, size) };
+        (got == size).then_some(info.pti_resident_size)
+    }
+    #[cfg(windows)]
+    {
+        #[repr(C)]
+        #[derive(Default)]
+        struct Counters { cb: u32, page_faults: u32, peak_working_set: usize, working_set: usize, quota_peak_paged: usize, quota_paged: usize, quota_peak_nonpaged: usize, quota_nonpaged: usize, pagefile: usize, peak_pagefile: usize }
+        #[link(name = "kernel32")]
+        extern "system" { fn GetCurrentProcess() -> *mut std::ffi::c_void; fn K32GetProcessMemoryInfo(process: *mut std::ffi::c_void, counters: *mut Counters, cb: u32) -> i32; }
+        let mut counters = Counters { cb: std::mem::size_of::<Counters>() as u32, ..Default::default() };
+        (unsafe { K32GetProcessMemoryInfo(GetCurrentProcess(), &mut counters, counters.cb) } != 0).then_some(counters.working_set as u64)
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+    { None }
 }
 
 #[derive(Clone)]
 pub struct Backend {
     conn: Arc<Mutex<Option<Connection>>>,
+    /// One connection per open file, so a slow query in one tab doesn't hold up the others. The interrupt
+    /// handle reaches a query that is holding the connection's lock.
+    path_conns: Arc<Mutex<HashMap<String, PathConn>>>,
+    job_conns: JobConns,
+    next_job_conn: Arc<AtomicU64>,
+    /// Files loaded into an in-memory table, mapped to the table's name; grid queries read the table instead.
+    memory_tables: Arc<Mutex<HashMap<String, String>>>,
+    /// The view created over each opened file, so queries name it instead of repeating the file path.
+    views: Arc<Mutex<HashMap<String, String>>>,
+    /// Merged datasets read with `union_by_name`, filling columns a file lacks with NULLs.
+    union_by_name: Arc<Mutex<HashSet<String>>>,
+    /// Numbers the views and tables; never reused, since a closed tab's objects are dropped in the background.
+    next_object: Arc<AtomicU64>,
+    engine: Arc<Mutex<EngineConfig>>,
+    /// Grid queries running longer than this many seconds are interrupted; 0 means no limit.
+    query_timeout: Arc<AtomicU64>,
+    /// Text longer than this many characters comes back from page queries cut short; 0 fetches it whole.
+    cell_fetch_chars: Arc<AtomicU64>,
+    /// Most rows a query held in memory may return.
+    row_cap: Arc<AtomicU64>,
+    /// Set while the startup warm-up is opening the connection; anything else asking for one waits.
+    warming: Arc<(Mutex<bool>, Condvar)>,
+    /// What went wrong during the startup warm-up, kept for Help > About.
+    warm_up_problems: Arc<Mutex<Vec<String>>>,
+    /// Each open file's columns from `get_schema`, which decide the text columns the fetch cap applies to.
+    schemas: Arc<Mutex<HashMap<String, Vec<SchemaField>>>>,
+    /// Schemas and row counts of files seen before, least recently used first; kept in the app's storage.
+    file_facts: Arc<Mutex<VecDeque<FileFacts>>>,
+    /// Job timings for Help > Performance.
+    perf: Arc<PerfLog>,
+    /// Where `Progress` messages for slow grid queries go, once the UI has set it.
+    progress: Arc<Mutex<Option<mpsc::Sender<BackendMessage>>>>,
+    pool: Arc<WorkerPool>,
+    watchdog: Arc<Watchdog>,
 }
 
-impl Backend {
-    pub fn new() -> Self {
-        Self {
-            conn: Arc::new(Mutex::new(None)),
+impl Backend {
+    pub fn new() -> Self {
+        Self {
+            conn: Arc::new(Mutex::new(None)),
+            path_conns: Arc::new(Mutex::new(HashMap::new())),
+            job_conns: Arc::new(Mutex::new(HashMap::new())),
+            next_job_conn: Arc::new(AtomicU64::new(1)),
+            memory_tables: Arc::new(Mutex::new(HashMap::new())),
+            views: Arc::new(Mutex::new(HashMap::new())),
+            union_by_name: Arc::new(Mutex::new(HashSet::new())),
+            next_object: Arc::new(AtomicU64::new(1)),
+            engine: Arc::new(Mutex::new(EngineConfig::default())),
+            query_timeout: Arc::new(AtomicU64::new(0)),
+            cell_fetch_chars: Arc::new(AtomicU64::new(0)),
+            row_cap: Arc::new(AtomicU64::new(DEFAULT_ROW_CAP as u64)),
+            warming: Arc::new((Mutex::new(false), Condvar::new())),
+            warm_up_problems: Arc::new(Mutex::new(Vec::new())),
+            schemas: Arc::new(Mutex::new(HashMap::new())),
+            file_facts: Arc::new(Mutex::new(VecDeque::new())),
+            perf: Arc::new(PerfLog::default()),
+            progress: Arc::new(Mutex::new(None)),
+            pool: Arc::new(WorkerPool::default()),
+            watchdog: Arc::new(Watchdog::default()),
+        }
+    }
+
+    /// Runs `job` for the file at `path` on a worker thread. A job with a `kind` replaces a queued one of the
+    /// same kind for the same file that hasn't started, e.g. an older page request. Every job goes through
+    /// here, so recording for the performance panel covers them all.
+    pub fn submit(&self, path: &str, kind: Option<&'static str>, job: impl FnOnce() + Send + 'static) {
+        match self.perf_ticket(path, kind.unwrap_or("job")) {
+            Some(ticket) => self.pool.submit(path, kind, Box::new(move || ticket.run(job))),
+            None => self.pool.submit(path, kind, Box::new(job)),
+        }
+    }
+
+    /// Runs `job` on a thread of its own, for exports and other long jobs that shouldn't hold up a worker the
+    /// grid needs. It is recorded for the performance panel like a submitted job.
+    pub fn spawn(&self, path: &str, kind: &'static str, job: impl FnOnce() + Send + 'static) {
+        let ticket = self.perf_ticket(path, kind);
+        std::thread::spawn(move || match ticket { Some(ticket) => ticket.run(job), None => job() });
+    }
+
+    fn perf_ticket(&self, path: &str, kind: &'static str) -> Option<PerfTicket> {
+        if !self.perf.recording.load(Ordering::Relaxed) { return None; }
+        let record = PerfRecord { kind, path: path.to_string(), sql: String::new(), started: std::time::SystemTime::now(), duration: std::time::Duration::ZERO, rows: None, failure: None };
+        Some(PerfTicket { log: self.perf.clone(), record: Some(record) })
+    }
+
+    /// Starts or pauses recording jobs for the performance panel.
+    pub fn set_perf_recording(&self, on: bool) {
+        self.perf.recording.store(on, Ordering::Relaxed);
+    }
+
+    pub fn perf_recording(&self) -> bool {
+        self.perf.recording.load(Ordering::Relaxed)
+    }
+
+    /// Recorded jobs, oldest first.
+    pub fn perf_records(&self) -> Vec<PerfRecord> {
+        self.perf.records.lock().map(|r| r.iter().cloned().collect()).unwrap_or_default()
+    }
+
+    pub fn clear_perf_records(&self) {
+        if let Ok(mut r) = self.perf.records.lock() { r.clear(); }
+    }
+
+    /// Drops queued jobs, interrupts running queries and joins the workers, so quitting never waits on a scan.
+    pub fn shutdown(&self) {
+        self.pool.shutdown(|| {
+            if let Ok(conns) = self.path_conns.lock() {
+                for (_, interrupt) in conns.values() { interrupt.interrupt(); }
+            }
+            self.stop_jobs(None);
+        });
+    }
+
+    /// Interrupts the queries jobs run on their own connections, for `path` or for every path.
+    fn stop_jobs(&self, path: Option<&str>) {
+        let Ok(conns) = self.job_conns.lock() else { return };
+        for (_, interrupt, stopped) in conns.values().filter(|(p, _, _)| path.is_none_or(|path| p == path)) {
+            stopped.store(true, Ordering::Relaxed);
+            interrupt.interrupt();
+        }
+    }
+
+    /// The connection queries against `path` run on, created on first use. Every connection is a clone
+    /// of the same in-memory database, so loaded extensions, secrets and global settings are shared.
+    fn path_conn(&self, path: &str) -> Result<Arc<Mutex<Connection>>, String> {
+        let mut conns = self.path_conns.lock().map_err(|e| e.to_string())?;
+        if let Some((c, _)) = conns.get(path) {
+            return Ok(c.clone());
+        }
+        let conn = self.clone_conn()?;
+        let interrupt = conn.interrupt_handle();
+        let conn = Arc::new(Mutex::new(conn));
+        conns.insert(path.to_string(), (conn.clone(), interrupt));
+        Ok(conn)
+    }
+
+    /// Fails a job that waited for the connection while its tab was closed, instead of running it anyway.
+    fn check_open(&self, path: &str, conn: &Arc<Mutex<Connection>>) -> Result<(), String> {
+        let conns = self.path_conns.lock().map_err(|e| e.to_string())?;
+        if conns.get(path).is_some_and(|(c, _)| Arc::ptr_eq(c, conn)) { Ok(()) } else { Err(closed_error(path)) }
+    }
+
+    /// Stops the queries running for `path`, on its connection and on the ones its jobs opened for themselves,
+    /// which then fail as interrupted; the tab stays open.
+    pub fn interrupt(&self, path: &str) {
+        if let Some((_, interrupt)) = self.path_conns.lock().ok().and_then(|c| c.get(path).cloned()) { interrupt.interrupt(); }
+        let Ok(conns) = self.job_conns.lock() else { return };
+        for (_, interrupt, _) in conns.values().filter(|(p, _, _)| p == path) { interrupt.interrupt(); }
+    }
+
+    /// The remembered schemas and row counts, for saving with the session.
+    pub fn file_facts(&self) -> Vec<FileFacts> {
+        self.file_facts.lock().map(|f| f.iter().cloned().collect()).unwrap_or_default()
+    }
+
+    pub fn set_file_facts(&self, facts: Vec<FileFacts>) {
+        if let Ok(mut f) = self.file_facts.lock() { *f = facts.into_iter().rev().take(FILE_FACTS_KEPT).rev().collect(); }
+    }
+
+    /// Forgets what was remembered about `path`, so its next schema and count come from DuckDB.
+    pub fn forget_file_facts(&self, path: &str) {
+        let Some((canonical, _, _)) = FileFacts::stamp(path) else { return };
+        if let Ok(mut f) = self.file_facts.lock() { f.retain(|e| e.path != canonical); }
+    }
+
+    /// The remembered facts for `path` if the file is unchanged since, marked as just used.
+    fn cached_facts(&self, path: &str) -> Option<FileFacts> {
+        let (canonical, modified, size) = FileFacts::stamp(path)?;
+        let mut facts = self.file_facts.lock().ok()?;
+        let i = facts.iter().position(|e| e.path == canonical && e.modified == modified && e.size == size)?;
+        let entry = facts.remove(i)?;
+        facts.push_back(entry.clone());
+        Some(entry)
+    }
+
+    /// Records a schema or row count just read for `path`, replacing whatever an older version left.
+    fn remember_facts(&self, path: &str, update: impl FnOnce(&mut FileFacts)) {
+        let Some((canonical, modified, size)) = FileFacts::stamp(path) else { return };
+        let Ok(mut facts) = self.file_facts.lock() else { return };
+        let mut entry = match facts.iter().position(|e| e.path == canonical).and_then(|i| facts.remove(i)) {
+            Some(e) if e.modified == modified && e.size == size => e,
+            _ => FileFacts { path: canonical, modified, size, fields: None, rows: None },
+        };
+        update(&mut entry);
+        facts.push_back(entry);
+        while facts.len() > FILE_FACTS_KEPT { facts.pop_front(); }
+    }
+
+    /// Releases everything held for a closed tab: queued jobs are dropped and queries still running on its
+    /// connection or on its jobs' own connections are interrupted, so the workers are free again right away.
+    pub fn close_path(&self, path: &str) {
+        self.pool.cancel_path(path);
+        if let Ok(mut conns) = self.path_conns.lock() {
+            if let Some((_, interrupt)) = conns.remove(path) { interrupt.interrupt(); }
+        }
+        self.stop_jobs(Some(path));
+        let table = self.memory_tables.lock().ok().and_then(|mut t| t.remove(path));
+        let view = self.views.lock().ok().and_then(|mut v| v.remove(path));
+        if let Ok(mut s) = self.union_by_name.lock() { s.remove(path); }
+        if let Ok(mut schemas) = self.schemas.lock() { schemas.remove(path); }
+        if let Ok(conn) = self.job_conn(path) {
+            self.pool.submit(path, None, Box::new(move || {
+                if let Some(table) = table { let _ = conn.execute(&format!("DROP TABLE IF EXISTS {}", quote_ident(&table)), []); }
+                if let Some(view) = view { let _ = conn.execute(&format!("DROP VIEW IF EXISTS {}", quote_ident(&view)), []); }
+            }));
+        }
+    }
+
+    fn get_conn(&self) -> Result<Arc<Mutex<Option<Connection>>>, String> {
+        let (warming, done) = &*self.warming;
+        if let Ok(w) = warming.lock() { drop(done.wait_while(w, |w| *w)); }
+        self.open_conn()
+    }
+
+    fn open_conn(&self) -> Result<Arc<Mutex<Option<Connection>>>, String> {
+        let mut conn_guard = self.conn.lock().map_err(|e| e.to_string())?;
+        if conn_guard.is_none() {
+            match Connection::open_in_memory() {
+                Ok(c) => {
+                    c.execute_batch(&self.engine.lock().map_err(|e| e.to_string())?.global_sql()).map_err(|e| e.to_string())?;
+                    self.configure(&c)?;
+                    *conn_guard = Some(c)
+                }
+                Err(e) => return Err(e.to_string()),
+            }
+        }
+        Ok(self.conn.clone())
+    }
+
+    /// Runs the session SQL on a new connection; the database-wide settings were applied once when it opened.
+    fn configure(&self, conn: &Connection) -> Result<(), String> {
+        let sql = self.engine.lock().map_err(|e| e.to_string())?.session_sql();
+        if sql.is_empty() { return Ok(()); }
+        conn.execute_batch(&sql).map_err(|e| e.to_string())
+    }
+
+    /// Opens the shared connection with `config` applied and loads `extensions` on a background thread, so the
+    /// first file opened doesn't pay for it. Queries started meanwhile wait for it rather than racing it. An
+    /// invalid `config` is turned down before anything starts; later failures are sent to `report`.
+    pub fn warm_up(&self, config: EngineConfig, extensions: Vec<String>, report: mpsc::Sender<BackendMessage>) -> Result<(), String> {
+        if let Err(e) = config.validate() {
+            if let Ok(mut problems) = self.warm_up_problems.lock() { problems.push(format!("DuckDB settings: {}", e)); }
+            return Err(e);
+        }
+        *self.engine.lock().map_err(|e| e.to_string())? = config;
+        if let Ok(mut w) = self.warming.0.lock() { *w = true; }
+        let backend = self.clone();
+        std::thread::spawn(move || {
+            let fail = |message: String, sql: Option<String>| {
+                if let Ok(mut problems) = backend.warm_up_problems.lock() { problems.push(message.clone()); }
+                let _ = report.send(BackendMessage::Error { request: None, path: None, message, sql });
+            };
+            if let Err(e) = backend.open_conn() {
+                // Most likely the session SQL; the defaults still get the app working
+                fail(format!("Starting DuckDB with the configured settings: {}", e), None);
+                if let Ok(mut engine) = backend.engine.lock() { *engine = EngineConfig::default(); }
+                if let Err(e) = backend.open_conn() { fail(format!("Starting DuckDB: {}", e), None); }
+            }
+            for ext in extensions {
+                let sql = format!("INSTALL {0}; LOAD {0};", ext);
+                if !ext.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') { fail(format!("\"{}\" isn't an extension name", ext), None); continue; }
+                let loaded = backend.open_conn().and_then(|c| c.lock().map_err(|e| e.to_string())?.as_ref().ok_or("No connection")?.execute_batch(&sql).map_err(|e| e.to_string()));
+                match loaded {
+                    Ok(()) => { let _ = report.send(BackendMessage::SqlLog { path: "Startup".to_string(), sql }); }
+                    Err(e) => fail(format!("Preloading extension {}: {}", ext, e), Some(sql)),
+                }
+            }
+            let (warming, done) = &*backend.warming;
+            if let Ok(mut w) = warming.lock() { *w = false; }
+            done.notify_all();
+        });
+        Ok(())
+    }
+
+    /// The failures the startup warm-up reported, oldest first.
+    pub fn warm_up_problems(&self) -> Vec<String> {
+        self.warm_up_problems.lock().map(|p| p.clone()).unwrap_or_default()
+    }
+
+    /// Validates and applies DuckDB resource settings. The database-wide ones reach every connection at once;
+    /// the session SQL runs on each open file's connection once its current query is done.
+    pub fn set_engine_config(&self, config: EngineConfig) -> Result<(), String> {
+        config.validate()?;
+        let (global, session) = (config.global_sql(), config.session_sql());
+        let conn_arc = self.get_conn()?;
+        let conn_guard = conn_arc.lock().map_err(|e| e.to_string())?;
+        let conn = conn_guard.as_ref().ok_or("No connection")?;
+        // The session SQL is tried here first so a statement DuckDB rejects turns the settings down
+        let mut engine = self.engine.lock().map_err(|e| e.to_string())?;
+        if let Err(e) = conn.execute_batch(&format!("{}{}", global, session)) {
+            let _ = conn.execute_batch(&engine.global_sql());
+            return Err(e.to_string());
+        }
+        *engine = config;
+        drop(engine);
+        if session.is_empty() { return Ok(()); }
+        let conns: Vec<_> = self.path_conns.lock().map(|c| c.iter().map(|(p, (c, _))| (p.clone(), c.clone())).collect()).unwrap_or_default();
+        for (path, conn) in conns {
+            let sql = session.clone();
+            self.pool.submit(&path, None, Box::new(move || { if let Ok(c) = conn.lock() { let _ = c.execute_batch(&sql); } }));
+        }
+        Ok(())
+    }
+
+    pub fn spill_directory(&self) -> Option<PathBuf> {
+        self.engine.lock().ok()?.spill_directory()
+    }
+
+    /// Creates the spill directory and deletes the spill files a crashed session left in it, returning how many.
+    /// DuckDB removes its own on a clean exit.
+    pub fn prepare_spill_directory(&self) -> Result<usize, String> {
+        let Some(dir) = self.spill_directory() else { return Ok(0) };
+        std::fs::create_dir_all(&dir).map_err(|e| format!("Creating temp directory {}: {}", dir.display(), e))?;
+        remove_stale_spill_files(&dir, STALE_SPILL_AGE)
+    }
+
+    /// Points a query that ran out of room to spill at the temp directory setting.
+    fn spill_hint(&self, raw: String) -> String {
+        const SIGNATURES: [&str; 3] = ["No space left on device", "not enough space on the disk", "max_temp_directory_size"];
+        if !SIGNATURES.iter().any(|s| raw.contains(s)) { return raw; }
+        let dir = self.spill_directory().map_or_else(|| "the temp directory".to_string(), |d| d.display().to_string());
+        format!("Ran out of disk space spilling to {}. Free some space there, or pick a temp directory on a bigger disk under File > Settings.{}{}", dir, ERROR_DETAILS_SEPARATOR, raw)
+    }
+
+    fn get_read_func(path: &str) -> &'static str {
+        let path_lower = path.to_lowercase();
+        if path_lower.ends_with(".parquet") || path_lower.ends_with(".pqt") {
+            "read_parquet"
+        } else if path_lower.ends_with(".csv") {
+            "read_csv_auto"
+        } else if path_lower.ends_with(".json") || path_lower.ends_with(".json.gz") {
+            "read_json_auto"
+        } else {
+            // Default or fallback
+            "read_parquet"
+        }
+    }
+
+    pub fn set_query_timeout(&self, secs: u64) {
+        self.query_timeout.store(secs, Ordering::Relaxed);
+    }
+
+    pub fn set_cell_fetch_chars(&self, chars: usize) {
+        self.cell_fetch_chars.store(chars as u64, Ordering::Relaxed);
+    }
+
+    /// Clamped to `MAX_ROW_CAP`, whatever the setting says.
+    pub fn set_row_cap(&self, rows: usize) {
+        self.row_cap.store(rows.clamp(1, MAX_ROW_CAP) as u64, Ordering::Relaxed);
+    }
+
+    pub fn row_cap(&self) -> usize {
+        self.row_cap.load(Ordering::Relaxed) as usize
+    }
+
+    /// A select list that cuts text longer than `chars` short, ending it with its full length, and reads
+    /// blobs as just their size, which is all the grid shows of them. A `chars` of 0 leaves both whole.
+    /// UHUGEINT comes back as text either way, since Arrow carries it as a signed 128-bit decimal and
+    /// values past `i128::MAX` would show as negative. `None` when no column needs it.
+    fn capped_projection(fields: &[SchemaField], chars: u64) -> Option<String> {
+        let mut capped = false;
+        let items: Vec<String> = fields.iter().map(|f| {
+            let col = quote_ident(&f.name);
+            match f.data_type.as_str() {
+                "UHUGEINT" => { capped = true; format!("CAST({c} AS VARCHAR) AS {c}", c = col) }
+                _ if chars == 0 => col,
+                "VARCHAR" | "JSON" => {
+                    capped = true;
+                    format!("CASE WHEN length({c}) > {n} THEN left({c}, {n}) || '{m}' || length({c}) || ' chars]' ELSE CAST({c} AS VARCHAR) END AS {c}", c = col, n = chars, m = CAPPED_MARKER)
+                }
+                "BLOB" => { capped = true; format!("'<blob ' || octet_length({c}) || ' bytes>' AS {c}", c = col) }
+                _ => col,
+            }
+        }).collect();
+        capped.then(|| items.join(", "))
+    }
+
+    pub fn set_progress_sink(&self, tx: mpsc::Sender<BackendMessage>) {
+        if let Ok(mut progress) = self.progress.lock() { *progress = Some(tx); }
+    }
+
+    /// Runs a grid query on `path`'s connection, reporting progress while it is slow and interrupting it once
+    /// it passes the query timeout. A query that was interrupted always fails, since Arrow results stop early
+    /// rather than erroring.
+    fn timed<T>(&self, request: RequestId, path: &str, query: impl FnOnce() -> Result<T, String>) -> Result<T, String> {
+        let secs = self.query_timeout.load(Ordering::Relaxed);
+        let handle = self.path_conns.lock().ok().and_then(|c| c.get(path).map(|(_, h)| h.clone()));
+        let progress = self.progress.lock().ok().and_then(|p| p.clone());
+        let Some(handle) = handle.filter(|_| secs > 0 || progress.is_some()) else { return query().map_err(|e| { note_failure(&e); self.spill_hint(e) }) };
+        let limit = (secs > 0).then(|| std::time::Duration::from_secs(secs));
+        let id = self.watchdog.watch(Watched { request, path: path.to_string(), started: std::time::Instant::now(), limit, handle, progress, timed_out: false });
+        let result = query();
+        // A query that got its answer just as the watchdog fired still succeeded; the stray interrupt is
+        // cleared when the connection's next query starts
+        if self.watchdog.finish(id) && result.is_err() {
+            note_failure("Timed out");
+            return Err(format!("Query timed out after {}s. Add a filter or use a smaller page size, or raise the timeout under View > Query timeout.", secs));
+        }
+        result.map_err(|e| { note_failure(&e); self.spill_hint(e) })
+    }
+
+    /// What queries on an open file read from: its in-memory table if it has one, otherwise its view, falling
+    /// back to reading the file directly.
+    fn source(&self, path: &str) -> String {
+        let named = self.memory_tables.lock().ok().and_then(|t| t.get(path).cloned()).or_else(|| self.views.lock().ok().and_then(|v| v.get(path).cloned()));
+        match named {
+            Some(name) => quote_ident(&name),
+            None => self.file_source(path),
+        }
+    }
+
+    /// The reader call for `path`; the one place a file path is put into SQL. A merged dataset passes all
+    /// its files to one reader as a list.
+    fn file_source(&self, path: &str) -> String {
+        match source_files(path).as_slice() {
+            [file] if dataset_dir(file).is_some() => format!("read_parquet({}, hive_partitioning = true)", quote_literal(file)),
+            [file] => format!("{}({})", Self::get_read_func(file), quote_literal(file)),
+            files => {
+                let options = if self.unions_by_name(path) { ", union_by_name = true" } else { "" };
+                format!("{}([{}]{})", Self::get_read_func(files[0]), files.iter().map(|f| quote_literal(f)).collect::<Vec<_>>().join(", "), options)
+            }
+        }
+    }
+
+    /// Whether the merged dataset at `path` was opened with its files' columns matched up by name.
+    fn unions_by_name(&self, path: &str) -> bool {
+        self.union_by_name.lock().is_ok_and(|s| s.contains(path))
+    }
+
+    /// Checks that the files of a merged dataset share one format and, unless matched by name, the same
+    /// columns, since DuckDB only reads the first file's schema up front and would fail partway through a scan instead.
+    fn check_mergeable(conn: &Connection, files: &[&str], by_name: bool) -> Result<(), String> {
+        let func = Self::get_read_func(files[0]);
+        if let Some(other) = files.iter().find(|f| Self::get_read_func(f) != func) {
+            return Err(format!("Can't merge {} with {}: they are different file formats", file_label(files[0]), file_label(other)));
+        }
+        // union_by_name reads every file's schema itself
+        if by_name { return Ok(()); }
+        let describe = |file: &str| -> Result<Vec<(String, String)>, String> {
+            let mut stmt = conn.prepare(&format!("DESCRIBE SELECT * FROM {}({})", func, quote_literal(file))).map_err(|e| format!("{}: {}", file_label(file), e))?;
+            let rows = stmt.query_map([], |r| Ok((r.get::<_, String>(0)?, r.get::<_, String>(1)?))).map_err(|e| e.to_string())?;
+            rows.collect::<Result<_, _>>().map_err(|e| e.to_string())
+        };
+        let first = describe(files[0])?;
+        for file in &files[1..] {
+            let schema = describe(file)?;
+            let mismatch = first.iter().zip(&schema).find(|(a, b)| a != b).map(|((name, ty), (other_name, other_ty))| {
+                if name == other_name { format!("column \"{}\" is {} in {} but {} in {}", name, ty, file_label(files[0]), other_ty, file_label(file)) }
+                else { format!("{} has column \"{}\" where {} has \"{}\"", file_label(file), other_name, file_label(files[0]), name) }
+            });
+            let mismatch = mismatch.or_else(|| (first.len() != schema.len()).then(|| format!("{} has {} columns but {} has {}", file_label(files[0]), first.len(), file_label(file), schema.len())));
+            if let Some(m) = mismatch { return Err(format!("These files can't be merged into one table: {}", m)); }
         }
+        Ok(())
     }
 
-    fn get_conn(&self) -> Result<Arc<Mutex<Option<Connection>>>, String> {
-        let mut conn_guard = self.conn.lock().map_err(|e| e.to_string())?;
-        if conn_guard.is_none() {
-            match Connection::open_in_memory() {
-                Ok(c) => *conn_guard = Some(c),
-                Err(e) => return Err(e.to_string()),
+    fn object_name(&self, prefix: &str) -> String {
+        format!("{}_{}", prefix, self.next_object.fetch_add(1, Ordering::Relaxed))
+    }
+
+    /// Copies the whole file into an in-memory table so later filters and sorts don't re-read it. Files bigger
+    /// on disk than DuckDB's memory limit are refused up front; running out of memory midway is reported too.
+    pub fn cache_in_memory(&self, path: &str) -> Result<BackendMessage, String> {
+        let conn_arc = self.path_conn(path)?;
+        let conn = conn_arc.lock().map_err(|e| e.to_string())?;
+        self.check_open(path, &conn_arc)?;
+
+        let limit: String = conn.query_row("SELECT current_setting('memory_limit')", [], |r| r.get(0)).map_err(|e| e.to_string())?;
+        let on_disk = source_files(path).iter().map(|f| std::fs::metadata(f).map(|m| m.len())).sum::<std::io::Result<u64>>();
+        if let (Ok(on_disk), Some(max)) = (on_disk, parse_size(&limit)) {
+            if on_disk > max {
+                return Err(format!("{} is too large to keep in memory ({} on disk, memory limit {})", path, crate::format::human_bytes(on_disk as f64), limit));
             }
         }
-        Ok(self.conn.clone())
+        let table = self.object_name("memory");
+        let sql = format!("CREATE OR REPLACE TABLE {} AS SELECT * FROM {}", quote_ident(&table), self.file_source(path));
+        // The tab may have been closed while the copy ran
+        note_sql(&sql);
+        if let Err(raw) = conn.execute(&sql, []).map_err(|e| e.to_string()).and_then(|_| self.check_open(path, &conn_arc)) {
+            note_failure(&raw);
+            let _ = conn.execute(&format!("DROP TABLE IF EXISTS {}", quote_ident(&table)), []);
+            return Err(if raw.contains("Out of Memory") { format!("Not enough memory to keep {} in memory; it is still read from disk.{}{}", path, ERROR_DETAILS_SEPARATOR, raw) } else { self.spill_hint(raw) });
+        }
+        if let Ok(mut t) = self.memory_tables.lock() { t.insert(path.to_string(), table); }
+        Ok(BackendMessage::MemoryCache { path: path.to_string(), cached: true, sql, error: None })
     }
 
-    fn get_read_func(path: &str) -> &'static str {
-        let path_lower = path.to_lowercase();
-        if path_lower.ends_with(".parquet") || path_lower.ends_with(".pqt") {
-            "read_parquet"
-        } else if path_lower.ends_with(".csv") {
-            "read_csv_auto"
-        } else if path_lower.ends_with(".json") || path_lower.ends_with(".json.gz") {
-            "read_json_auto"
-        } else {
-            // Default or fallback
-            "read_parquet"
-        }
+    /// Drops the file's in-memory table; queries go back to reading the file.
+    pub fn drop_memory_cache(&self, path: &str) -> Result<BackendMessage, String> {
+        let conn_arc = self.path_conn(path)?;
+        let conn = conn_arc.lock().map_err(|e| e.to_string())?;
+        let Some(table) = self.memory_tables.lock().map_err(|e| e.to_string())?.remove(path) else {
+            return Ok(BackendMessage::MemoryCache { path: path.to_string(), cached: false, sql: String::new(), error: None });
+        };
+        let sql = format!("DROP TABLE IF EXISTS {}", quote_ident(&table));
+        conn.execute(&sql, []).map_err(|e| e.to_string())?;
+        Ok(BackendMessage::MemoryCache { path: path.to_string(), cached: false, sql, error: None })
     }
 
-    pub fn open_file(&self, path: String) -> Result<BackendMessage, String> {
-        let conn_arc = self.get_conn()?;
-        let conn_guard = conn_arc.lock().map_err(|e| e.to_string())?;
-        let conn = conn_guard.as_ref().ok_or("No connection")?;
+    /// Creates the view queries on `path` read; `union_by_name` lines a merged dataset's files up by column name.
+    pub fn open_file(&self, path: String, union_by_name: bool) -> Result<BackendMessage, String> {
+        let conn_arc = self.path_conn(&path)?;
+        let conn = conn_arc.lock().map_err(|e| e.to_string())?;
+        self.check_open(&path, &conn_arc)?;
         
-        // Use a temporary check to see if we can read the file
-        let func = Self::get_read_func(&path);
-        let sql = format!("SELECT 1 FROM {}('{}') LIMIT 0;", func, path);
-        match conn.execute(&sql, []) {
-            Ok(_) => Ok(BackendMessage::FileOpened { path }),
-            Err(e) => Err(e.to_string()),
+        let files = source_files(&path);
+        if let Ok(mut s) = self.union_by_name.lock() {
+            if union_by_name { s.insert(path.clone()); } else { s.remove(&path); }
+        }
+        if files.len() > 1 { Self::check_mergeable(&conn, &files, union_by_name)?; }
+        // Creating the view binds the file, which is also the check that it can be read
+        let func = Self::get_read_func(files[0]);
+        let view = match self.views.lock().ok().and_then(|v| v.get(&path).cloned()) {
+            Some(view) => view,
+            None => self.object_name("tab"),
+        };
+        let sql = format!("CREATE OR REPLACE VIEW {} AS SELECT * FROM {}", quote_ident(&view), self.file_source(&path));
+        note_sql(&sql);
+        let mut result = conn.execute(&sql, []).map_err(|e| e.to_string());
+        // A file another program is still writing (say, over a Windows share) is locked only briefly
+        for attempt in 1..=OPEN_BUSY_ATTEMPTS {
+            if !result.as_ref().is_err_and(|e| is_sharing_violation(e)) { break; }
+            if let Some(tx) = self.progress.lock().ok().and_then(|p| p.clone()) { let _ = tx.send(BackendMessage::FileBusy { path: path.clone(), attempt, attempts: OPEN_BUSY_ATTEMPTS }); }
+            std::thread::sleep(OPEN_BUSY_DELAY);
+            self.check_open(&path, &conn_arc)?;
+            result = conn.execute(&sql, []).map_err(|e| e.to_string());
+        }
+        match result {
+            Ok(_) => {
+                if let Ok(mut v) = self.views.lock() { v.insert(path.clone(), view); }
+                Ok(BackendMessage::FileOpened { path })
+            }
+            Err(raw) if is_sharing_violation(&raw) => Err(format!("File is busy: another program still has it open. Try again once it's done writing.{}{}", ERROR_DETAILS_SEPARATOR, raw)),
+            Err(raw) => {
+                match (func, Self::corruption_hint(&raw)) {
+                    ("read_parquet", Some(hint)) => Err(format!("{}{}{}", hint, ERROR_DETAILS_SEPARATOR, raw)),
+                    _ => Err(raw),
+                }
+            }
         }
     }
 
+    /// DuckDB's parquet reader errors for files that are cut short or aren't parquet at all: its magic-byte and
+    /// footer checks. Other "Invalid Input Error"s are about the query or options, not the file.
+    fn corruption_hint(raw: &str) -> Option<&'static str> {
+        const SIGNATURES: [&str; 5] = ["No magic bytes found at end of file", "too small to be a Parquet file", "Footer length error in file", "Invalid footer length provided", "footer length stored in file is not equal"];
+        SIGNATURES.iter().any(|s| raw.contains(s)).then_some("File appears truncated or is not valid Parquet. Check that the download or copy finished and that the file really is Parquet.")
+    }
+
     pub fn get_schema(&self, path: String) -> Result<BackendMessage, String> {
-        let conn_arc = self.get_conn()?;
-        let conn_guard = conn_arc.lock().map_err(|e| e.to_string())?;
-        let conn = conn_guard.as_ref().ok_or("No connection")?;
+        let conn_arc = self.path_conn(&path)?;
+        let conn = conn_arc.lock().map_err(|e| e.to_string())?;
+        self.check_open(&path, &conn_arc)?;
         
-        let func = Self::get_read_func(&path);
-        let sql = format!("DESCRIBE SELECT * FROM {}('{}');", func, path);
+        if let Some(fields) = self.cached_facts(&path).and_then(|f| f.fields) {
+            if let Ok(mut schemas) = self.schemas.lock() { schemas.insert(path.clone(), fields.clone()); }
+            return Ok(BackendMessage::Schema { path, fields });
+        }
+        let sql = format!("DESCRIBE SELECT * FROM {}", self.source(&path));
+        note_sql(&sql);
         let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
         let mut rows = stmt.query([]).map_err(|e| e.to_string())?;
         
-        let mut names = Vec::new();
+        let mut fields = Vec::new();
         while let Some(row) = rows.next().map_err(|e| e.to_string())? {
-            // column_name is the first column in DESCRIBE output
-            names.push(row.get::<_, String>(0).unwrap_or_default());
+            // DESCRIBE columns: column_name, column_type, null, key, default, extra
+            let nullable = match row.get::<_, Option<String>>(2).ok().flatten().as_deref() { Some("YES") => Some(true), Some("NO") => Some(false), _ => None };
+            fields.push(SchemaField { name: row.get::<_, String>(0).unwrap_or_default(), data_type: row.get::<_, String>(1).unwrap_or_default(), nullable });
         }
-        Ok(BackendMessage::Schema { path, columns: names })
+        if let Ok(mut schemas) = self.schemas.lock() { schemas.insert(path.clone(), fields.clone()); }
+        self.remember_facts(&path, |f| f.fields = Some(fields.clone()));
+        note_rows(fields.len());
+        Ok(BackendMessage::Schema { path, fields })
+    }
+
+    /// The linked DuckDB library's version and source id, e.g. `v1.5.6 (069cc9f9b5)`.
+    pub fn duckdb_version(&self) -> Result<String, String> {
+        let conn = self.clone_conn()?;
+        conn.query_row("SELECT library_version, source_id FROM pragma_version()", [], |r| Ok(format!("{} ({})", r.get::<_, String>(0)?, r.get::<_, String>(1)?))).map_err(|e| e.to_string())
     }
 
-    pub fn get_row_count(&self, path: String, filter: Option<String>) -> Result<BackendMessage, String> {
+    /// Bytes DuckDB's buffers hold across every connection, and bytes spilled to the temp directory.
+    pub fn memory_usage(&self) -> Result<(u64, u64), String> {
+        // duckdb_memory() covers the whole database, so the shared connection answers without a clone of its own
         let conn_arc = self.get_conn()?;
         let conn_guard = conn_arc.lock().map_err(|e| e.to_string())?;
         let conn = conn_guard.as_ref().ok_or("No connection")?;
+        let sql = "SELECT COALESCE(SUM(memory_usage_bytes), 0)::BIGINT, COALESCE(SUM(temporary_storage_bytes), 0)::BIGINT FROM duckdb_memory()";
+        note_sql(sql);
+        let (held, spilled): (i64, i64) = conn.query_row(sql, [], |r| Ok((r.get(0)?, r.get(1)?))).map_err(|e| e.to_string())?;
+        Ok((held.max(0) as u64, spilled.max(0) as u64))
+    }
+
+    /// Row-group layout of a Parquet file; other formats have none and get an empty list.
+    pub fn get_row_groups(&self, path: String) -> Result<BackendMessage, String> {
+        // A merged dataset's row groups restart in every file, so it gets no map either
+        if Self::get_read_func(&path) != "read_parquet" || source_files(&path).len() > 1 {
+            return Ok(BackendMessage::RowGroups { path, groups: Vec::new(), sql: String::new() });
+        }
+        let conn = self.job_conn(&path)?;
+        // parquet_metadata has one row per column chunk; the row-group figures repeat on each
+        let sql = format!("SELECT row_group_id, any_value(row_group_num_rows), any_value(row_group_bytes) FROM parquet_metadata({}) GROUP BY row_group_id ORDER BY row_group_id", quote_literal(&path));
+        let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+        let mut rows = stmt.query([]).map_err(|e| e.to_string())?;
+        let (mut groups, mut first_row) = (Vec::new(), 0);
+        while let Some(row) = rows.next().map_err(|e| e.to_string())? {
+            let rows_in_group = row.get::<_, i64>(1).map_err(|e| e.to_string())?.max(0) as usize;
+            let bytes = row.get::<_, Option<i64>>(2).ok().flatten().unwrap_or(0).max(0) as u64;
+            groups.push(RowGroupInfo { first_row, rows: rows_in_group, bytes });
+            first_row += rows_in_group;
+        }
+        Ok(BackendMessage::RowGroups { path, groups, sql })
+    }
+
+    pub fn get_row_count(&self, request: RequestId, path: String, filter: Option<String>) -> Result<BackendMessage, String> {
+        let conn_arc = self.path_conn(&path)?;
+        let conn = conn_arc.lock().map_err(|e| e.to_string())?;
+        self.check_open(&path, &conn_arc)?;
         
-        let func = Self::get_read_func(&path);
-        let mut sql = format!("SELECT count(*) FROM {}('{}')", func, path);
+        // Parquet footers already store the row count, so only a filter or another format needs a scan
+        let files = source_files(&path);
+        let unfiltered = filter.as_deref().is_none_or(|f| f.trim().is_empty()) && self.memory_tables.lock().is_ok_and(|t| !t.contains_key(&path));
+        if let Some(count) = self.cached_facts(&path).and_then(|f| f.rows).filter(|_| unfiltered) {
+            return Ok(BackendMessage::RowCount { request, path, count, sql: "-- Row count remembered from an earlier load; the file is unchanged".to_string() });
+        }
+        let mut sql = if unfiltered && files.iter().all(|f| Self::get_read_func(f) == "read_parquet") {
+            let list = files.iter().map(|f| quote_literal(f)).collect::<Vec<_>>().join(", ");
+            format!("SELECT CAST(coalesce(sum(num_rows), 0) AS BIGINT) FROM parquet_file_metadata([{}])", list)
+        } else {
+            format!("SELECT count(*) FROM {}", self.source(&path))
+        };
         if let Some(f) = filter {
             if !f.trim().is_empty() {
                 sql.push_str(&format!(" WHERE {}", f));
             }
         }
         
-        let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
-        let mut rows = stmt.query([]).map_err(|e| e.to_string())?;
-        
-        if let Some(row) = rows.next().map_err(|e| e.to_string())? {
-            let count: i64 = row.get(0).map_err(|e| e.to_string())?;
-            return Ok(BackendMessage::RowCount { path, count: count as usize, sql });
-        }
-        Ok(BackendMessage::RowCount { path, count: 0, sql })
+        note_sql(&sql);
+        let count = self.timed(request, &path, || {
+            let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+            let mut rows = stmt.query([]).map_err(|e| e.to_string())?;
+            match rows.next().map_err(|e| e.to_string())? {
+                Some(row) => row.get::<_, i64>(0).map_err(|e| e.to_string()),
+                None => Ok(0),
+            }
+        })?;
+        if unfiltered { self.remember_facts(&path, |f| f.rows = Some(count as usize)); }
+        Ok(BackendMessage::RowCount { request, path, count: count as usize, sql })
     }
 
-    pub fn run_query(&self, path: String, filter: Option<String>, sort: Option<String>, limit: Option<usize>, offset: Option<usize>) -> Result<BackendMessage, String> {
-        let conn_arc = self.get_conn()?;
-        let conn_guard = conn_arc.lock().map_err(|e| e.to_string())?;
-        let conn = conn_guard.as_ref().ok_or("No connection")?;
-        
-        let func = Self::get_read_func(&path);
-        let mut query = format!("SELECT * FROM {}('{}')", func, path);
+    /// The filtered and sorted `SELECT *` shared by paging and export queries.
+    pub fn select_sql(source: &str, filter: Option<&str>, sort: Option<&str>) -> String {
+        Self::select_columns_sql(source, "*", filter, sort)
+    }
+
+    fn select_columns_sql(source: &str, projection: &str, filter: Option<&str>, sort: Option<&str>) -> String {
+        let mut query = format!("SELECT {} FROM {}", projection, source);
         
         if let Some(f) = filter {
             if !f.trim().is_empty() {
@@ -126,60 +1534,492 @@ impl Backend {
                 query.push_str(&format!(" ORDER BY {}", s));
             }
         }
+        query
+    }
 
-        if let Some(l) = limit {
-            query.push_str(&format!(" LIMIT {}", l));
-        }
-        if let Some(o) = offset {
-            query.push_str(&format!(" OFFSET {}", o));
+    /// A grid page, with long text cut short at the cell fetch cap.
+    /// With `chunks`, rows are sent there as they arrive so the grid can show them before the page is complete.
+    #[allow(clippy::too_many_arguments)]
+    pub fn run_query(&self, request: RequestId, path: String, filter: Option<String>, sort: Option<String>, limit: Option<usize>, offset: Option<usize>, chunks: Option<&mpsc::Sender<BackendMessage>>) -> Result<BackendMessage, String> {
+        self.page_query(request, path, filter, sort, limit, offset, self.cell_fetch_chars.load(Ordering::Relaxed), self.row_cap(), chunks)
+    }
+
+    /// The same page as `run_query` with every value whole, for copying it.
+    pub fn run_query_full(&self, request: RequestId, path: String, filter: Option<String>, sort: Option<String>, limit: Option<usize>, offset: Option<usize>) -> Result<BackendMessage, String> {
+        self.page_query(request, path, filter, sort, limit, offset, 0, self.row_cap(), None)
+    }
+
+    /// `run_query` with the row cap raised to `MAX_ROW_CAP`, for when the user asks for the rows it cut off.
+    #[allow(clippy::too_many_arguments)]
+    pub fn run_query_past_cap(&self, request: RequestId, path: String, filter: Option<String>, sort: Option<String>, limit: Option<usize>, offset: Option<usize>, chunks: Option<&mpsc::Sender<BackendMessage>>) -> Result<BackendMessage, String> {
+        self.page_query(request, path, filter, sort, limit, offset, self.cell_fetch_chars.load(Ordering::Relaxed), MAX_ROW_CAP, chunks)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn page_query(&self, request: RequestId, path: String, filter: Option<String>, sort: Option<String>, limit: Option<usize>, offset: Option<usize>, cap: u64, row_cap: usize, chunks: Option<&mpsc::Sender<BackendMessage>>) -> Result<BackendMessage, String> {
+        let conn_arc = self.path_conn(&path)?;
+        let conn = conn_arc.lock().map_err(|e| e.to_string())?;
+        self.check_open(&path, &conn_arc)?;
+        
+        let base = Self::select_sql(&self.source(&path), filter.as_deref(), sort.as_deref());
+        // LIMIT and OFFSET are parameters, so turning pages under one filter reuses a cached statement instead
+        // of parsing and planning it again. Its SQL names the file's view, which is never reused, so a reopened
+        // file can't hit a statement planned for the old one. A sorted page keeps them literal: DuckDB only
+        // turns ORDER BY plus LIMIT into a top-N for a constant limit, and a parameter would mean a full sort
+        let (mut query, mut query_log, params) = Self::paged_sql(base, sort.as_deref().is_some_and(|s| !s.trim().is_empty()), limit, offset);
+        // Capped in an outer query, so WHERE and ORDER BY still see the full values
+        let projection = self.schemas.lock().ok().and_then(|s| s.get(&path).and_then(|f| Self::capped_projection(f, cap)));
+        if let Some(projection) = projection {
+            query = format!("SELECT {} FROM ({}) AS page", projection, query);
+            query_log = format!("SELECT {} FROM ({}) AS page", projection, query_log);
         }
 
-        let mut stmt = match conn.prepare(&query) {
+        note_sql(&query_log);
+        let mut stmt = match conn.prepare_cached(&query) {
             Ok(s) => s,
             Err(e) => return Ok(BackendMessage::Error { 
+                request: Some(request),
                 path: Some(path), 
                 message: e.to_string(), 
-                sql: Some(query) 
+                sql: Some(query_log) 
             }),
         };
         
-        let mut rows = stmt.query([]).map_err(|e| e.to_string())?;
-        
-        let mut column_count = 0;
-        let mut result_rows = Vec::new();
-        let mut row_count = 0;
-        
         // Safety break
-        let max_rows = limit.unwrap_or(50_000);
-
-        while let Some(row) = rows.next().map_err(|e| e.to_string())? {
-            if row_count >= max_rows {
-                break;
-            }
-            
-            if column_count == 0 {
-                while row.get_ref(column_count).is_ok() {
-                    column_count += 1;
+        let max_rows = limit.unwrap_or(usize::MAX).min(row_cap);
+        let (schema, batches, mut truncated) = self.timed(request, &path, || {
+            let arrow = stmt.query_arrow(duckdb::params_from_iter(&params)).map_err(|e| e.to_string())?;
+            let schema = arrow.get_schema();
+            let mut batches = Vec::new();
+            let mut row_count = 0;
+            // Batches and rows already streamed, and when the last chunk went out
+            let (mut sent, mut sent_rows, mut sent_at) = (0, 0, std::time::Instant::now());
+            for batch in arrow {
+                if row_count >= max_rows {
+                    // A batch past the cap means there were more rows
+                    return Ok((schema, batches, true));
                 }
+                row_count += batch.num_rows();
+                batches.push(batch);
+                if let Some(tx) = chunks.filter(|_| row_count - sent_rows >= STREAM_CHUNK_ROWS || sent_at.elapsed() >= STREAM_CHUNK_INTERVAL) {
+                    let chunk = duckdb::arrow::compute::concat_batches(&schema, &batches[sent..]).map_err(|e| e.to_string())?;
+                    let chunk = chunk.slice(0, chunk.num_rows().min(max_rows - sent_rows));
+                    sent_rows += chunk.num_rows(); sent = batches.len(); sent_at = std::time::Instant::now();
+                    let columns = schema.fields().iter().map(|f| f.name().clone()).collect();
+                    let _ = tx.send(BackendMessage::QueryData { request, path: path.clone(), columns, batch: Arc::new(chunk), sql: String::new(), truncated: false, complete: false });
+                }
+            }
+            Ok((schema, batches, false))
+        })?;
+        let columns = schema.fields().iter().map(|f| f.name().clone()).collect();
+        let batch = duckdb::arrow::compute::concat_batches(&schema, &batches).map_err(|e| e.to_string())?;
+        truncated |= batch.num_rows() > max_rows;
+        let batch = batch.slice(0, batch.num_rows().min(max_rows));
+        note_rows(batch.num_rows());
+
+        Ok(BackendMessage::QueryData { request, path, columns, batch: Arc::new(batch), sql: query_log, truncated, complete: true })
+    }
+
+    /// `base` with the page's LIMIT and OFFSET: the query to run, the same with the values written out for the
+    /// log, and the values to bind. Only an unsorted page binds them.
+    fn paged_sql(base: String, sorted: bool, limit: Option<usize>, offset: Option<usize>) -> (String, String, Vec<i64>) {
+        let (mut query, mut query_log, mut params) = (base.clone(), base, Vec::new());
+        for (keyword, value) in [("LIMIT", limit), ("OFFSET", offset)] {
+            let Some(value) = value else { continue };
+            query_log.push_str(&format!(" {} {}", keyword, value));
+            if sorted { query.push_str(&format!(" {} {}", keyword, value)); } else { query.push_str(&format!(" {} ?", keyword)); params.push(value as i64); }
+        }
+        (query, query_log, params)
+    }
+
+    /// Fetches one cell for the detail popup; `value` is `None` when that row no longer exists.
+    pub fn get_cell(&self, path: String, filter: Option<String>, sort: Option<String>, row: usize, column: String) -> Result<BackendMessage, String> {
+        let value = self.fetch_cell(&path, filter.as_deref(), sort.as_deref(), row, &column)?;
+        Ok(BackendMessage::CellValue { path, row, column, value })
+    }
+
+    /// One cell by its global row index, whole whatever the cell fetch cap; `None` when that row no longer exists.
+    pub fn fetch_cell(&self, path: &str, filter: Option<&str>, sort: Option<&str>, row: usize, column: &str) -> Result<Option<String>, String> {
+        let conn_arc = self.path_conn(path)?;
+        let conn = conn_arc.lock().map_err(|e| e.to_string())?;
+        self.check_open(path, &conn_arc)?;
+
+        let field = self.schemas.lock().ok().and_then(|s| s.get(path)?.iter().find(|f| f.name == column).cloned());
+        let projection = field.and_then(|f| Self::capped_projection(&[f], 0)).unwrap_or_else(|| quote_ident(column));
+        let sql = format!("{} LIMIT 1 OFFSET {}", Self::select_columns_sql(&self.source(path), &projection, filter, sort), row);
+        note_sql(&sql);
+        Self::arrow_cell(&conn, &sql)
+    }
+
+    /// The first value `sql` returns, read through Arrow like the grid so zoned timestamps keep their offset.
+    fn arrow_cell(conn: &Connection, sql: &str) -> Result<Option<String>, String> {
+        let mut stmt = conn.prepare(sql).map_err(|e| e.to_string())?;
+        Ok(stmt.query_arrow([]).map_err(|e| e.to_string())?.find(|b| b.num_rows() > 0).map(|b| array_value_to_string(b.column(0), 0)))
+    }
+
+    /// Binds the filter and sort against the file without reading any rows.
+    pub fn validate_query(&self, path: &str, filter: Option<&str>, sort: Option<&str>) -> Result<(), String> {
+        let conn_arc = self.path_conn(path)?;
+        let conn = conn_arc.lock().map_err(|e| e.to_string())?;
+        self.check_open(path, &conn_arc)?;
+
+        let sql = format!("{} LIMIT 0", Self::select_sql(&self.source(path), filter, sort));
+        note_sql(&sql);
+        let mut stmt = conn.prepare(&sql).map_err(|e| { note_failure(&e.to_string()); e.to_string() })?;
+        stmt.query([]).map(|_| ()).map_err(|e| { note_failure(&e.to_string()); e.to_string() })
+    }
+
+    /// Every value of one column across the filtered/sorted result, typed so callers can tell numbers from text.
+    pub fn get_column_values(&self, path: String, filter: Option<String>, sort: Option<String>, column: String) -> Result<BackendMessage, String> {
+        // One row past the cap tells a result that fits from one that was cut off
+        let cap = self.row_cap();
+        let spec = QuerySpec { path: path.clone(), projection: Some(quote_ident(&column)), filter, sort, limit: Some(cap + 1), ..Default::default() };
+        let (_, rows) = self.run_query_typed(&spec)?;
+        let truncated = rows.len() > cap;
+        let values = rows.into_iter().take(cap).map(|mut r| r.pop().unwrap_or(Cell::Null)).collect();
+        Ok(BackendMessage::ColumnValues { path, column, values, sql: spec.sql(self), truncated })
+    }
+
+    /// Like `run_query` but keeps each value's type, for exporters and copy actions that write native types.
+    pub fn run_query_typed(&self, spec: &QuerySpec) -> Result<(Vec<String>, Vec<Vec<Cell>>), String> {
+        let mut collector = CellCollector { columns: Vec::new(), rows: Vec::new() };
+        self.stream_query(spec, &ExportCancel::default(), &mut collector)?;
+        Ok((collector.columns, collector.rows))
+    }
+
+    /// A fresh connection to the shared database, so long jobs never hold the lock the viewer and other jobs need.
+    fn clone_conn(&self) -> Result<Connection, String> {
+        let conn_arc = self.get_conn()?;
+        let conn_guard = conn_arc.lock().map_err(|e| e.to_string())?;
+        let conn = conn_guard.as_ref().ok_or("No connection")?.try_clone().map_err(|e| e.to_string())?;
+        self.configure(&conn)?;
+        Ok(conn)
+    }
+
+    /// `clone_conn` for a job working on `path`, which closing that tab or quitting interrupts.
+    fn job_conn(&self, path: &str) -> Result<JobConn, String> {
+        let conn = self.clone_conn()?;
+        let (id, stopped) = (self.next_job_conn.fetch_add(1, Ordering::Relaxed), Arc::new(AtomicBool::new(false)));
+        self.job_conns.lock().map_err(|e| e.to_string())?.insert(id, (path.to_string(), conn.interrupt_handle(), stopped.clone()));
+        Ok(JobConn { conn, id, stopped, registry: self.job_conns.clone() })
+    }
+
+    /// Runs `SUMMARIZE` over the filtered file on its own connection, so it works while the grid is busy.
+    pub fn get_profile(&self, path: &str, filter: Option<&str>, cancel: &ExportCancel) -> Result<ProfileReport, String> {
+        let conn = self.job_conn(path)?;
+        if let Ok(mut h) = cancel.interrupt.lock() { *h = Some(conn.interrupt_handle()); }
+        let cancelled = |e: duckdb::Error| if cancel.is_cancelled() { "Profile cancelled".to_string() } else if conn.stopped() { closed_error(path) } else { self.spill_hint(e.to_string()) };
+        let sql = format!("SUMMARIZE {}", Self::select_sql(&self.source(path), filter, None));
+        note_sql(&sql);
+        let mut stmt = conn.prepare(&sql).map_err(cancelled)?;
+        let mut rows = stmt.query([]).map_err(cancelled)?;
+        let columns = rows.as_ref().map(|s| s.column_names()).unwrap_or_default();
+        let count_col = columns.iter().position(|c| c == "count");
+        let (mut out, mut row_count) = (Vec::new(), 0);
+        while let Some(row) = rows.next().map_err(cancelled)? {
+            let values: Vec<Option<String>> = (0..columns.len()).map(|i| match row.get_ref(i) {
+                Ok(ValueRef::Null) | Err(_) => None,
+                Ok(v) => Some(value_ref_to_string(v)),
+            }).collect();
+            if let Some(n) = count_col.and_then(|i| values[i].as_deref()).and_then(|c| c.parse().ok()) { row_count = n; }
+            out.push(values);
+        }
+        note_rows(out.len());
+        Ok(ProfileReport { columns, rows: out, row_count, sql })
+    }
+
+    /// Writes the query straight to `target` with DuckDB's `COPY`, e.g. as Parquet. Returns the rows written.
+    pub fn copy_to(&self, spec: &QuerySpec, target: &str, options: &str, cancel: &ExportCancel) -> Result<usize, String> {
+        let conn = self.job_conn(&spec.path)?;
+        if let Ok(mut h) = cancel.interrupt.lock() { *h = Some(conn.interrupt_handle()); }
+        let sql = format!("COPY ({}) TO {} ({})", spec.sql(self), quote_literal(target), options);
+        note_sql(&sql);
+        let rows = conn.execute(&sql, []).map_err(|e| if cancel.is_cancelled() { "Export cancelled".to_string() } else if conn.stopped() { closed_error(&spec.path) } else { self.spill_hint(e.to_string()) })?;
+        note_rows(rows);
+        Ok(rows)
+    }
+
+    /// Streams the filtered/sorted result into `sink`, checking `cancel` between rows.
+    pub fn stream_query(&self, spec: &QuerySpec, cancel: &ExportCancel, sink: &mut dyn RowSink) -> Result<usize, String> {
+        let conn = self.job_conn(&spec.path)?;
+        if let Ok(mut h) = cancel.interrupt.lock() { *h = Some(conn.interrupt_handle()); }
+        let cancelled = |e: duckdb::Error| if cancel.is_cancelled() { "Export cancelled".to_string() } else if conn.stopped() { closed_error(&spec.path) } else { self.spill_hint(e.to_string()) };
+
+        let query = spec.sql(self);
+        note_sql(&query);
+        let mut stmt = conn.prepare(&query).map_err(cancelled)?;
+        let mut rows = stmt.query([]).map_err(cancelled)?;
+        let columns = rows.as_ref().map(|s| s.column_names()).unwrap_or_default();
+        // A ValueRef has no zone, so zoned timestamps reach the sinks as text already showing their offset
+        let zones: Vec<Option<String>> = (0..columns.len()).map(|i| match rows.as_ref().map(|s| s.column_type(i)) {
+            Some(DataType::Timestamp(_, Some(zone))) => Some(zone.to_string()),
+            _ => None,
+        }).collect();
+        let zoned = zones.iter().any(Option::is_some);
+        sink.begin(&columns)?;
+
+        let mut row_count = 0;
+        while let Some(row) = rows.next().map_err(cancelled)? {
+            if cancel.is_cancelled() {
+                return Err("Export cancelled".to_string());
             }
-            
-            let mut row_data = Vec::with_capacity(column_count);
-            for i in 0..column_count {
-                let val_ref = row.get_ref(i).unwrap();
-                row_data.push(value_ref_to_string(val_ref));
+            let values = (0..columns.len()).map(|i| row.get_ref(i)).collect::<Result<Vec<_>>>().map_err(|e| e.to_string())?;
+            if zoned {
+                let texts: Vec<Option<String>> = values.iter().zip(&zones).map(|(v, zone)| match (v, zone) {
+                    (ValueRef::Timestamp(u, t), Some(_)) => Some(timestamp_to_string(*u, *t, zone.as_deref())),
+                    _ => None,
+                }).collect();
+                let values: Vec<ValueRef> = values.into_iter().zip(&texts).map(|(v, text)| text.as_ref().map_or(v, |t| ValueRef::Text(t.as_bytes()))).collect();
+                sink.row(&values)?;
+            } else {
+                sink.row(&values)?;
             }
-            result_rows.push(row_data);
             row_count += 1;
         }
+        note_rows(row_count);
+        Ok(row_count)
+    }
+
+    /// Streams the filtered/sorted result into `on_batch` as Arrow batches, fetched as they are consumed and
+    /// starting with an empty one that carries the schema. Checks `cancel` between batches; returns the rows.
+    pub fn stream_batches(&self, spec: &QuerySpec, cancel: &ExportCancel, on_batch: &mut dyn FnMut(&RecordBatch) -> Result<(), String>) -> Result<usize, String> {
+        let conn = self.job_conn(&spec.path)?;
+        if let Ok(mut h) = cancel.interrupt.lock() { *h = Some(conn.interrupt_handle()); }
+        let cancelled = |e: duckdb::Error| if cancel.is_cancelled() { "Export cancelled".to_string() } else if conn.stopped() { closed_error(&spec.path) } else { self.spill_hint(e.to_string()) };
 
-        Ok(BackendMessage::QueryData { path, rows: result_rows, sql: query })
+        let query = spec.sql(self);
+        note_sql(&query);
+        let mut stmt = conn.prepare(&query).map_err(cancelled)?;
+        // The stream's own iterator panics on a failed fetch, so batches are stepped by hand
+        drop(stmt.stream_arrow([]).map_err(cancelled)?);
+        on_batch(&RecordBatch::new_empty(stmt.schema()))?;
+        let mut row_count = 0;
+        while let Some(batch) = stmt.step().map_err(cancelled)? {
+            if cancel.is_cancelled() {
+                return Err("Export cancelled".to_string());
+            }
+            let batch = RecordBatch::from(&batch);
+            row_count += batch.num_rows();
+            on_batch(&batch)?;
+        }
+        note_rows(row_count);
+        Ok(row_count)
     }
 }
 
 use duckdb::types::{ValueRef, TimeUnit};
-use chrono::{Utc, TimeZone, NaiveDate, Duration};
+use chrono::{Utc, TimeZone, NaiveDate, NaiveTime, FixedOffset, Duration};
+
+/// Joins the kept start of a capped cell to its full length, e.g. `abc… [52000 chars]`.
+const CAPPED_MARKER: &str = "… [";
+
+/// Whether a grid cell was cut short by the cell fetch cap.
+pub fn is_capped(cell: &str) -> bool {
+    cell.ends_with(" chars]") && cell.rfind(CAPPED_MARKER).is_some_and(|i| cell[i + CAPPED_MARKER.len()..cell.len() - " chars]".len()].bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// Quotes a column name for use as a SQL identifier.
+pub fn quote_ident(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+pub fn quote_literal(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "''"))
+}
+
+/// The path a merged dataset is opened and keyed under: its files, one per line.
+pub fn merged_path(files: &[String]) -> String {
+    files.join("\n")
+}
+
+/// Written next to a dataset's parts by Spark, Dask and Arrow, describing the whole dataset. Only their presence is
+/// checked; the parts themselves are read for the schema and row counts.
+const DATASET_MARKERS: [&str; 2] = ["_metadata", "_common_metadata"];
+
+/// The dataset folder a local Parquet file belongs to: the nearest one holding `_metadata` or `_common_metadata`,
+/// looking up through the hive-style `column=value` folders the file sits in.
+pub fn dataset_root(file: &str) -> Option<PathBuf> {
+    if Backend::get_read_func(file) != "read_parquet" || file.contains('\n') || dataset_dir(file).is_some() { return None; }
+    let mut dir = Path::new(file).parent()?;
+    loop {
+        if DATASET_MARKERS.iter().any(|m| dir.join(m).is_file()) { return Some(dir.to_path_buf()); }
+        if !dir.file_name()?.to_string_lossy().contains('=') { return None; }
+        dir = dir.parent()?;
+    }
+}
+
+/// A path reading every Parquet file under `root` as one table, its `column=value` folders becoming columns.
+pub fn dataset_path(root: &Path) -> String {
+    root.join("**").join("*.parquet").to_string_lossy().into_owned()
+}
+
+/// The folder a `dataset_path` reads, or `None` for any other path.
+pub fn dataset_dir(path: &str) -> Option<&Path> {
+    let glob = Path::new(path);
+    let parent = glob.parent()?;
+    (glob.file_name()? == "*.parquet" && parent.file_name()? == "**").then(|| parent.parent()).flatten()
+}
+
+/// The files behind a tab's path; more than one for a merged dataset.
+pub fn source_files(path: &str) -> Vec<&str> {
+    path.split('\n').collect()
+}
+
+/// How a job for `path` fails when its tab is closed before or while it runs.
+fn closed_error(path: &str) -> String {
+    format!("{} was closed", path)
+}
+
+/// Whether `message` is only a job for `path` stopping because its tab was closed: it was interrupted, or
+/// found the tab gone when its turn came.
+pub fn is_closed_error(path: &str, message: &str) -> bool {
+    message == closed_error(path) || message.starts_with("INTERRUPT Error")
+}
+
+/// Windows' "used by another process" (ERROR_SHARING_VIOLATION) and lock violation errors, which clear
+/// once the writer lets go; every other open error is final.
+fn is_sharing_violation(error: &str) -> bool {
+    const SIGNATURES: [&str; 5] = ["being used by another process", "sharing violation", "locked a portion of the file", "os error 32", "os error 33"];
+    let lower = error.to_lowercase();
+    SIGNATURES.iter().any(|s| lower.contains(s))
+}
+
+/// A file's name without its directory, for messages.
+fn file_label(file: &str) -> &str {
+    std::path::Path::new(file).file_name().and_then(|n| n.to_str()).unwrap_or(file)
+}
+
+/// Converts a DuckDB timestamp in the given unit into a UTC datetime.
+pub fn timestamp_to_datetime(u: TimeUnit, t: i64) -> Option<chrono::DateTime<Utc>> {
+    // Euclidean division keeps the sub-second part positive for times before 1970
+    let split = |per_second: i64| (t.div_euclid(per_second), (t.rem_euclid(per_second) * (1_000_000_000 / per_second)) as u32);
+    let (secs, nanos) = match u {
+        TimeUnit::Second => (t, 0),
+        TimeUnit::Millisecond => split(1000),
+        TimeUnit::Microsecond => split(1_000_000),
+        TimeUnit::Nanosecond => split(1_000_000_000),
+    };
+    let dt = Utc.timestamp_opt(secs, nanos);
+    match dt {
+        chrono::LocalResult::Single(dt) => Some(dt),
+        _ => None,
+    }
+}
+
+/// Converts a DuckDB `Date32` (days since the Unix epoch) into a calendar date.
+pub fn date32_to_date(d: i32) -> Option<NaiveDate> {
+    NaiveDate::from_ymd_opt(1970, 1, 1)?.checked_add_signed(Duration::days(d as i64))
+}
+
+/// Renders one cell of an Arrow column the same way `value_ref_to_string` renders the row-based value.
+pub fn array_value_to_string(array: &ArrayRef, row: usize) -> String {
+    if array.is_null(row) {
+        return "(null)".to_string();
+    }
+    let zone = match array.data_type() {
+        DataType::Timestamp(_, tz) => tz.as_deref(),
+        _ => None,
+    };
+    let timestamp = |unit: TimeUnit, t: i64| timestamp_to_string(unit, t, zone);
+    match array.data_type() {
+        DataType::Float32 => array.as_primitive::<arrow_types::Float32Type>().value(row).to_string(),
+        DataType::Float64 => array.as_primitive::<arrow_types::Float64Type>().value(row).to_string(),
+        DataType::Binary => format!("<blob {} bytes>", array.as_binary::<i32>().value(row).len()),
+        DataType::LargeBinary => format!("<blob {} bytes>", array.as_binary::<i64>().value(row).len()),
+        DataType::BinaryView => format!("<blob {} bytes>", array.as_binary_view().value(row).len()),
+        // Arrow's formatter drops digits past the declared precision, which HUGEINT (exported as DECIMAL(38, 0)) exceeds
+        DataType::Decimal128(_, scale) => decimal_to_string(array.as_primitive::<arrow_types::Decimal128Type>().value(row), *scale),
+        DataType::Date32 => {
+            let d = array.as_primitive::<arrow_types::Date32Type>().value(row);
+            match date32_to_date(d) {
+                Some(date) => date.format("%Y-%m-%d").to_string(),
+                None => format!("Date32({})", d),
+            }
+        }
+        DataType::Time32(arrow_types::TimeUnit::Second) => time_to_string(TimeUnit::Second, array.as_primitive::<arrow_types::Time32SecondType>().value(row) as i64),
+        DataType::Time32(arrow_types::TimeUnit::Millisecond) => time_to_string(TimeUnit::Millisecond, array.as_primitive::<arrow_types::Time32MillisecondType>().value(row) as i64),
+        DataType::Time64(arrow_types::TimeUnit::Microsecond) => time_to_string(TimeUnit::Microsecond, array.as_primitive::<arrow_types::Time64MicrosecondType>().value(row)),
+        DataType::Time64(arrow_types::TimeUnit::Nanosecond) => time_to_string(TimeUnit::Nanosecond, array.as_primitive::<arrow_types::Time64NanosecondType>().value(row)),
+        DataType::Timestamp(arrow_types::TimeUnit::Second, _) => timestamp(TimeUnit::Second, array.as_primitive::<arrow_types::TimestampSecondType>().value(row)),
+        DataType::Timestamp(arrow_types::TimeUnit::Millisecond, _) => timestamp(TimeUnit::Millisecond, array.as_primitive::<arrow_types::TimestampMillisecondType>().value(row)),
+        DataType::Timestamp(arrow_types::TimeUnit::Microsecond, _) => timestamp(TimeUnit::Microsecond, array.as_primitive::<arrow_types::TimestampMicrosecondType>().value(row)),
+        DataType::Timestamp(arrow_types::TimeUnit::Nanosecond, _) => timestamp(TimeUnit::Nanosecond, array.as_primitive::<arrow_types::TimestampNanosecondType>().value(row)),
+        // Integers, text, decimals, times and nested values all read fine with Arrow's own display
+        _ => match ArrayFormatter::try_new(array.as_ref(), &FormatOptions::default().with_null("(null)")) {
+            Ok(f) => f.value(row).to_string(),
+            Err(e) => format!("<{}>", e),
+        },
+    }
+}
+
+/// The one rendering of a timestamp shared by the Arrow and row-based paths; `zone` is the Arrow type's zone.
+fn timestamp_to_string(u: TimeUnit, t: i64, zone: Option<&str>) -> String {
+    match timestamp_to_datetime(u, t) {
+        Some(dt) => timestamp_with_zone(dt, zone),
+        None => format!("Timestamp({:?}, {})", u, t),
+    }
+}
+
+/// A zoned timestamp (DuckDB's TIMESTAMPTZ) is shown in its offset with the offset appended; named zones
+/// can't be resolved without a tz database, so those stay in UTC.
+fn timestamp_with_zone(dt: chrono::DateTime<Utc>, zone: Option<&str>) -> String {
+    match zone {
+        None => dt.format("%Y-%m-%d %H:%M:%S").to_string(),
+        Some(z) => match z.parse::<FixedOffset>() {
+            Ok(offset) => dt.with_timezone(&offset).format("%Y-%m-%d %H:%M:%S%:z").to_string(),
+            Err(_) => dt.format("%Y-%m-%d %H:%M:%S+00:00").to_string(),
+        },
+    }
+}
+
+/// Formats a time of day as `HH:MM:SS.ffffff`.
+pub fn time_to_string(u: TimeUnit, t: i64) -> String {
+    let nanos = match u {
+        TimeUnit::Second => t.checked_mul(1_000_000_000),
+        TimeUnit::Millisecond => t.checked_mul(1_000_000),
+        TimeUnit::Microsecond => t.checked_mul(1000),
+        TimeUnit::Nanosecond => Some(t),
+    };
+    let time = nanos.filter(|n| *n >= 0).and_then(|n| NaiveTime::from_num_seconds_from_midnight_opt((n / 1_000_000_000) as u32, (n % 1_000_000_000) as u32));
+    match time {
+        Some(time) => time.format("%H:%M:%S%.6f").to_string(),
+        None => format!("Time({:?}, {})", u, t),
+    }
+}
 
-fn value_ref_to_string(v: ValueRef<'_>) -> String {
+/// Accepts `4GB`, `4 gb` or `4 GB` alike by putting the unit in the form `parse_size` reads.
+fn normalize_size(s: &str) -> String {
+    let s = s.trim();
+    let split = s.find(|c: char| !(c.is_ascii_digit() || c == '.')).unwrap_or(s.len());
+    let (num, unit) = s.split_at(split);
+    let unit = unit.trim();
+    let unit = ["B", "KB", "MB", "GB", "TB", "KiB", "MiB", "GiB", "TiB"].into_iter().find(|u| u.eq_ignore_ascii_case(unit)).unwrap_or(unit);
+    format!("{} {}", num, if unit.is_empty() { "B" } else { unit })
+}
+
+/// Reads DuckDB's rendering of a byte size, e.g. `12.4 GiB`.
+fn parse_size(s: &str) -> Option<u64> {
+    let (num, unit) = s.trim().split_once(' ')?;
+    let scale: u64 = match unit {
+        "bytes" | "B" => 1,
+        "KiB" => 1 << 10, "MiB" => 1 << 20, "GiB" => 1 << 30, "TiB" => 1 << 40,
+        "KB" => 1_000, "MB" => 1_000_000, "GB" => 1_000_000_000, "TB" => 1_000_000_000_000,
+        _ => return None,
+    };
+    Some((num.parse::<f64>().ok()? * scale as f64) as u64)
+}
+
+fn decimal_to_string(v: i128, scale: i8) -> String {
+    if scale <= 0 {
+        return format!("{}{}", v, "0".repeat(scale.unsigned_abs() as usize));
+    }
+    let scale = scale as usize;
+    let digits = format!("{:0>width$}", v.unsigned_abs(), width = scale + 1);
+    let (int, frac) = digits.split_at(digits.len() - scale);
+    format!("{}{}.{}", if v < 0 { "-" } else { "" }, int, frac)
+}
+
+pub fn value_ref_to_string(v: ValueRef<'_>) -> String {
     match v {
         ValueRef::Null => "(null)".to_string(),
         ValueRef::Boolean(b) => b.to_string(),
@@ -192,32 +2032,17 @@ fn value_ref_to_string(v: ValueRef<'_>) -> String {
         ValueRef::USmallInt(i) => i.to_string(),
         ValueRef::UInt(i) => i.to_string(),
         ValueRef::UBigInt(i) => i.to_string(),
+        ValueRef::UHugeInt(i) => i.to_string(),
         ValueRef::Float(f) => f.to_string(),
         ValueRef::Double(f) => f.to_string(),
         ValueRef::Text(s) => String::from_utf8_lossy(s).into_owned(),
         ValueRef::Blob(b) => format!("<blob {} bytes>", b.len()),
-        ValueRef::Date32(d) => {
-            if let Some(date) = NaiveDate::from_ymd_opt(1970, 1, 1) {
-                if let Some(final_date) = date.checked_add_signed(Duration::days(d as i64)) {
-                    return final_date.format("%Y-%m-%d").to_string();
-                }
-            }
-            format!("Date32({})", d)
-        }
-        ValueRef::Time64(_u, t) => format!("Time64({})", t),
-        ValueRef::Timestamp(u, t) => {
-            let dt = match u {
-                TimeUnit::Second => Utc.timestamp_opt(t, 0),
-                TimeUnit::Millisecond => Utc.timestamp_opt(t / 1000, ((t % 1000) * 1_000_000) as u32),
-                TimeUnit::Microsecond => Utc.timestamp_opt(t / 1_000_000, ((t % 1_000_000) * 1000) as u32),
-                TimeUnit::Nanosecond => Utc.timestamp_opt(t / 1_000_000_000, (t % 1_000_000_000) as u32),
-            };
-            
-            match dt {
-                chrono::LocalResult::Single(dt) => dt.format("%Y-%m-%d %H:%M:%S").to_string(),
-                _ => format!("Timestamp({:?}, {})", u, t),
-            }
-        }
+        ValueRef::Date32(d) => match date32_to_date(d) {
+            Some(date) => date.format("%Y-%m-%d").to_string(),
+            None => format!("Date32({})", d),
+        },
+        ValueRef::Time64(u, t) => time_to_string(u, t),
+        ValueRef::Timestamp(u, t) => timestamp_to_string(u, t, None),
         ValueRef::Interval { months, days, nanos } => format!("Interval(M: {}, D: {}, N: {})", months, days, nanos),
         ValueRef::Decimal(d) => d.to_string(),
         ValueRef::List(_t, _idx) => "[List]".to_string(),
@@ -226,3 +2051,395 @@ fn value_ref_to_string(v: ValueRef<'_>) -> String {
         _ => format!("{:?}", v),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    /// A table of `rows` rows with an id, a text and a random score column.
+    fn sample_table(conn: &Connection, rows: usize) {
+        conn.execute_batch(&format!("CREATE TABLE t AS SELECT range AS id, md5(range::VARCHAR) AS name, random() AS score FROM range({})", rows)).unwrap();
+    }
+
+    /// A file in the temp directory holding what `query` returns, in the format its extension names.
+    fn temp_file(name: &str, query: &str) -> String {
+        let path = std::env::temp_dir().join(format!("parquetgrip_{}_{}", std::process::id(), name)).to_string_lossy().into_owned();
+        Connection::open_in_memory().unwrap().execute_batch(&format!("COPY ({}) TO {}", query, quote_literal(&path))).unwrap();
+        path
+    }
+
+    fn explain(conn: &Connection, sql: &str, params: &[i64]) -> String {
+        let mut stmt = conn.prepare(&format!("EXPLAIN {}", sql)).unwrap();
+        let rows = stmt.query_map(duckdb::params_from_iter(params), |r| r.get::<_, String>(1)).unwrap();
+        rows.map(|r| r.unwrap()).collect::<Vec<_>>().join("\n")
+    }
+
+    #[test]
+    fn sorted_page_plans_a_top_n() {
+        let conn = Connection::open_in_memory().unwrap();
+        sample_table(&conn, 1000);
+        let (query, log, params) = Backend::paged_sql("SELECT * FROM t ORDER BY score".to_string(), true, Some(50), Some(100));
+        assert_eq!((query.as_str(), params.len()), (log.as_str(), 0));
+        assert!(explain(&conn, &query, &params).contains("TOP_N"));
+    }
+
+    #[test]
+    fn unsorted_page_binds_limit_and_offset() {
+        let (query, log, params) = Backend::paged_sql("SELECT * FROM t".to_string(), false, Some(50), Some(100));
+        assert_eq!(query, "SELECT * FROM t LIMIT ? OFFSET ?");
+        assert_eq!(log, "SELECT * FROM t LIMIT 50 OFFSET 100");
+        assert_eq!(params, vec![50, 100]);
+    }
+
+    #[test]
+    fn split_statements_skips_line_comments() {
+        assert_eq!(split_statements("SET threads = 2; -- was 4; too many\nSET memory_limit = '1GB';"), ["SET threads = 2", "SET memory_limit = '1GB'"]);
+        assert_eq!(split_statements("-- only a note; nothing to run\n"), Vec::<String>::new());
+        assert_eq!(split_statements("SET search_path = 'a--b;c' -- trailing"), ["SET search_path = 'a--b;c'"]);
+        assert_eq!(split_statements("SET threads = 2 - -1"), ["SET threads = 2 - -1"]);
+        let config = EngineConfig { session_sql: Some("-- keep it small\nSET threads = 1; -- DROP TABLE x;".to_string()), ..Default::default() };
+        assert_eq!(config.validate(), Ok(()));
+    }
+
+    #[test]
+    fn engine_config_reaches_open_connections() {
+        let backend = Backend::new();
+        let open = backend.clone_conn().unwrap();
+        backend.set_engine_config(EngineConfig { threads: Some(3), ..Default::default() }).unwrap();
+        assert_eq!(open.query_row("SELECT current_setting('threads')", [], |r| r.get::<_, i64>(0)).unwrap(), 3);
+        assert!(backend.set_engine_config(EngineConfig { threads: Some(2), session_sql: Some("SET no_such_setting = 1".to_string()), ..Default::default() }).is_err());
+        assert_eq!(open.query_row("SELECT current_setting('threads')", [], |r| r.get::<_, i64>(0)).unwrap(), 3);
+    }
+
+    #[test]
+    fn closing_a_tab_frees_its_query() {
+        let path = temp_file("close.csv", "SELECT range AS id FROM range(10)");
+        let backend = Backend::new();
+        backend.open_file(path.clone(), false).unwrap();
+        let (tx, rx) = mpsc::channel();
+        let (b, p) = (backend.clone(), path.clone());
+        // A filter that runs for minutes unless interrupted
+        let slow = "(SELECT count(*) FROM range(100000000000) t(x) WHERE x % 7 = 3) > 0".to_string();
+        backend.submit(&path, Some("page"), move || { let _ = tx.send(b.run_query(1, p, Some(slow), None, Some(10), None, None)); });
+        std::thread::sleep(Duration::from_millis(300));
+        let closed = Instant::now();
+        backend.close_path(&path);
+        let result = rx.recv_timeout(Duration::from_secs(5)).expect("query still running after close");
+        assert!(closed.elapsed() < Duration::from_secs(2), "close took {:?}", closed.elapsed());
+        assert!(result.as_ref().err().is_some_and(|e| is_closed_error(&path, e)), "{:?}", result.err());
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn cancel_stops_jobs_on_their_own_connections() {
+        let path = temp_file("cancel.csv", "SELECT range AS id FROM range(10)");
+        let backend = Backend::new();
+        backend.open_file(path.clone(), false).unwrap();
+        let (tx, rx) = mpsc::channel();
+        let (b, p) = (backend.clone(), path.clone());
+        let slow = "(SELECT count(*) FROM range(100000000000) t(x) WHERE x % 7 = 3) > 0".to_string();
+        // Profiles run on a connection of their own rather than the tab's
+        backend.submit(&path, None, move || { let _ = tx.send(b.get_profile(&p, Some(&slow), &ExportCancel::default()).map(|_| ())); });
+        std::thread::sleep(Duration::from_millis(300));
+        backend.interrupt(&path);
+        let result = rx.recv_timeout(Duration::from_secs(5)).expect("profile still running after cancel");
+        // Interrupted, not reported as closed: the tab is still open
+        assert!(result.as_ref().err().is_some_and(|e| e.starts_with("INTERRUPT Error") && *e != closed_error(&path)), "{:?}", result);
+        backend.close_path(&path);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn a_slow_tab_does_not_block_the_others() {
+        let paths: Vec<String> = (0..4).map(|i| temp_file(&format!("tabs_{}.csv", i), &format!("SELECT range AS id, range % 7 AS k FROM range({})", 1000 * (i + 1)))).collect();
+        let backend = Backend::new();
+        for path in &paths { backend.open_file(path.clone(), false).unwrap(); }
+        let (slow_tx, slow_rx) = mpsc::channel();
+        let (b, p) = (backend.clone(), paths[0].clone());
+        let slow = "(SELECT count(*) FROM range(100000000000) t(x) WHERE x % 7 = 3) > 0".to_string();
+        backend.submit(&paths[0], Some("page"), move || { let _ = slow_tx.send(b.run_query(1, p, Some(slow), None, Some(10), None, None)); });
+        std::thread::sleep(Duration::from_millis(200));
+        // Pages and filtered counts on the other tabs, interleaved, while the first tab is still busy
+        let (tx, rx) = mpsc::channel();
+        for round in 0..5 {
+            for path in &paths[1..] {
+                let (b, p, tx) = (backend.clone(), path.clone(), tx.clone());
+                backend.submit(path, None, move || {
+                    let page = b.run_query(round, p.clone(), None, Some("k DESC, id".to_string()), Some(50), Some(round as usize * 50), None).is_ok();
+                    let count = b.get_row_count(round, p.clone(), Some(format!("k = {}", round % 7))).is_ok();
+                    let _ = tx.send((p, page && count));
+                });
+            }
+        }
+        // With one shared connection these would wait behind the slow query until it was closed
+        let done: Vec<(String, bool)> = (0..15).map(|_| rx.recv_timeout(Duration::from_secs(30)).expect("other tabs blocked")).collect();
+        assert!(done.iter().all(|(_, ok)| *ok), "{:?}", done);
+        assert!(slow_rx.try_recv().is_err(), "the slow query finished first, so nothing overlapped it");
+        backend.close_path(&paths[0]);
+        let slow = slow_rx.recv_timeout(Duration::from_secs(5)).expect("slow query still running after close");
+        assert!(slow.as_ref().err().is_some_and(|e| is_closed_error(&paths[0], e)), "{:?}", slow.err());
+        for path in &paths { backend.close_path(path); let _ = std::fs::remove_file(path); }
+    }
+
+    #[test]
+    fn times_and_zoned_timestamps_format_alike_on_both_paths() {
+        let conn = Connection::open_in_memory().unwrap();
+        let sql = "SELECT TIME '13:04:05.123456' AS t, TIMESTAMPTZ '2024-03-01 10:00:00.5+02' AS tz, TIMESTAMP '2024-03-01 10:00:00' AS ts";
+        let row: Vec<String> = conn.query_row(sql, [], |r| (0..3).map(|i| Ok(value_ref_to_string(r.get_ref(i)?))).collect()).unwrap();
+        let batch = conn.prepare(sql).unwrap().query_arrow([]).unwrap().next().unwrap();
+        let arrow: Vec<String> = batch.columns().iter().map(|c| array_value_to_string(c, 0)).collect();
+        assert_eq!(row[0], "13:04:05.123456");
+        assert_eq!(arrow[0], "13:04:05.123456");
+        assert_eq!(arrow[1], "2024-03-01 08:00:00+00:00");
+        assert_eq!(arrow[2], "2024-03-01 10:00:00");
+        assert_eq!(row[2], arrow[2]);
+        assert_eq!(value_ref_to_string(ValueRef::Timestamp(TimeUnit::Second, i64::MAX)), "Timestamp(Second, 9223372036854775807)");
+
+        // The row-based path used by exports and typed cells keeps the offset too
+        let path = temp_file("zones.csv", "SELECT 1 AS id");
+        let backend = Backend::new();
+        backend.open_file(path.clone(), false).unwrap();
+        let spec = QuerySpec { path: path.clone(), projection: Some(sql.trim_start_matches("SELECT ").to_string()), ..Default::default() };
+        let (_, rows) = backend.run_query_typed(&spec).unwrap();
+        let typed: Vec<Option<String>> = rows[0].iter().map(Cell::display).collect();
+        assert_eq!(typed, arrow.into_iter().map(Some).collect::<Vec<_>>());
+        backend.close_path(&path);
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(time_to_string(TimeUnit::Millisecond, -1), "Time(Millisecond, -1)");
+        assert_eq!(timestamp_with_zone(timestamp_to_datetime(TimeUnit::Second, 0).unwrap(), Some("-05:30")), "1969-12-31 18:30:00-05:30");
+    }
+
+    #[test]
+    fn timestamps_before_1970_and_at_the_i64_limits() {
+        let text = |u, t| timestamp_to_datetime(u, t).map(|dt| dt.format("%Y-%m-%d %H:%M:%S%.f").to_string());
+        assert_eq!(text(TimeUnit::Second, -1).as_deref(), Some("1969-12-31 23:59:59"));
+        assert_eq!(text(TimeUnit::Millisecond, -1).as_deref(), Some("1969-12-31 23:59:59.999"));
+        assert_eq!(text(TimeUnit::Microsecond, -1).as_deref(), Some("1969-12-31 23:59:59.999999"));
+        assert_eq!(text(TimeUnit::Nanosecond, -1).as_deref(), Some("1969-12-31 23:59:59.999999999"));
+        assert_eq!(text(TimeUnit::Millisecond, -1500).as_deref(), Some("1969-12-31 23:59:58.500"));
+        assert_eq!(text(TimeUnit::Microsecond, -86_400_000_000).as_deref(), Some("1969-12-31 00:00:00"));
+        // Nanoseconds reach only 1677-2262; the coarser units overflow chrono's range at the i64 limits
+        assert_eq!(text(TimeUnit::Nanosecond, i64::MAX).as_deref(), Some("2262-04-11 23:47:16.854775807"));
+        assert_eq!(text(TimeUnit::Nanosecond, i64::MIN).as_deref(), Some("1677-09-21 00:12:43.145224192"));
+        for unit in [TimeUnit::Second, TimeUnit::Millisecond, TimeUnit::Microsecond] {
+            assert_eq!(text(unit, i64::MAX), None);
+            assert_eq!(text(unit, i64::MIN), None);
+        }
+    }
+
+    #[test]
+    fn merging_by_name_is_an_option_not_part_of_the_path() {
+        let files = vec![temp_file("shard_a.csv", "SELECT 1 AS id, 'x' AS name"), temp_file("shard_b.csv", "SELECT 2 AS id, 3.5 AS price")];
+        let path = merged_path(&files);
+        assert_eq!(source_files(&path), files.iter().map(String::as_str).collect::<Vec<_>>());
+        // A file that happens to be called union_by_name is just a file
+        assert_eq!(source_files(&merged_path(&["union_by_name".to_string(), files[0].clone()])).len(), 2);
+        let backend = Backend::new();
+        assert!(backend.open_file(path.clone(), false).is_err(), "shards with different columns merged by position");
+        backend.open_file(path.clone(), true).unwrap();
+        match backend.run_query(1, path.clone(), None, Some("id".to_string()), Some(10), None, None) {
+            Ok(BackendMessage::QueryData { columns, batch, .. }) => {
+                assert_eq!(columns, ["id", "name", "price"]);
+                assert_eq!(batch.num_rows(), 2);
+            }
+            _ => panic!("no page"),
+        }
+        backend.close_path(&path);
+        files.iter().for_each(|f| { let _ = std::fs::remove_file(f); });
+    }
+
+    #[test]
+    fn only_magic_byte_and_footer_errors_suggest_corruption() {
+        assert!(Backend::corruption_hint("Invalid Input Error: No magic bytes found at end of file 'a.parquet'").is_some());
+        assert!(Backend::corruption_hint("Invalid Input Error: File 'a.parquet' too small to be a Parquet file").is_some());
+        assert!(Backend::corruption_hint("Invalid Input Error: Footer length error in file 'a.parquet'").is_some());
+        assert!(Backend::corruption_hint("Invalid Input Error: File 'a.parquet' is encrypted, but 'encryption_config' was not set").is_none());
+        assert!(Backend::corruption_hint("Invalid Input Error: Unknown named parameter 'union_by_nam' for read_parquet").is_none());
+        assert!(Backend::corruption_hint("Binder Error: Referenced column \"PAR1\" not found").is_none());
+    }
+
+    #[test]
+    fn watchdog_times_out_only_queries_past_their_limit() {
+        let conn = Connection::open_in_memory().unwrap();
+        let watchdog = Watchdog::default();
+        let (tx, rx) = mpsc::channel();
+        let watched = |started: Instant, limit: u64| Watched { request: 1, path: "p".to_string(), started, limit: Some(Duration::from_secs(limit)), handle: conn.interrupt_handle(), progress: Some(tx.clone()), timed_out: false };
+        // Finished before the first tick: neither timed out nor reported on, then or later
+        let quick = watchdog.watch(watched(Instant::now(), 1));
+        assert!(!watchdog.finish(quick));
+        let past = watchdog.watch(watched(Instant::now() - Duration::from_secs(5), 1));
+        let slow = watchdog.watch(watched(Instant::now() - SLOW_QUERY, 60));
+        std::thread::sleep(WATCH_TICK * 3);
+        assert!(watchdog.finish(past));
+        assert!(!watchdog.finish(slow));
+        drop(tx);
+        let reports: Vec<BackendMessage> = rx.iter().collect();
+        assert!(!reports.is_empty() && reports.iter().all(|m| matches!(m, BackendMessage::Progress { elapsed, .. } if *elapsed >= SLOW_QUERY && *elapsed < Duration::from_secs(5))));
+    }
+
+    #[test]
+    fn exports_read_the_tabs_view() {
+        let path = temp_file("view.csv", "SELECT range AS id FROM range(5)");
+        let backend = Backend::new();
+        backend.open_file(path.clone(), false).unwrap();
+        let spec = QuerySpec { path: path.clone(), filter: Some("id > 1".to_string()), ..Default::default() };
+        let sql = spec.sql(&backend);
+        assert!(!sql.contains("read_csv"), "{}", sql);
+        assert!(sql.contains(&backend.source(&path)), "{}", sql);
+        assert_eq!(backend.run_query_typed(&spec).unwrap().1.len(), 3);
+        backend.close_path(&path);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn columns_come_from_the_schema_not_the_first_row() {
+        let path = temp_file("nulls.csv", "SELECT * FROM (VALUES (NULL::INTEGER, NULL::VARCHAR, 0), (1, 'x', 1)) AS t(a, b, n) ORDER BY n");
+        let backend = Backend::new();
+        backend.open_file(path.clone(), false).unwrap();
+        let page = |filter: Option<&str>| match backend.run_query(1, path.clone(), filter.map(str::to_string), None, Some(10), None, None) {
+            Ok(BackendMessage::QueryData { columns, batch, .. }) => (columns, PageData::new(batch)),
+            _ => panic!("no page"),
+        };
+        let (columns, data) = page(None);
+        assert_eq!((columns, data.len()), (vec!["a".to_string(), "b".to_string(), "n".to_string()], 2));
+        assert_eq!(data.row(0).map(|r| r.iter().map(|c| c.to_string()).collect::<Vec<_>>()), Some(vec!["(null)".to_string(), "(null)".to_string(), "0".to_string()]));
+        let (columns, data) = page(Some("false"));
+        assert_eq!((columns.len(), data.len()), (3, 0));
+        backend.close_path(&path);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    fn row_count(backend: &Backend, path: &str, filter: Option<&str>) -> (usize, String) {
+        match backend.get_row_count(1, path.to_string(), filter.map(str::to_string)) {
+            Ok(BackendMessage::RowCount { count, sql, .. }) => (count, sql),
+            _ => panic!("no row count"),
+        }
+    }
+
+    #[test]
+    fn filtered_count_applies_the_filter() {
+        let path = temp_file("count.csv", "SELECT range AS id FROM range(100)");
+        let backend = Backend::new();
+        backend.open_file(path.clone(), false).unwrap();
+        assert_eq!(row_count(&backend, &path, Some("id % 3 = 0")).0, 34);
+        assert_eq!(row_count(&backend, &path, None).0, 100);
+        // A remembered unfiltered count must not answer a filtered one
+        assert_eq!(row_count(&backend, &path, Some("id < 10")).0, 10);
+        backend.close_path(&path);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    #[ignore = "needs DuckDB's parquet extension"]
+    fn footer_count_agrees_with_count_star() {
+        let files = [temp_file("count_a.parquet", "SELECT range AS id FROM range(70000)"), temp_file("count_b.parquet", "SELECT range AS id FROM range(123)")];
+        for path in [files[0].clone(), files.join("\n")] {
+            let backend = Backend::new();
+            backend.open_file(path.clone(), false).unwrap();
+            let (fast, sql) = row_count(&backend, &path, None);
+            // Either the footers or the remembered count from an earlier run, never a scan
+            assert!(!sql.contains("count(*)"), "{}", sql);
+            let (slow, sql) = row_count(&backend, &path, Some("true"));
+            assert!(sql.contains("count(*)"), "{}", sql);
+            assert_eq!(fast, slow);
+            backend.close_path(&path);
+        }
+        files.iter().for_each(|f| { let _ = std::fs::remove_file(f); });
+    }
+
+    #[test]
+    fn hugeint_cells_round_trip() {
+        let conn = Connection::open_in_memory().unwrap();
+        let values = [(i64::MAX as u128 + 1).to_string(), (u64::MAX as u128 + 1).to_string(), (i128::MAX as u128 + 1).to_string(), u128::MAX.to_string()];
+        for value in &values {
+            let field = SchemaField { name: "u".to_string(), data_type: "UHUGEINT".to_string(), nullable: None };
+            let projection = Backend::capped_projection(&[field], 0).unwrap();
+            let sql = format!("SELECT {} FROM (SELECT {}::UHUGEINT AS u)", projection, value);
+            assert_eq!(Backend::arrow_cell(&conn, &sql).unwrap().as_deref(), Some(value.as_str()));
+            assert_eq!(conn.query_row(&format!("SELECT {}::UHUGEINT", value), [], |r| Ok(value_ref_to_string(r.get_ref(0)?))).unwrap(), *value);
+        }
+        for value in [i128::MIN, i128::MIN + 1, i64::MIN as i128 - 1, i64::MAX as i128 + 1, i128::MAX] {
+            let sql = format!("SELECT CAST('{}' AS HUGEINT)", value);
+            assert_eq!(Backend::arrow_cell(&conn, &sql).unwrap(), Some(value.to_string()));
+            assert_eq!(conn.query_row(&sql, [], |r| Ok(value_ref_to_string(r.get_ref(0)?))).unwrap(), value.to_string());
+            // IN filters put the value back as a bare literal
+            assert_eq!(conn.query_row(&format!("SELECT count(*) FROM ({}) AS t(h) WHERE h IN ({})", sql, value), [], |r| r.get::<_, i64>(0)).unwrap(), 1);
+        }
+    }
+
+    /// Binding a 3,000-column CSV dominates; debug builds of DuckDB take about a minute here, release builds a few
+    /// seconds. Run with `cargo test --release -- --ignored wide_file_opens_and_pages`.
+    #[test]
+    #[ignore = "slow outside release builds"]
+    fn wide_file_opens_and_pages() {
+        const COLUMNS: usize = 3000;
+        let columns: Vec<String> = (0..COLUMNS).map(|i| format!("range + {} AS c{}", i, i)).collect();
+        let path = temp_file("wide.csv", &format!("SELECT {} FROM range(500)", columns.join(", ")));
+        let backend = Backend::new();
+        let started = Instant::now();
+        assert!(matches!(backend.open_file(path.clone(), false), Ok(BackendMessage::FileOpened { .. })));
+        let Ok(BackendMessage::Schema { fields, .. }) = backend.get_schema(path.clone()) else { panic!("no schema") };
+        assert_eq!(fields.len(), COLUMNS);
+        let Ok(BackendMessage::QueryData { columns, batch, .. }) = backend.run_query(1, path.clone(), None, None, Some(100), Some(200), None) else { panic!("no page") };
+        assert_eq!((columns.len(), batch.num_rows()), (COLUMNS, 100));
+        assert_eq!(columns[COLUMNS - 1], format!("c{}", COLUMNS - 1));
+        assert!(started.elapsed() < Duration::from_secs(30), "wide file took {:?}", started.elapsed());
+        backend.close_path(&path);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn only_stale_spill_files_are_removed() {
+        let dir = std::env::temp_dir().join(format!("parquetgrip_{}_spill", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let day = Duration::from_secs(24 * 60 * 60);
+        for (name, idle) in [("duckdb_temp_storage_DEFAULT-0.tmp", 2 * day), ("duckdb_temp_storage_DEFAULT-1.tmp", Duration::ZERO), ("notes.tmp", 2 * day)] {
+            let file = std::fs::File::create(dir.join(name)).unwrap();
+            file.set_modified(std::time::SystemTime::now() - idle).unwrap();
+        }
+        // The fresh one could be another window's
+        assert_eq!(remove_stale_spill_files(&dir, day), Ok(1));
+        let mut left: Vec<String> = std::fs::read_dir(&dir).unwrap().map(|e| e.unwrap().file_name().to_string_lossy().into_owned()).collect();
+        left.sort();
+        assert_eq!(left, ["duckdb_temp_storage_DEFAULT-1.tmp", "notes.tmp"]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn warm_up_keeps_its_failures() {
+        let backend = Backend::new();
+        let (tx, rx) = mpsc::channel();
+        backend.warm_up(EngineConfig::default(), vec!["not-an-extension".to_string()], tx).unwrap();
+        // Asking for the connection waits out the warm-up
+        backend.get_conn().unwrap();
+        assert_eq!(backend.warm_up_problems(), vec!["\"not-an-extension\" isn't an extension name".to_string()]);
+        assert!(matches!(rx.try_recv(), Ok(BackendMessage::Error { .. })));
+    }
+
+    /// Dev-only: what reusing the paging statement saves over preparing each page, and what a bound LIMIT
+    /// costs a sorted page. Run with `cargo test --release -- --ignored --nocapture paging_benchmark`.
+    #[test]
+    #[ignore = "benchmark"]
+    fn paging_benchmark() {
+        const PAGES: usize = 50;
+        const PAGE: usize = 100;
+        let conn = Connection::open_in_memory().unwrap();
+        sample_table(&conn, 200_000);
+        let run = |label: &str, sort: &str, cached: bool, bind: bool| {
+            let started = Instant::now();
+            for page in 0..PAGES {
+                let (query, _, params) = Backend::paged_sql(format!("SELECT * FROM t{}", sort), !bind, Some(PAGE), Some(page * PAGE));
+                let count = if cached { conn.prepare_cached(&query).unwrap().query(duckdb::params_from_iter(&params)).unwrap().mapped(|_| Ok(())).count() }
+                    else { conn.prepare(&query).unwrap().query(duckdb::params_from_iter(&params)).unwrap().mapped(|_| Ok(())).count() };
+                assert_eq!(count, PAGE);
+            }
+            println!("{:<40} {:>8.2} ms/page", label, started.elapsed().as_secs_f64() * 1000.0 / PAGES as f64);
+        };
+        run("unsorted, prepared per page", "", false, false);
+        run("unsorted, cached statement, bound", "", true, true);
+        run("sorted, literal LIMIT", " ORDER BY score", true, false);
+        run("sorted, bound LIMIT", " ORDER BY score", true, true);
+    }
+}
@@ -0,0 +1,1084 @@
+// ParquetGrip - A high-performance Parquet file viewer.
+// Copyright (c) 2026 Edward (wansanai)
+// SPDX-License-Identifier: MIT
+
+use crate::backend::{date32_to_date, quote_ident, Backend, Cell, ExportCancel, ProfileReport, QuerySpec, time_to_string, timestamp_to_datetime, value_ref_to_string, RowSink, SchemaField};
+use arrow::datatypes::{DataType, Field, IntervalUnit, Schema, TimeUnit};
+use arrow::ipc::writer::FileWriter;
+use arrow::array::StructArray;
+use arrow::record_batch::RecordBatch;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use duckdb::types::{Value, ValueRef};
+use serde::Serialize;
+use rust_xlsxwriter::{Format, Workbook, XlsxError};
+use std::fs::File;
+use std::collections::BTreeSet;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Excel's hard limit is 1,048,576 rows per sheet, one of which is the header.
+pub const EXCEL_MAX_DATA_ROWS: usize = 1_048_575;
+
+/// Excel stores numbers as doubles, so larger integers are written as text to keep every digit.
+const EXCEL_MAX_EXACT_INT: i128 = 1 << 53;
+
+/// Excel refuses cells longer than this many characters.
+const EXCEL_MAX_CELL_CHARS: usize = 32_767;
+
+/// How the UI marks SQL NULL in string cells.
+pub const NULL_DISPLAY: &str = "(null)";
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    Csv,
+    Tsv,
+    Markdown,
+    Xlsx,
+    SqlInsert,
+    Json,
+    Parquet,
+}
+
+impl ExportFormat {
+    pub const ALL: [ExportFormat; 7] = [ExportFormat::Csv, ExportFormat::Tsv, ExportFormat::Markdown, ExportFormat::Xlsx, ExportFormat::SqlInsert, ExportFormat::Json, ExportFormat::Parquet];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "CSV (.csv)",
+            ExportFormat::Tsv => "TSV (.tsv)",
+            ExportFormat::Markdown => "Markdown (.md)",
+            ExportFormat::Xlsx => "Excel (.xlsx)",
+            ExportFormat::SqlInsert => "SQL INSERT (.sql)",
+            ExportFormat::Json => "JSON array (.json)",
+            ExportFormat::Parquet => "Parquet (.parquet)",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Tsv => "tsv",
+            ExportFormat::Markdown => "md",
+            ExportFormat::Xlsx => "xlsx",
+            ExportFormat::SqlInsert => "sql",
+            ExportFormat::Json => "json",
+            ExportFormat::Parquet => "parquet",
+        }
+    }
+
+    /// Text formats can be serialized straight from the already-loaded page; the others need typed values.
+    pub fn is_text(&self) -> bool {
+        !matches!(self, ExportFormat::Xlsx | ExportFormat::SqlInsert | ExportFormat::Json | ExportFormat::Parquet)
+    }
+
+    /// Parquet is written by DuckDB's `COPY` rather than streamed through a `RowSink`.
+    pub fn uses_copy(&self) -> bool {
+        *self == ExportFormat::Parquet
+    }
+}
+
+/// Which rows an export covers; shared by every format.
+#[derive(Clone, Copy, PartialEq)]
+pub enum ExportScope {
+    WholeResult,
+    CurrentPage,
+    Selection,
+}
+
+/// Serializes rows as CSV, TSV or a Markdown table. NULLs become empty fields.
+pub struct TextWriter<W: Write> {
+    out: W,
+    format: ExportFormat,
+}
+
+impl<W: Write> TextWriter<W> {
+    pub fn new(out: W, format: ExportFormat) -> Self {
+        Self { out, format }
+    }
+
+    pub fn header(&mut self, columns: &[String]) -> std::io::Result<()> {
+        self.row(columns.iter().map(|c| Some(c.as_str())))?;
+        if self.format == ExportFormat::Markdown {
+            writeln!(self.out, "|{}", "---|".repeat(columns.len()))?;
+        }
+        Ok(())
+    }
+
+    pub fn row<'a>(&mut self, cells: impl IntoIterator<Item = Option<&'a str>>) -> std::io::Result<()> {
+        let line = match self.format {
+            ExportFormat::Markdown => {
+                let cells: Vec<String> = cells.into_iter().map(|c| c.unwrap_or("").replace('|', "\\|").replace("\r\n", "<br>").replace('\n', "<br>")).collect();
+                format!("| {} |", cells.join(" | "))
+            }
+            _ => {
+                let delim = if self.format == ExportFormat::Tsv { '\t' } else { ',' };
+                let cells: Vec<String> = cells.into_iter().map(|c| quote_field(c.unwrap_or(""), delim)).collect();
+                cells.join(&delim.to_string())
+            }
+        };
+        writeln!(self.out, "{}", line)
+    }
+
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+}
+
+/// Quotes a delimited field when it contains the delimiter, a quote or a line break.
+fn quote_field(s: &str, delim: char) -> String {
+    if s.contains(delim) || s.contains('"') || s.contains('\n') || s.contains('\r') {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+/// Serializes in-memory string rows, treating the UI's null marker as NULL.
+pub fn write_text_rows<W: Write, S: AsRef<str>, R: AsRef<[S]>>(out: W, format: ExportFormat, columns: &[String], rows: impl IntoIterator<Item = R>) -> std::io::Result<W> {
+    let mut w = TextWriter::new(out, format);
+    w.header(columns)?;
+    for r in rows {
+        w.row(r.as_ref().iter().map(|c| Some(c.as_ref()).filter(|c| *c != NULL_DISPLAY)))?;
+    }
+    Ok(w.into_inner())
+}
+
+/// A standalone `<table>` with inline styles: NULLs dimmed, numeric columns right-aligned, long cells
+/// truncated to `max_chars` with the full value in a `title` attribute.
+pub fn html_table(columns: &[String], numeric: &[bool], rows: &[Vec<String>], max_chars: usize) -> String {
+    let cell_style = "border:1px solid #ccc;padding:2px 6px;";
+    let mut html = String::from("<table style=\"border-collapse:collapse;font-family:sans-serif;font-size:13px;\">\n<thead><tr>");
+    for c in columns {
+        html += &format!("<th style=\"{}background:#f0f0f0;text-align:left;\">{}</th>", cell_style, html_escape(c));
+    }
+    html += "</tr></thead>\n<tbody>\n";
+    for r in rows {
+        html += "<tr>";
+        for (i, c) in r.iter().enumerate() {
+            let align = if numeric.get(i).copied().unwrap_or(false) { "text-align:right;" } else { "" };
+            if c == NULL_DISPLAY {
+                html += &format!("<td style=\"{}{}color:#999;font-style:italic;\">NULL</td>", cell_style, align);
+            } else if c.chars().count() > max_chars {
+                let short: String = c.chars().take(max_chars).collect();
+                html += &format!("<td style=\"{}{}\" title=\"{}\">{}…</td>", cell_style, align, html_escape(c), html_escape(&short));
+            } else {
+                html += &format!("<td style=\"{}{}\">{}</td>", cell_style, align, html_escape(c));
+            }
+        }
+        html += "</tr>\n";
+    }
+    html += "</tbody>\n</table>\n";
+    html
+}
+
+fn html_escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;").replace('\'', "&#39;")
+}
+
+/// Whether a DuckDB type name holds numbers, for right-aligning them.
+pub fn is_numeric_type(t: &str) -> bool {
+    let t = t.to_ascii_uppercase();
+    ["TINYINT", "SMALLINT", "INTEGER", "BIGINT", "HUGEINT", "UTINYINT", "USMALLINT", "UINTEGER", "UBIGINT", "UHUGEINT", "FLOAT", "DOUBLE", "DECIMAL"].iter().any(|n| t == *n || t.starts_with("DECIMAL("))
+}
+
+/// Joins one column's values for the clipboard: one per line, or a SQL `IN (...)` list with text quoted and numbers bare.
+pub fn column_values_text<'a>(values: impl IntoIterator<Item = Option<&'a str>>, numeric: bool, in_list: bool, skip_nulls: bool) -> String {
+    let values = values.into_iter().filter(|v| !skip_nulls || v.is_some());
+    if in_list {
+        let items: Vec<String> = values.map(|v| match v {
+            Some(v) if numeric => v.to_string(),
+            Some(v) => format!("'{}'", v.replace('\'', "''")),
+            None => "NULL".to_string(),
+        }).collect();
+        format!("({})", items.join(", "))
+    } else {
+        values.map(|v| v.unwrap_or("")).collect::<Vec<_>>().join("\n")
+    }
+}
+
+/// Streams a backend result into a text file, reporting rows and bytes flushed so far.
+pub struct TextSink<F: FnMut(usize, u64)> {
+    writer: TextWriter<BufWriter<File>>,
+    rows_done: usize,
+    on_progress: F,
+}
+
+impl<F: FnMut(usize, u64)> TextSink<F> {
+    pub fn create(target: &Path, format: ExportFormat, on_progress: F) -> Result<Self, String> {
+        let file = File::create(target).map_err(|e| e.to_string())?;
+        Ok(Self { writer: TextWriter::new(BufWriter::new(file), format), rows_done: 0, on_progress })
+    }
+}
+
+impl<F: FnMut(usize, u64)> RowSink for TextSink<F> {
+    fn begin(&mut self, columns: &[String]) -> Result<(), String> {
+        self.writer.header(columns).map_err(|e| e.to_string())
+    }
+
+    fn row(&mut self, values: &[ValueRef<'_>]) -> Result<(), String> {
+        let cells: Vec<Option<String>> = values.iter().map(|v| match v {
+            ValueRef::Null => None,
+            v => Some(value_ref_to_string(*v)),
+        }).collect();
+        self.writer.row(cells.iter().map(|c| c.as_deref())).map_err(|e| e.to_string())?;
+        self.rows_done += 1;
+        if self.rows_done.is_multiple_of(10_000) {
+            let bytes = self.writer.out.get_ref().metadata().map(|m| m.len()).unwrap_or(0);
+            (self.on_progress)(self.rows_done, bytes);
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), String> {
+        self.writer.out.flush().map_err(|e| e.to_string())
+    }
+}
+
+/// Forwards only the chosen rows (indices relative to the query's offset) and columns, in the given order.
+pub struct SelectionSink<'a> {
+    inner: &'a mut dyn RowSink,
+    rows: Option<BTreeSet<usize>>,
+    cols: Vec<usize>,
+    index: usize,
+    written: usize,
+}
+
+impl<'a> SelectionSink<'a> {
+    pub fn new(inner: &'a mut dyn RowSink, rows: Option<BTreeSet<usize>>, cols: Vec<usize>) -> Self {
+        Self { inner, rows, cols, index: 0, written: 0 }
+    }
+
+    pub fn written(&self) -> usize {
+        self.written
+    }
+}
+
+impl RowSink for SelectionSink<'_> {
+    fn begin(&mut self, columns: &[String]) -> Result<(), String> {
+        let names: Vec<String> = self.cols.iter().filter_map(|&c| columns.get(c).cloned()).collect();
+        self.inner.begin(&names)
+    }
+
+    fn row(&mut self, values: &[ValueRef<'_>]) -> Result<(), String> {
+        let keep = self.rows.as_ref().is_none_or(|r| r.contains(&self.index));
+        self.index += 1;
+        if !keep { return Ok(()); }
+        let picked: Vec<ValueRef<'_>> = self.cols.iter().filter_map(|&c| values.get(c).copied()).collect();
+        self.written += 1;
+        self.inner.row(&picked)
+    }
+
+    fn finish(&mut self) -> Result<(), String> {
+        self.inner.finish()
+    }
+}
+
+/// Rows per `INSERT` statement.
+const SQL_INSERT_BATCH: usize = 500;
+
+/// Writes `CREATE TABLE` DDL followed by batched `INSERT INTO ... VALUES` statements.
+pub struct SqlSink<F: FnMut(usize, u64)> {
+    out: BufWriter<File>,
+    table: String,
+    fields: Vec<SchemaField>,
+    types: Vec<String>,
+    rows_done: usize,
+    on_progress: F,
+}
+
+impl<F: FnMut(usize, u64)> SqlSink<F> {
+    /// `fields` supplies the column types for the DDL; columns missing from it are declared VARCHAR.
+    pub fn create(target: &Path, table: &str, fields: Vec<SchemaField>, on_progress: F) -> Result<Self, String> {
+        let file = File::create(target).map_err(|e| e.to_string())?;
+        Ok(Self { out: BufWriter::new(file), table: quote_ident(table), fields, types: Vec::new(), rows_done: 0, on_progress })
+    }
+}
+
+impl<F: FnMut(usize, u64)> RowSink for SqlSink<F> {
+    fn begin(&mut self, columns: &[String]) -> Result<(), String> {
+        self.types = columns.iter().map(|c| self.fields.iter().find(|f| &f.name == c).map(|f| f.data_type.clone()).unwrap_or_else(|| "VARCHAR".to_string())).collect();
+        let defs: Vec<String> = columns.iter().zip(&self.types).map(|(c, t)| format!("    {} {}", quote_ident(c), t)).collect();
+        writeln!(self.out, "CREATE TABLE {} (\n{}\n);", self.table, defs.join(",\n")).map_err(|e| e.to_string())
+    }
+
+    fn row(&mut self, values: &[ValueRef<'_>]) -> Result<(), String> {
+        let first = self.rows_done.is_multiple_of(SQL_INSERT_BATCH);
+        if first {
+            // Close the previous batch (if any) and open a new statement
+            if self.rows_done > 0 { writeln!(self.out, ";").map_err(|e| e.to_string())?; }
+            writeln!(self.out, "INSERT INTO {} VALUES", self.table).map_err(|e| e.to_string())?;
+        } else {
+            writeln!(self.out, ",").map_err(|e| e.to_string())?;
+        }
+        let literals = values.iter().enumerate().map(|(i, v)| sql_literal(*v, self.types.get(i).map(|t| t.as_str()).unwrap_or("VARCHAR"))).collect::<Result<Vec<_>, _>>()?;
+        write!(self.out, "    ({})", literals.join(", ")).map_err(|e| e.to_string())?;
+        self.rows_done += 1;
+        if self.rows_done.is_multiple_of(10_000) {
+            let bytes = self.out.get_ref().metadata().map(|m| m.len()).unwrap_or(0);
+            (self.on_progress)(self.rows_done, bytes);
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), String> {
+        if self.rows_done > 0 { writeln!(self.out, ";").map_err(|e| e.to_string())?; }
+        self.out.flush().map_err(|e| e.to_string())
+    }
+}
+
+/// A SQL literal for one value; `data_type` is the column's DuckDB type. Nested values are cast to it, since DuckDB
+/// would otherwise infer their element types from the literal (an empty list, a NULL field, a union member).
+fn sql_literal(v: ValueRef<'_>, data_type: &str) -> Result<String, String> {
+    let literal = value_literal(&v.to_owned()).ok_or_else(|| format!("{} values can't be written as SQL literals", data_type))?;
+    Ok(match v {
+        ValueRef::List(..) | ValueRef::Array(..) | ValueRef::Struct(..) | ValueRef::Map(..) | ValueRef::Union(..) => format!("CAST({} AS {})", literal, data_type),
+        _ => literal,
+    })
+}
+
+/// The literal for a value, nested ones included; `None` for values with no literal syntax.
+fn value_literal(v: &Value) -> Option<String> {
+    let quote = |s: &str| format!("'{}'", s.replace('\'', "''"));
+    let list = |items: &[Value]| items.iter().map(value_literal).collect::<Option<Vec<_>>>().map(|l| format!("[{}]", l.join(", ")));
+    Some(match v {
+        Value::Null => "NULL".to_string(),
+        Value::Boolean(b) => if *b { "TRUE" } else { "FALSE" }.to_string(),
+        Value::Float(f) if !f.is_finite() => format!("{}::FLOAT", quote(&f.to_string())),
+        Value::Double(f) if !f.is_finite() => format!("{}::DOUBLE", quote(&f.to_string())),
+        Value::TinyInt(_) | Value::SmallInt(_) | Value::Int(_) | Value::BigInt(_) | Value::HugeInt(_)
+        | Value::UTinyInt(_) | Value::USmallInt(_) | Value::UInt(_) | Value::UBigInt(_) | Value::UHugeInt(_)
+        | Value::Float(_) | Value::Double(_) | Value::Decimal(_) => value_ref_to_string(v.into()),
+        Value::Text(t) | Value::Enum(t) => quote(t),
+        Value::Blob(b) => format!("from_hex('{}')", b.iter().map(|x| format!("{:02X}", x)).collect::<String>()),
+        Value::Date32(d) => format!("DATE '{}'", date32_to_date(*d)?.format("%Y-%m-%d")),
+        Value::Timestamp(u, t) => format!("TIMESTAMP '{}'", timestamp_to_datetime(*u, *t)?.format("%Y-%m-%d %H:%M:%S%.f")),
+        Value::Time64(u, t) => format!("TIME '{}'", time_to_string(*u, *t)),
+        Value::Interval { months, days, nanos } => format!("INTERVAL '{} months {} days {} microseconds'", months, days, nanos / 1000),
+        Value::List(items) | Value::Array(items) => list(items)?,
+        Value::Struct(fields) => {
+            let fields = fields.iter().map(|(k, v)| value_literal(v).map(|v| format!("{}: {}", quote(k), v))).collect::<Option<Vec<_>>>()?;
+            format!("{{{}}}", fields.join(", "))
+        }
+        Value::Map(entries) => {
+            let entries = entries.iter().map(|(k, v)| Some(format!("{}: {}", value_literal(k)?, value_literal(v)?))).collect::<Option<Vec<_>>>()?;
+            format!("MAP {{{}}}", entries.join(", "))
+        }
+        Value::Union(inner) => value_literal(inner)?,
+        // Geometry has no literal short of a spatial function call
+        _ => return None,
+    })
+}
+
+/// Writes rows as a JSON array of objects with native types; structs, lists and maps become real JSON.
+/// Keys keep the column order, which `serde_json::Map` would sort, so the text is assembled by hand.
+pub struct JsonSink<W: Write, F: FnMut(usize, u64)> {
+    out: W,
+    keys: Vec<String>,
+    camel_case: bool,
+    rows_done: usize,
+    bytes: u64,
+    on_progress: F,
+}
+
+impl<W: Write, F: FnMut(usize, u64)> JsonSink<W, F> {
+    pub fn new(out: W, camel_case: bool, on_progress: F) -> Self {
+        Self { out, keys: Vec::new(), camel_case, rows_done: 0, bytes: 0, on_progress }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+
+    fn write(&mut self, s: &str) -> Result<(), String> {
+        self.bytes += s.len() as u64;
+        self.out.write_all(s.as_bytes()).map_err(|e| e.to_string())
+    }
+}
+
+impl<W: Write, F: FnMut(usize, u64)> RowSink for JsonSink<W, F> {
+    fn begin(&mut self, columns: &[String]) -> Result<(), String> {
+        self.keys = columns.iter().map(|c| json_string(&if self.camel_case { camel_case(c) } else { c.clone() })).collect();
+        self.write("[")
+    }
+
+    fn row(&mut self, values: &[ValueRef<'_>]) -> Result<(), String> {
+        let mut obj = String::from(if self.rows_done == 0 { "\n  {" } else { ",\n  {" });
+        for (i, (k, v)) in self.keys.iter().zip(values).enumerate() {
+            if i > 0 { obj.push_str(", "); }
+            obj.push_str(k);
+            obj.push_str(": ");
+            json_value(&mut obj, &v.to_owned());
+        }
+        obj.push('}');
+        self.write(&obj)?;
+        self.rows_done += 1;
+        if self.rows_done.is_multiple_of(10_000) {
+            (self.on_progress)(self.rows_done, self.bytes);
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), String> {
+        self.write(if self.rows_done == 0 { "]\n" } else { "\n]\n" })?;
+        self.out.flush().map_err(|e| e.to_string())
+    }
+}
+
+/// `user_id` -> `userId`; leading underscores are kept.
+fn camel_case(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut upper = false;
+    for c in s.chars() {
+        if c == '_' && !out.trim_start_matches('_').is_empty() { upper = true; continue; }
+        if upper { out.extend(c.to_uppercase()); upper = false; } else { out.push(c); }
+    }
+    out
+}
+
+fn json_string(s: &str) -> String {
+    serde_json::Value::String(s.to_string()).to_string()
+}
+
+fn json_value(out: &mut String, v: &Value) {
+    let push_list = |out: &mut String, items: &[Value]| {
+        out.push('[');
+        for (i, item) in items.iter().enumerate() {
+            if i > 0 { out.push_str(", "); }
+            json_value(out, item);
+        }
+        out.push(']');
+    };
+    match v {
+        Value::Null => out.push_str("null"),
+        Value::Boolean(b) => out.push_str(if *b { "true" } else { "false" }),
+        Value::TinyInt(i) => out.push_str(&i.to_string()),
+        Value::SmallInt(i) => out.push_str(&i.to_string()),
+        Value::Int(i) => out.push_str(&i.to_string()),
+        Value::BigInt(i) => out.push_str(&i.to_string()),
+        Value::HugeInt(i) => out.push_str(&i.to_string()),
+        Value::UHugeInt(i) => out.push_str(&i.to_string()),
+        Value::UTinyInt(i) => out.push_str(&i.to_string()),
+        Value::USmallInt(i) => out.push_str(&i.to_string()),
+        Value::UInt(i) => out.push_str(&i.to_string()),
+        Value::UBigInt(i) => out.push_str(&i.to_string()),
+        Value::Float(f) if f.is_finite() => out.push_str(&f.to_string()),
+        Value::Double(f) if f.is_finite() => out.push_str(&f.to_string()),
+        Value::Float(_) | Value::Double(_) => out.push_str("null"),
+        Value::Decimal(d) => out.push_str(&d.to_string()),
+        Value::Text(t) | Value::Enum(t) => out.push_str(&json_string(t)),
+        Value::Blob(b) | Value::Geometry(b) => out.push_str(&json_string(&b.iter().map(|x| format!("{:02x}", x)).collect::<String>())),
+        Value::Date32(d) => out.push_str(&date32_to_date(*d).map_or("null".to_string(), |d| json_string(&d.format("%Y-%m-%d").to_string()))),
+        Value::Timestamp(u, t) => out.push_str(&timestamp_to_datetime(*u, *t).map_or("null".to_string(), |dt| json_string(&dt.format("%Y-%m-%dT%H:%M:%S%.fZ").to_string()))),
+        Value::Time64(u, t) => out.push_str(&json_string(&time_to_string(*u, *t))),
+        Value::Interval { months, days, nanos } => out.push_str(&json_string(&format!("{} months {} days {} microseconds", months, days, nanos / 1000))),
+        Value::List(items) | Value::Array(items) => push_list(out, items),
+        Value::Struct(fields) => {
+            out.push('{');
+            for (i, (k, v)) in fields.iter().enumerate() {
+                if i > 0 { out.push_str(", "); }
+                out.push_str(&json_string(k));
+                out.push_str(": ");
+                json_value(out, v);
+            }
+            out.push('}');
+        }
+        // JSON keys must be strings, so map keys use their JSON text unless they already are strings
+        Value::Map(entries) => {
+            out.push('{');
+            for (i, (k, v)) in entries.iter().enumerate() {
+                if i > 0 { out.push_str(", "); }
+                let key = match k { Value::Text(t) => t.clone(), k => { let mut s = String::new(); json_value(&mut s, k); s } };
+                out.push_str(&json_string(&key));
+                out.push_str(": ");
+                json_value(out, v);
+            }
+            out.push('}');
+        }
+        Value::Union(inner) => json_value(out, inner),
+        other => out.push_str(&json_string(&format!("{:?}", other))),
+    }
+}
+
+/// What to do when a result has more rows than fit on one Excel sheet.
+#[derive(Clone, Copy, PartialEq)]
+pub enum XlsxOverflow {
+    FirstRows,
+    SplitSheets,
+}
+
+/// Writes rows into an Excel workbook, starting a new sheet whenever the current one is full.
+/// Nothing reaches `target` until the workbook is saved, so progress reports zero bytes.
+pub struct XlsxSink<F: FnMut(usize, u64)> {
+    target: PathBuf,
+    workbook: Workbook,
+    columns: Vec<String>,
+    header_fmt: Format,
+    date_fmt: Format,
+    datetime_fmt: Format,
+    sheet: usize,
+    sheet_row: usize,
+    rows_done: usize,
+    split_sheets: bool,
+    on_progress: F,
+}
+
+impl<F: FnMut(usize, u64)> XlsxSink<F> {
+    pub fn new(target: PathBuf, overflow: XlsxOverflow, on_progress: F) -> Self {
+        Self {
+            target,
+            workbook: Workbook::new(),
+            columns: Vec::new(),
+            header_fmt: Format::new().set_bold(),
+            date_fmt: Format::new().set_num_format("yyyy-mm-dd"),
+            datetime_fmt: Format::new().set_num_format("yyyy-mm-dd hh:mm:ss"),
+            sheet: 0,
+            sheet_row: 0,
+            rows_done: 0,
+            split_sheets: overflow == XlsxOverflow::SplitSheets,
+            on_progress,
+        }
+    }
+
+    /// Adds a sheet with the bold header row; the autofilter is applied once its height is known.
+    fn start_sheet(&mut self) -> Result<(), XlsxError> {
+        let ws = self.workbook.add_worksheet_with_constant_memory();
+        for (c, name) in self.columns.iter().enumerate() {
+            ws.write_string_with_format(0, c as u16, name, &self.header_fmt)?;
+        }
+        self.sheet = self.workbook.worksheets().len() - 1;
+        self.sheet_row = 0;
+        Ok(())
+    }
+
+    fn finish_sheet(&mut self) -> Result<(), XlsxError> {
+        if self.columns.is_empty() { return Ok(()); }
+        let last_col = (self.columns.len() - 1) as u16;
+        self.workbook.worksheet_from_index(self.sheet)?.autofilter(0, 0, self.sheet_row as u32, last_col)?;
+        Ok(())
+    }
+
+    fn write_cell(&mut self, col: u16, v: ValueRef<'_>) -> Result<(), XlsxError> {
+        let row = self.sheet_row as u32 + 1;
+        let ws = self.workbook.worksheet_from_index(self.sheet)?;
+        // A range check rather than abs(), which overflows on i128::MIN
+        let int = |i: i128| -> Option<f64> { (-EXCEL_MAX_EXACT_INT..=EXCEL_MAX_EXACT_INT).contains(&i).then_some(i as f64) };
+        let number = match v {
+            ValueRef::Null => return Ok(()),
+            ValueRef::Boolean(b) => { ws.write_boolean(row, col, b)?; return Ok(()); }
+            ValueRef::TinyInt(i) => Some(i as f64),
+            ValueRef::SmallInt(i) => Some(i as f64),
+            ValueRef::Int(i) => Some(i as f64),
+            ValueRef::UTinyInt(i) => Some(i as f64),
+            ValueRef::USmallInt(i) => Some(i as f64),
+            ValueRef::UInt(i) => Some(i as f64),
+            ValueRef::BigInt(i) => int(i as i128),
+            ValueRef::UBigInt(i) => int(i as i128),
+            ValueRef::HugeInt(i) => int(i),
+            ValueRef::UHugeInt(i) => i128::try_from(i).ok().and_then(int),
+            ValueRef::Float(f) => Some(f as f64),
+            ValueRef::Double(f) => Some(f),
+            ValueRef::Decimal(d) => d.to_string().parse::<f64>().ok(),
+            ValueRef::Date32(d) => {
+                if let Some(date) = date32_to_date(d) {
+                    if ws.write_datetime_with_format(row, col, date, &self.date_fmt).is_ok() { return Ok(()); }
+                }
+                None
+            }
+            ValueRef::Timestamp(u, t) => {
+                if let Some(dt) = timestamp_to_datetime(u, t) {
+                    if ws.write_datetime_with_format(row, col, dt.naive_utc(), &self.datetime_fmt).is_ok() { return Ok(()); }
+                }
+                None
+            }
+            _ => None,
+        };
+        match number {
+            Some(n) if n.is_finite() => { ws.write_number(row, col, n)?; }
+            _ => {
+                let text = value_ref_to_string(v);
+                let text = if text.chars().count() > EXCEL_MAX_CELL_CHARS { text.chars().take(EXCEL_MAX_CELL_CHARS).collect() } else { text };
+                ws.write_string(row, col, text)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<F: FnMut(usize, u64)> RowSink for XlsxSink<F> {
+    fn begin(&mut self, columns: &[String]) -> Result<(), String> {
+        self.columns = columns.to_vec();
+        self.start_sheet().map_err(|e| e.to_string())
+    }
+
+    fn row(&mut self, values: &[ValueRef<'_>]) -> Result<(), String> {
+        if self.sheet_row >= EXCEL_MAX_DATA_ROWS {
+            if !self.split_sheets {
+                return Ok(());
+            }
+            self.finish_sheet().map_err(|e| e.to_string())?;
+            self.start_sheet().map_err(|e| e.to_string())?;
+        }
+        for (c, v) in values.iter().enumerate() {
+            self.write_cell(c as u16, *v).map_err(|e| e.to_string())?;
+        }
+        self.sheet_row += 1;
+        self.rows_done += 1;
+        if self.rows_done.is_multiple_of(10_000) {
+            (self.on_progress)(self.rows_done, 0);
+        }
+        Ok(())
+    }
+
+    /// Applies the final autofilter and writes the workbook to its target.
+    fn finish(&mut self) -> Result<(), String> {
+        self.finish_sheet().map_err(|e| e.to_string())?;
+        self.workbook.save(&self.target).map_err(|e| e.to_string())
+    }
+
+    /// Rows past a full sheet are dropped unless the sink splits sheets.
+    fn rows_kept(&self) -> Option<usize> {
+        Some(self.rows_done)
+    }
+}
+
+/// Writes the typed schema as JSON, or as an empty Arrow IPC file when `target` ends in `.arrow`/`.arrows`.
+pub fn write_schema(target: &Path, source: &str, fields: &[SchemaField]) -> Result<(), String> {
+    let out = BufWriter::new(File::create(target).map_err(|e| e.to_string())?);
+    match target.extension().and_then(|e| e.to_str()) {
+        Some("arrow") | Some("arrows") => {
+            let mut w = FileWriter::try_new(out, &arrow_schema(source, fields)).map_err(|e| e.to_string())?;
+            w.finish().map_err(|e| e.to_string())
+        }
+        _ => {
+            #[derive(serde::Serialize)]
+            struct SchemaDoc<'a> { source: &'a str, fields: &'a [SchemaField] }
+            serde_json::to_writer_pretty(out, &SchemaDoc { source, fields }).map_err(|e| e.to_string())
+        }
+    }
+}
+
+/// Parquet files carry an exact Arrow schema (nullability included); anything else is mapped from DuckDB's type names.
+fn arrow_schema(source: &str, fields: &[SchemaField]) -> Schema {
+    if let Some(b) = File::open(source).ok().and_then(|f| ParquetRecordBatchReaderBuilder::try_new(f).ok()) {
+        return b.schema().as_ref().clone();
+    }
+    Schema::new(fields.iter().map(|f| Field::new(&f.name, duckdb_type_to_arrow(&f.data_type), f.nullable.unwrap_or(true))).collect::<Vec<_>>())
+}
+
+/// Nested types other than lists fall back to Utf8, which is how the grid renders them anyway.
+fn duckdb_type_to_arrow(t: &str) -> DataType {
+    let t = t.trim();
+    if let Some(inner) = t.strip_suffix("[]") {
+        return DataType::List(Arc::new(Field::new("item", duckdb_type_to_arrow(inner), true)));
+    }
+    let upper = t.to_ascii_uppercase();
+    if let Some((p, s)) = upper.strip_prefix("DECIMAL(").and_then(|a| a.strip_suffix(')')).and_then(|a| a.split_once(',')) {
+        if let (Ok(p), Ok(s)) = (p.trim().parse::<u8>(), s.trim().parse::<i8>()) {
+            return DataType::Decimal128(p, s);
+        }
+    }
+    match upper.as_str() {
+        "BOOLEAN" => DataType::Boolean,
+        "TINYINT" => DataType::Int8,
+        "SMALLINT" => DataType::Int16,
+        "INTEGER" => DataType::Int32,
+        "BIGINT" => DataType::Int64,
+        "UTINYINT" => DataType::UInt8,
+        "USMALLINT" => DataType::UInt16,
+        "UINTEGER" => DataType::UInt32,
+        "UBIGINT" => DataType::UInt64,
+        "HUGEINT" => DataType::Decimal128(38, 0),
+        "FLOAT" => DataType::Float32,
+        "DOUBLE" => DataType::Float64,
+        "DATE" => DataType::Date32,
+        "TIME" => DataType::Time64(TimeUnit::Microsecond),
+        "TIMESTAMP" => DataType::Timestamp(TimeUnit::Microsecond, None),
+        "TIMESTAMP_S" => DataType::Timestamp(TimeUnit::Second, None),
+        "TIMESTAMP_MS" => DataType::Timestamp(TimeUnit::Millisecond, None),
+        "TIMESTAMP_NS" => DataType::Timestamp(TimeUnit::Nanosecond, None),
+        "TIMESTAMP WITH TIME ZONE" => DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+        "INTERVAL" => DataType::Interval(IntervalUnit::MonthDayNano),
+        "BLOB" => DataType::Binary,
+        _ => DataType::Utf8,
+    }
+}
+
+/// Writes a profile as CSV (with a `#` comment header) or JSON, chosen by `target`'s extension.
+pub fn write_profile(target: &Path, source: &str, filter: Option<&str>, report: &ProfileReport) -> Result<(), String> {
+    let generated = chrono::Local::now().to_rfc3339();
+    let mut out = BufWriter::new(File::create(target).map_err(|e| e.to_string())?);
+    if target.extension().and_then(|e| e.to_str()) == Some("json") {
+        let columns: Vec<serde_json::Map<String, serde_json::Value>> = report.rows.iter().map(|r| {
+            report.columns.iter().zip(r).map(|(c, v)| (c.clone(), v.clone().map_or(serde_json::Value::Null, serde_json::Value::String))).collect()
+        }).collect();
+        let doc = serde_json::json!({ "source": source, "row_count": report.row_count, "filter": filter, "generated": generated, "columns": columns });
+        serde_json::to_writer_pretty(&mut out, &doc).map_err(|e| e.to_string())?;
+    } else {
+        let header = [("source", source.to_string()), ("rows", report.row_count.to_string()), ("filter", filter.unwrap_or("").to_string()), ("generated", generated)];
+        write_profile_csv(&mut out, &header, report).map_err(|e| e.to_string())?;
+    }
+    out.flush().map_err(|e| e.to_string())
+}
+
+fn write_profile_csv<W: Write>(out: &mut W, header: &[(&str, String)], report: &ProfileReport) -> std::io::Result<()> {
+    for (k, v) in header {
+        writeln!(out, "# {}: {}", k, v)?;
+    }
+    let mut w = TextWriter::new(out, ExportFormat::Csv);
+    w.header(&report.columns)?;
+    for r in &report.rows {
+        w.row(r.iter().map(|v| v.as_deref()))?;
+    }
+    Ok(())
+}
+
+/// How a whole-result export is broken into several files.
+#[derive(Clone, PartialEq)]
+pub enum ExportSplit {
+    Single,
+    /// At most this many rows per `part-0001.ext`, `part-0002.ext`, ...
+    MaxRows(usize),
+    /// One file per distinct value of this column.
+    ByColumn(String),
+}
+
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// One written file as listed in the manifest.
+#[derive(Serialize)]
+pub struct PartFile {
+    pub file: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    /// Unknown for files DuckDB partitions itself.
+    pub rows: Option<usize>,
+    pub bytes: u64,
+}
+
+#[derive(Serialize)]
+struct Manifest<'a> {
+    source: &'a str,
+    sql: &'a str,
+    split: String,
+    /// False when the export was cancelled or failed; the listed files are still whole.
+    complete: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<&'a str>,
+    files: &'a [PartFile],
+}
+
+/// Opens the sink for one part; the counts are the rows and bytes already written by earlier parts.
+pub type PartSinkFactory<'a> = dyn FnMut(&Path, usize, u64) -> Result<Box<dyn RowSink>, String> + 'a;
+
+/// Writes `spec` into `dir` as several files and always leaves a manifest of the finished ones behind.
+/// Cancelling stops between files, or between batches of a streamed query; a part still being written is removed.
+/// Returns the rows and bytes written.
+#[allow(clippy::too_many_arguments)]
+pub fn write_split(backend: &Backend, spec: &QuerySpec, split: &ExportSplit, format: ExportFormat, dir: &Path, total_rows: usize, cancel: &ExportCancel, make_sink: &mut PartSinkFactory, on_files: &mut dyn FnMut(usize, usize)) -> Result<(usize, u64), String> {
+    let ext = format.extension();
+    let mut files = Vec::new();
+    let mut partial = None;
+    let result = match split {
+        ExportSplit::Single => Err("Not a split export".to_string()),
+        ExportSplit::MaxRows(max_rows) if format.uses_copy() => {
+            // One streamed query, so the result is sorted once and rows that tie on the sort key land in exactly
+            // one part; DuckDB's own COPY can't rotate files without giving up the row order
+            let max_rows = (*max_rows).max(1);
+            let total = total_rows.div_ceil(max_rows).max(1);
+            let mut parts = ParquetParts { dir, max_rows, total, schema: None, current: None, files: &mut files, on_files };
+            let streamed = backend.stream_batches(spec, cancel, &mut |batch| parts.write(batch)).and_then(|_| parts.finish());
+            partial = parts.current.take().map(|(path, _, _)| path);
+            streamed
+        }
+        ExportSplit::MaxRows(max_rows) => {
+            let total = total_rows.div_ceil((*max_rows).max(1)).max(1);
+            let mut sink = PartSink { dir, ext, max_rows: (*max_rows).max(1), total, columns: Vec::new(), current: None, files: &mut files, make_sink, on_files };
+            let streamed = backend.stream_query(spec, cancel, &mut sink).and_then(|_| sink.finish());
+            partial = sink.current.take().map(|(path, _, _)| path);
+            streamed
+        }
+        ExportSplit::ByColumn(column) if format.uses_copy() => {
+            // DuckDB writes the hive-style `column=value/` folders itself, so there is nothing to stop between
+            let options = format!("FORMAT PARQUET, PARTITION_BY ({}), OVERWRITE_OR_IGNORE", quote_ident(column));
+            backend.copy_to(spec, &dir.to_string_lossy(), &options, cancel).map(|_| {
+                collect_parquet_files(dir, dir, &mut files);
+                on_files(files.len(), files.len());
+            })
+        }
+        ExportSplit::ByColumn(column) => write_value_parts(backend, spec, column, ext, dir, cancel, make_sink, on_files, &mut files, &mut partial),
+    };
+    if let Some(path) = partial { let _ = std::fs::remove_file(path); }
+
+    let split_desc = match split {
+        ExportSplit::Single => "single".to_string(),
+        ExportSplit::MaxRows(n) => format!("max {} rows per file", n),
+        ExportSplit::ByColumn(c) => format!("by column {}", c),
+    };
+    let error = result.as_ref().err().map(|e| e.as_str());
+    let manifest = Manifest { source: &spec.path, sql: &spec.sql(backend), split: split_desc, complete: result.is_ok(), error, files: &files };
+    let json = serde_json::to_string_pretty(&manifest).map_err(|e| e.to_string())?;
+    std::fs::write(dir.join(MANIFEST_FILE), json).map_err(|e| e.to_string())?;
+    result.map(|_| files.iter().fold((0, 0), |(r, b), f| (r + f.rows.unwrap_or(0), b + f.bytes)))
+}
+
+/// The per-value loop for streamed formats: one query and one file per distinct value of `column`.
+#[allow(clippy::too_many_arguments)]
+fn write_value_parts(backend: &Backend, spec: &QuerySpec, column: &str, ext: &str, dir: &Path, cancel: &ExportCancel, make_sink: &mut PartSinkFactory, on_files: &mut dyn FnMut(usize, usize), files: &mut Vec<PartFile>, partial: &mut Option<PathBuf>) -> Result<(), String> {
+    let values = partition_values(backend, spec, column)?;
+    let mut used = BTreeSet::new();
+    for (i, value) in values.iter().enumerate() {
+        if cancel.is_cancelled() { return Err("Export cancelled".to_string()); }
+        let mut name = format!("{}={}", file_safe(column), value.as_deref().map_or("NULL".to_string(), file_safe));
+        if !used.insert(name.clone()) { name = format!("{}-{}", name, i + 1); }
+        let path = dir.join(format!("{}.{}", name, ext));
+        *partial = Some(path.clone());
+        let (rows_before, bytes_before) = files.iter().fold((0, 0), |(r, b), f| (r + f.rows.unwrap_or(0), b + f.bytes));
+        let mut sink = make_sink(&path, rows_before, bytes_before)?;
+        let part = QuerySpec { filter: Some(partition_filter(spec.filter.as_deref(), column, value.as_deref())), ..spec.clone() };
+        let rows = backend.stream_query(&part, cancel, sink.as_mut())?;
+        sink.finish()?;
+        files.push(part_file(&path, value.clone(), Some(sink.rows_kept().unwrap_or(rows))));
+        *partial = None;
+        on_files(files.len(), values.len());
+    }
+    Ok(())
+}
+
+fn part_file(path: &Path, value: Option<String>, rows: Option<usize>) -> PartFile {
+    let file = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    PartFile { file, value, rows, bytes: std::fs::metadata(path).map(|m| m.len()).unwrap_or(0) }
+}
+
+/// Distinct values as DuckDB renders them to text, so each can be matched back exactly in a filter.
+fn partition_values(backend: &Backend, spec: &QuerySpec, column: &str) -> Result<Vec<Option<String>>, String> {
+    let projection = format!("DISTINCT CAST({} AS VARCHAR) AS partition_value", quote_ident(column));
+    let distinct = QuerySpec { path: spec.path.clone(), projection: Some(projection), filter: spec.filter.clone(), sort: Some("partition_value NULLS LAST".to_string()), ..Default::default() };
+    let (_, rows) = backend.run_query_typed(&distinct)?;
+    Ok(rows.into_iter().map(|mut r| match r.pop() { Some(Cell::Text(t)) => Some(t), _ => None }).collect())
+}
+
+fn partition_filter(filter: Option<&str>, column: &str, value: Option<&str>) -> String {
+    let cond = match value {
+        Some(v) => format!("CAST({} AS VARCHAR) = '{}'", quote_ident(column), v.replace('\'', "''")),
+        None => format!("{} IS NULL", quote_ident(column)),
+    };
+    match filter.map(str::trim).filter(|f| !f.is_empty()) {
+        Some(f) => format!("({}) AND {}", f, cond),
+        None => cond,
+    }
+}
+
+/// Keeps file names portable: anything but letters, digits, `-`, `_` and `.` becomes `_`.
+fn file_safe(s: &str) -> String {
+    let safe: String = s.chars().take(80).map(|c| if c.is_alphanumeric() || matches!(c, '-' | '_' | '.') { c } else { '_' }).collect();
+    if safe.is_empty() { "_".to_string() } else { safe }
+}
+
+fn collect_parquet_files(root: &Path, dir: &Path, files: &mut Vec<PartFile>) {
+    let Ok(entries) = std::fs::read_dir(dir) else { return };
+    let mut entries: Vec<PathBuf> = entries.filter_map(|e| e.ok().map(|e| e.path())).collect();
+    entries.sort();
+    for path in entries {
+        if path.is_dir() { collect_parquet_files(root, &path, files); continue; }
+        if path.extension().is_none_or(|e| e != "parquet") { continue; }
+        let rel = path.strip_prefix(root).unwrap_or(&path).to_string_lossy().to_string();
+        let value = path.parent().and_then(|p| p.file_name()).map(|n| n.to_string_lossy().to_string()).filter(|n| n.contains('='));
+        files.push(PartFile { file: rel, value, rows: None, bytes: std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0) });
+    }
+}
+
+/// Rotates to a fresh part file every `max_rows` rows of a single streamed query.
+struct PartSink<'a, 'f> {
+    dir: &'a Path,
+    ext: &'static str,
+    max_rows: usize,
+    total: usize,
+    columns: Vec<String>,
+    current: Option<(PathBuf, Box<dyn RowSink>, usize)>,
+    files: &'a mut Vec<PartFile>,
+    make_sink: &'a mut PartSinkFactory<'f>,
+    on_files: &'a mut dyn FnMut(usize, usize),
+}
+
+impl PartSink<'_, '_> {
+    fn open_part(&mut self) -> Result<(), String> {
+        let path = self.dir.join(format!("part-{:04}.{}", self.files.len() + 1, self.ext));
+        let (rows, bytes) = self.files.iter().fold((0, 0), |(r, b), f| (r + f.rows.unwrap_or(0), b + f.bytes));
+        let mut sink = (self.make_sink)(&path, rows, bytes)?;
+        sink.begin(&self.columns)?;
+        self.current = Some((path, sink, 0));
+        Ok(())
+    }
+
+    fn close_part(&mut self) -> Result<(), String> {
+        let Some((path, mut sink, rows)) = self.current.take() else { return Ok(()) };
+        if let Err(e) = sink.finish() { self.current = Some((path, sink, rows)); return Err(e); }
+        self.files.push(part_file(&path, None, Some(sink.rows_kept().unwrap_or(rows))));
+        // The row count is only an estimate, so the total grows if the result ran longer
+        (self.on_files)(self.files.len(), self.total.max(self.files.len()));
+        Ok(())
+    }
+}
+
+impl RowSink for PartSink<'_, '_> {
+    fn begin(&mut self, columns: &[String]) -> Result<(), String> {
+        self.columns = columns.to_vec();
+        Ok(())
+    }
+
+    fn row(&mut self, values: &[ValueRef<'_>]) -> Result<(), String> {
+        if self.current.is_none() { self.open_part()?; }
+        let Some((_, sink, rows)) = self.current.as_mut() else { return Ok(()) };
+        sink.row(values)?;
+        *rows += 1;
+        if *rows >= self.max_rows { self.close_part()?; }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), String> {
+        // An empty result still gets one part with just the header
+        if self.files.is_empty() && self.current.is_none() { self.open_part()?; }
+        self.close_part()
+    }
+}
+
+/// Rotates to a fresh Parquet part every `max_rows` rows of a single streamed query.
+struct ParquetParts<'a> {
+    dir: &'a Path,
+    max_rows: usize,
+    total: usize,
+    schema: Option<arrow::datatypes::SchemaRef>,
+    current: Option<(PathBuf, ArrowWriter<File>, usize)>,
+    files: &'a mut Vec<PartFile>,
+    on_files: &'a mut dyn FnMut(usize, usize),
+}
+
+impl ParquetParts<'_> {
+    fn write(&mut self, batch: &duckdb::arrow::record_batch::RecordBatch) -> Result<(), String> {
+        let mut batch = writer_batch(batch)?;
+        if self.schema.is_none() { self.schema = Some(batch.schema()); }
+        while batch.num_rows() > 0 {
+            if self.current.is_none() { self.open_part()?; }
+            let Some((_, writer, rows)) = self.current.as_mut() else { return Ok(()) };
+            let take = batch.num_rows().min(self.max_rows - *rows);
+            writer.write(&batch.slice(0, take)).map_err(|e| e.to_string())?;
+            *rows += take;
+            if *rows >= self.max_rows { self.close_part()?; }
+            batch = batch.slice(take, batch.num_rows() - take);
+        }
+        Ok(())
+    }
+
+    fn open_part(&mut self) -> Result<(), String> {
+        let path = self.dir.join(format!("part-{:04}.parquet", self.files.len() + 1));
+        let schema = self.schema.clone().ok_or("The result has no schema")?;
+        // Snappy, as DuckDB's COPY writes it
+        let props = WriterProperties::builder().set_compression(Compression::SNAPPY).build();
+        let writer = ArrowWriter::try_new(File::create(&path).map_err(|e| e.to_string())?, schema, Some(props)).map_err(|e| e.to_string())?;
+        self.current = Some((path, writer, 0));
+        Ok(())
+    }
+
+    fn close_part(&mut self) -> Result<(), String> {
+        let Some((path, writer, rows)) = self.current.take() else { return Ok(()) };
+        writer.close().map_err(|e| e.to_string())?;
+        self.files.push(part_file(&path, None, Some(rows)));
+        // The row count is only an estimate, so the total grows if the result ran longer
+        (self.on_files)(self.files.len(), self.total.max(self.files.len()));
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), String> {
+        // An empty result still gets one part with just the schema
+        if self.files.is_empty() && self.current.is_none() { self.open_part()?; }
+        self.close_part()
+    }
+}
+
+/// Moves a batch from the arrow version DuckDB links to the one the Parquet writer takes. Both export the Arrow
+/// C data interface, so the buffers are handed over rather than copied.
+fn writer_batch(batch: &duckdb::arrow::record_batch::RecordBatch) -> Result<RecordBatch, String> {
+    let data = duckdb::arrow::array::Array::into_data(duckdb::arrow::array::StructArray::from(batch.clone()));
+    let (array, schema) = duckdb::arrow::ffi::to_ffi(&data).map_err(|e| e.to_string())?;
+    // SAFETY: both sides' FFI_ArrowArray and FFI_ArrowSchema are #[repr(C)] definitions of the same C structs
+    // from the Arrow spec, and the release callback travels with the array
+    let data = unsafe {
+        let (array, schema) = (std::mem::transmute::<duckdb::arrow::ffi::FFI_ArrowArray, arrow::ffi::FFI_ArrowArray>(array), std::mem::transmute::<duckdb::arrow::ffi::FFI_ArrowSchema, arrow::ffi::FFI_ArrowSchema>(schema));
+        arrow::ffi::from_ffi(array, &schema)
+    }.map_err(|e| e.to_string())?;
+    Ok(RecordBatch::from(StructArray::from(data)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use duckdb::Connection;
+
+    #[test]
+    fn sql_export_round_trips_nested_values() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("CREATE TYPE mood AS ENUM ('sad', 'ok', 'happy');
+            CREATE TABLE src AS SELECT * FROM (VALUES
+                (1, [1, 2, NULL], {'a': 'x''y', 'b': [DATE '2024-01-02']}, MAP {'k': 1.5}, [[1], []], 'ok'::mood, union_value(n := 7)::UNION(n INTEGER, s VARCHAR), [1, 2]::INTEGER[2]),
+                (2, [], {'a': NULL, 'b': NULL}, MAP {}, NULL, NULL, union_value(s := 'z')::UNION(n INTEGER, s VARCHAR), NULL)
+            ) AS t(id, list, rec, m, nested, feeling, choice, pair)").unwrap();
+        let fields: Vec<SchemaField> = conn.prepare("DESCRIBE src").unwrap().query_map([], |r| Ok(SchemaField { name: r.get(0)?, data_type: r.get(1)?, nullable: None })).unwrap().map(|f| f.unwrap()).collect();
+        let target = std::env::temp_dir().join(format!("parquetgrip_{}_nested.sql", std::process::id()));
+        let mut sink = SqlSink::create(&target, "copy", fields, |_, _| {}).unwrap();
+        let mut stmt = conn.prepare("SELECT * FROM src ORDER BY id").unwrap();
+        let mut rows = stmt.query([]).unwrap();
+        let columns = rows.as_ref().unwrap().column_names();
+        sink.begin(&columns).unwrap();
+        while let Some(row) = rows.next().unwrap() {
+            let values: Vec<ValueRef> = (0..columns.len()).map(|i| row.get_ref(i).unwrap()).collect();
+            sink.row(&values).unwrap();
+        }
+        sink.finish().unwrap();
+        drop(rows);
+        let sql = std::fs::read_to_string(&target).unwrap();
+        let _ = std::fs::remove_file(&target);
+        assert!(!sql.contains("unsupported"), "{}", sql);
+        conn.execute_batch(&sql).unwrap();
+        let differ: i64 = conn.query_row("SELECT count(*) FROM ((FROM src EXCEPT ALL FROM copy) UNION ALL (FROM copy EXCEPT ALL FROM src))", [], |r| r.get(0)).unwrap();
+        assert_eq!(differ, 0, "{}", sql);
+    }
+
+    #[test]
+    fn parquet_parts_split_one_sorted_pass() {
+        use arrow::array::AsArray;
+        let base = std::env::temp_dir().join(format!("parquetgrip_{}_parts", std::process::id()));
+        let (source, dir) = (base.with_extension("csv"), base.join("out"));
+        std::fs::create_dir_all(&dir).unwrap();
+        // Only three sort keys, so most rows tie and separate queries per part could disagree on their order
+        Connection::open_in_memory().unwrap().execute_batch(&format!("COPY (SELECT range AS id, range % 3 AS k FROM range(2500)) TO '{}'", source.display())).unwrap();
+        let source = source.to_string_lossy().into_owned();
+        let backend = Backend::new();
+        backend.open_file(source.clone(), false).unwrap();
+        let spec = QuerySpec { path: source.clone(), sort: Some("k".to_string()), ..Default::default() };
+        // A stale count of 0 must not cut the export short
+        let (rows, _) = write_split(&backend, &spec, &ExportSplit::MaxRows(1000), ExportFormat::Parquet, &dir, 0, &ExportCancel::default(), &mut |_, _, _| Err("unused".to_string()), &mut |_, _| {}).unwrap();
+        assert_eq!(rows, 2500);
+        let (mut sizes, mut keys, mut ids) = (Vec::new(), Vec::new(), Vec::new());
+        for part in ["part-0001.parquet", "part-0002.parquet", "part-0003.parquet"] {
+            let reader = ParquetRecordBatchReaderBuilder::try_new(File::open(dir.join(part)).unwrap()).unwrap().build().unwrap();
+            let batches: Vec<RecordBatch> = reader.map(|b| b.unwrap()).collect();
+            sizes.push(batches.iter().map(|b| b.num_rows()).sum::<usize>());
+            for batch in &batches {
+                ids.extend(batch.column_by_name("id").unwrap().as_primitive::<arrow::datatypes::Int64Type>().values().iter().copied());
+                keys.extend(batch.column_by_name("k").unwrap().as_primitive::<arrow::datatypes::Int64Type>().values().iter().copied());
+            }
+        }
+        assert!(!dir.join("part-0004.parquet").exists());
+        assert_eq!(sizes, [1000, 1000, 500]);
+        assert!(keys.is_sorted(), "parts out of sort order");
+        ids.sort();
+        assert_eq!(ids, (0..2500).collect::<Vec<i64>>(), "rows repeated or skipped across parts");
+        backend.close_path(&source);
+        let _ = std::fs::remove_dir_all(&base);
+        let _ = std::fs::remove_file(&source);
+    }
+}
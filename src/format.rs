@@ -0,0 +1,82 @@
+// ParquetGrip - A high-performance Parquet file viewer.
+// Copyright (c) 2026 Edward (wansanai)
+// SPDX-License-Identifier: MIT
+
+use chrono::{TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A per-column display override for when the stored type is more generic than its meaning.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum ColumnFormat {
+    UnixSeconds,
+    UnixMillis,
+    Bytes,
+    Hex,
+    Percent,
+    /// Shown exactly as stored, without the digit grouping turned on for the grid; for IDs and codes.
+    Plain,
+}
+
+impl ColumnFormat {
+    pub const ALL: [ColumnFormat; 6] = [ColumnFormat::UnixSeconds, ColumnFormat::UnixMillis, ColumnFormat::Bytes, ColumnFormat::Hex, ColumnFormat::Percent, ColumnFormat::Plain];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ColumnFormat::UnixSeconds => "As unix timestamp (seconds)",
+            ColumnFormat::UnixMillis => "As unix timestamp (millis)",
+            ColumnFormat::Bytes => "As bytes (human size)",
+            ColumnFormat::Hex => "As hex",
+            ColumnFormat::Percent => "As percentage",
+            ColumnFormat::Plain => "As plain number (no grouping)",
+        }
+    }
+
+    /// Reformats an already-rendered cell; `None` means the value doesn't fit the format and is shown as-is.
+    pub fn apply(&self, cell: &str) -> Option<String> {
+        match self {
+            ColumnFormat::UnixSeconds => Utc.timestamp_opt(cell.parse::<i64>().ok()?, 0).single().map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string()),
+            ColumnFormat::UnixMillis => Utc.timestamp_millis_opt(cell.parse::<i64>().ok()?).single().map(|dt| dt.format("%Y-%m-%d %H:%M:%S%.3f").to_string()),
+            ColumnFormat::Bytes => Some(human_bytes(cell.parse::<f64>().ok()?)),
+            ColumnFormat::Hex => cell.parse::<i64>().ok().map(|i| format!("{:#x}", i)).or_else(|| cell.parse::<u64>().ok().map(|u| format!("{:#x}", u))),
+            ColumnFormat::Percent => cell.parse::<f64>().ok().map(|f| format!("{:.2}%", f * 100.0)),
+            ColumnFormat::Plain => None,
+        }
+    }
+}
+
+/// What the grid shows for a rendered cell: the column's own format comes first, then digit grouping when it's
+/// on for a numeric column. `None` shows the cell as it is.
+pub fn display_cell(cell: &str, format: Option<ColumnFormat>, group: bool) -> Option<String> {
+    match format {
+        Some(f) => f.apply(cell),
+        None if group => group_digits(cell),
+        None => None,
+    }
+}
+
+/// Puts thousands separators into a plainly written number, e.g. `-1234567.5` as `-1,234,567.5`; `None` for
+/// anything else, exponents included.
+pub fn group_digits(cell: &str) -> Option<String> {
+    let (sign, rest) = cell.strip_prefix('-').map_or(("", cell), |r| ("-", r));
+    let (int, frac) = rest.split_once('.').map_or((rest, None), |(i, f)| (i, Some(f)));
+    let digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    if int.len() <= 3 || !digits(int) || !frac.is_none_or(digits) { return None; }
+    let mut out = String::from(sign);
+    for (i, c) in int.chars().enumerate() {
+        if i > 0 && (int.len() - i) % 3 == 0 { out.push(','); }
+        out.push(c);
+    }
+    if let Some(f) = frac { out.push('.'); out.push_str(f); }
+    Some(out)
+}
+
+pub fn human_bytes(n: f64) -> String {
+    const UNITS: [&str; 6] = ["B", "KiB", "MiB", "GiB", "TiB", "PiB"];
+    let mut value = n;
+    let mut unit = 0;
+    while value.abs() >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 { format!("{} {}", n, UNITS[0]) } else { format!("{:.1} {}", value, UNITS[unit]) }
+}
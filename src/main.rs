@@ -5,13 +5,22 @@
 use eframe::egui;
 use egui_extras::Column;
 use std::sync::{Arc, mpsc};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
 use egui_dock::{DockArea, DockState, Style, TabViewer};
 use egui_dock::tab_viewer::OnCloseResponse;
 use serde::{Deserialize, Serialize};
 
 mod backend;
-use backend::{Backend, BackendMessage};
+mod export;
+mod format;
+mod settings;
+mod rules;
+use std::io::Write;
+use backend::{Backend, BackendMessage, Cell, PageData, PerfRecord, RequestId, RowGroupInfo, ExportCancel, QuerySpec, RowSink, SchemaField, ERROR_DETAILS_SEPARATOR, MAX_ROW_CAP, dataset_dir, dataset_path, dataset_root, is_capped, merged_path, quote_ident, source_files};
+use format::{human_bytes, ColumnFormat};
+use settings::{RowDensity, Settings};
+use rules::{FilterTerm, Operand, RowRule, RuleOp};
+use export::{ExportFormat, ExportScope, ExportSplit, JsonSink, SelectionSink, SqlSink, TextSink, XlsxOverflow, XlsxSink, EXCEL_MAX_DATA_ROWS};
 
 #[derive(Serialize, Deserialize, Clone)]
 struct LogEntry {
@@ -21,6 +30,249 @@ struct LogEntry {
     error: Option<String>,
 }
 
+/// The cell detail popup; `value` stays `None` until the backend returns the full value.
+struct CellDetail {
+    row: usize,
+    column: String,
+    value: Option<Option<String>>,
+}
+
+/// Cells Ctrl/Cmd-clicked in one column, by global row, with the values they showed when picked.
+struct CellSelection {
+    column: usize,
+    values: BTreeMap<usize, String>,
+}
+
+/// What a grid page was loaded with; a cached page is only reused for the exact same query.
+#[derive(Clone, PartialEq)]
+struct PageKey {
+    filter: String,
+    sort: String,
+    page: usize,
+    page_size: usize,
+}
+
+impl PageKey {
+    fn of(tab: &Tab) -> Self {
+        Self { filter: tab.filter.clone(), sort: tab.sort.clone(), page: tab.current_page, page_size: tab.page_size }
+    }
+}
+
+/// A tab's recently viewed pages, least recently used first.
+#[derive(Default)]
+struct PageCache {
+    entries: VecDeque<(PageKey, Vec<String>, PageData)>,
+}
+
+impl PageCache {
+    fn take(&mut self, key: &PageKey) -> Option<(Vec<String>, PageData)> {
+        let i = self.entries.iter().position(|(k, _, _)| k == key)?;
+        self.entries.remove(i).map(|(_, columns, data)| (columns, data))
+    }
+
+    fn put(&mut self, key: PageKey, columns: Vec<String>, data: PageData, max_pages: usize, max_bytes: usize) {
+        self.entries.retain(|(k, _, _)| *k != key);
+        self.entries.push_back((key, columns, data));
+        while self.entries.len() > max_pages || (self.entries.len() > 1 && self.memory_size() > max_bytes) { self.entries.pop_front(); }
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    fn memory_size(&self) -> usize {
+        self.entries.iter().map(|(_, _, d)| d.memory_size()).sum()
+    }
+}
+
+/// Storage key for the schemas and row counts remembered across sessions, kept apart from the session itself.
+const FILE_FACTS_KEY: &str = "file_facts";
+
+/// How often the menu bar's memory readout is refreshed.
+const MEMORY_POLL: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// How long a tab title stays highlighted after reopening its file brings the tab forward.
+const TITLE_FLASH_SECS: f32 = 1.5;
+
+/// Pages with more rows than this can't be transposed; past it the columns get too many to read across.
+const TRANSPOSE_MAX_ROWS: usize = 50;
+
+/// Rows a scroll-mode tab loads at a time, and how many such blocks it keeps.
+const SCROLL_BLOCK_ROWS: usize = 2000;
+const SCROLL_BLOCKS_KEPT: usize = 16;
+
+/// A tab's loaded page as a query, so it can be fetched again after the tab has moved on.
+struct PageQuery {
+    path: String,
+    filter: Option<String>,
+    sort: Option<String>,
+    limit: usize,
+    offset: usize,
+}
+
+impl PageQuery {
+    fn of(tab: &Tab) -> Self {
+        let filter = Some(tab.filter.clone()).filter(|f| !f.trim().is_empty());
+        let sort = Some(tab.sort.clone()).filter(|s| !s.trim().is_empty());
+        Self { path: tab.path.clone(), filter, sort, limit: tab.page_size, offset: (tab.current_page - 1) * tab.page_size }
+    }
+
+    /// Fetches the page with every value whole on a backend worker and sends what `finish` makes of it.
+    fn fetch_full(self, tx: mpsc::Sender<BackendMessage>, backend: Arc<Backend>, finish: impl FnOnce(&[String], &PageData) -> BackendMessage + Send + 'static) {
+        backend.clone().submit(&self.path.clone(), None, move || {
+            // Its own request id, so the grid's progress and errors are left alone
+            let msg = match backend.run_query_full(next_request(), self.path, self.filter, self.sort, Some(self.limit), Some(self.offset)) {
+                Ok(BackendMessage::QueryData { columns, batch, .. }) => finish(&columns, &PageData::new(batch)),
+                Ok(BackendMessage::Error { message, sql, .. }) => BackendMessage::Error { request: None, path: None, message, sql },
+                Ok(msg) => msg,
+                Err(e) => BackendMessage::Error { request: None, path: None, message: e, sql: None },
+            };
+            let _ = tx.send(msg);
+        });
+    }
+}
+
+/// Blocks of rows loaded for scroll mode, keyed by block number, least recently used first.
+#[derive(Default)]
+struct ScrollBlocks {
+    blocks: VecDeque<(usize, PageData)>,
+    /// The one block load in flight. The next waits for it, so dragging the scrollbar a long way never queues
+    /// the blocks it passed over.
+    loading: Option<(RequestId, usize)>,
+    /// Block loads still running when the blocks were cleared; their rows belong to no view and are dropped.
+    dropped: Vec<RequestId>,
+    /// Blocks whose load failed; they stay placeholders until the user retries them.
+    failed: Vec<usize>,
+    /// Rows drawn in the last frame.
+    shown: std::ops::Range<usize>,
+}
+
+impl ScrollBlocks {
+    fn get(&self, block: usize) -> Option<&PageData> {
+        self.blocks.iter().find(|(b, _)| *b == block).map(|(_, d)| d)
+    }
+
+    fn touch(&mut self, block: usize) {
+        if let Some(i) = self.blocks.iter().position(|(b, _)| *b == block) { if let Some(entry) = self.blocks.remove(i) { self.blocks.push_back(entry); } }
+    }
+
+    fn loaded(&mut self, data: PageData) {
+        let Some((_, block)) = self.loading.take() else { return };
+        self.blocks.push_back((block, data));
+        while self.blocks.len() > SCROLL_BLOCKS_KEPT { self.blocks.pop_front(); }
+    }
+
+    /// Marks the block in flight as failed.
+    fn failed(&mut self) {
+        if let Some((_, block)) = self.loading.take() { self.failed.push(block); }
+    }
+
+    /// Whether a block still needs loading; failed ones wait for a retry.
+    fn missing(&self, block: usize) -> bool {
+        self.get(block).is_none() && !self.failed.contains(&block)
+    }
+
+    /// Whether `request` is a block load that was cleared while it ran, forgetting it once it has answered.
+    fn was_dropped(&mut self, request: RequestId) -> bool {
+        let before = self.dropped.len();
+        self.dropped.retain(|&r| r != request);
+        self.dropped.len() != before
+    }
+
+    fn clear(&mut self) {
+        self.blocks.clear(); self.failed.clear();
+        if let Some((request, _)) = self.loading.take() { self.dropped.push(request); }
+    }
+}
+
+/// What a native file dialog was opened for; its answer comes back as `BackendMessage::FileDialog`.
+enum PendingDialog { Open, Schema(String), Profile(String), Export(ExportDialog), ExportView(String), ImportView(String) }
+
+/// Local paths of the files a dialog returned.
+fn handle_paths(handles: impl IntoIterator<Item = rfd::FileHandle>) -> Vec<std::path::PathBuf> {
+    handles.into_iter().map(|h| h.path().to_path_buf()).collect()
+}
+
+struct ExportDialog {
+    path: String,
+    format: ExportFormat,
+    scope: ExportScope,
+    overflow: XlsxOverflow,
+    row_limit: String,
+    table_name: String,
+    sql_max_rows: String,
+    /// Only offered for the whole result.
+    split: ExportSplit,
+    /// Leave out the columns hidden in the grid.
+    visible_only: bool,
+}
+
+impl ExportDialog {
+    fn new(path: String) -> Self {
+        let table_name = sql_table_name(file_stem(&path).unwrap_or("data"));
+        Self { path, format: ExportFormat::Csv, scope: ExportScope::WholeResult, overflow: XlsxOverflow::FirstRows, row_limit: EXCEL_MAX_DATA_ROWS.to_string(), table_name, sql_max_rows: SQL_DEFAULT_MAX_ROWS.to_string(), split: ExportSplit::Single, visible_only: true }
+    }
+}
+
+/// Columns of the Help > Performance table, also the header of its CSV copy.
+const PERF_COLUMNS: [&str; 7] = ["Started", "Kind", "File", "Duration (ms)", "Rows", "Result", "Query"];
+
+/// Help > Performance while it's open: the column its job table is sorted by, and which way.
+struct PerfPanel {
+    sort: usize,
+    descending: bool,
+}
+
+/// A recorded job as the cells of a Help > Performance row.
+fn perf_cells(record: &PerfRecord, name: &str) -> Vec<String> {
+    vec![
+        chrono::DateTime::<chrono::Local>::from(record.started).format("%H:%M:%S%.3f").to_string(),
+        record.kind.to_string(),
+        name.to_string(),
+        format!("{:.1}", record.duration.as_secs_f64() * 1000.0),
+        record.rows.map(|r| r.to_string()).unwrap_or_default(),
+        record.failure.clone().unwrap_or_else(|| "OK".to_string()),
+        record.sql.clone(),
+    ]
+}
+
+/// Draft DuckDB settings being edited in File > Settings; they reach `Settings` only once DuckDB accepts them.
+struct SettingsDialog {
+    memory_limit: String,
+    threads: usize,
+    temp_directory: String,
+    session_sql: String,
+    extensions: String,
+    error: Option<String>,
+}
+
+impl SettingsDialog {
+    fn new(settings: &Settings) -> Self {
+        Self { memory_limit: settings.duckdb_memory_limit.clone(), threads: settings.duckdb_threads, temp_directory: settings.duckdb_temp_directory.clone(), session_sql: settings.duckdb_session_sql.clone(), extensions: settings.duckdb_extensions.clone(), error: None }
+    }
+}
+
+/// INSERT scripts are for small repro slices; larger exports are capped unless the user raises the limit.
+const SQL_DEFAULT_MAX_ROWS: usize = 10_000;
+
+/// One entry in the Transfers popover; finished jobs stay listed until cleared.
+struct ExportJob {
+    id: usize,
+    file: String,
+    rows_done: usize,
+    bytes_written: u64,
+    /// Parts written and expected, for split exports.
+    files: Option<(usize, usize)>,
+    started: std::time::Instant,
+    cancel: Arc<ExportCancel>,
+    /// Elapsed time once the job ended, plus the error if it failed or was cancelled.
+    finished: Option<(std::time::Duration, Option<String>)>,
+}
+
 fn main() -> eframe::Result<()> {
     let icon_bytes = include_bytes!("../assets/icon.png");
     let icon = match image::load_from_memory(icon_bytes) {
@@ -32,6 +284,16 @@ fn main() -> eframe::Result<()> {
         Err(_) => None,
     };
 
+    // `parquetgrip -` reads a piped file; other arguments are opened as paths
+    let mut temp_files = Vec::new();
+    let paths: Vec<String> = std::env::args().skip(1).filter_map(|a| {
+        if a != "-" { return Some(a); }
+        match buffer_stdin() {
+            Ok(p) => { temp_files.push(p.clone()); Some(p.to_string_lossy().to_string()) }
+            Err(e) => { eprintln!("parquetgrip: cannot read stdin: {}", e); None }
+        }
+    }).collect();
+
     let native_options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
             .with_inner_size([1024.0, 768.0])
@@ -43,43 +305,377 @@ fn main() -> eframe::Result<()> {
     eframe::run_native(
         "ParquetGrip",
         native_options,
-        Box::new(|cc| Ok(Box::new(ParquetApp::new(cc)))),
+        Box::new(|cc| Ok(Box::new(ParquetApp::new(cc, paths, temp_files)))),
     )
 }
 
+/// DuckDB can't scan a pipe, so stdin is copied to a temp file named after the format its first bytes suggest.
+fn buffer_stdin() -> std::io::Result<std::path::PathBuf> {
+    use std::io::{IsTerminal, Read, Write};
+    let mut stdin = std::io::stdin().lock();
+    if stdin.is_terminal() { return Err(std::io::Error::other("nothing is piped in; try `cat data.parquet | parquetgrip -`")); }
+    // Only the magic bytes are read up front; the rest streams straight to disk, however big the pipe
+    let mut head = Vec::new();
+    stdin.by_ref().take(4).read_to_end(&mut head)?;
+    let ext = if head.starts_with(b"PAR1") { "parquet" } else if head.first().is_some_and(|b| *b == b'[' || *b == b'{') { "json" } else { "csv" };
+    let path = std::env::temp_dir().join(format!("parquetgrip-stdin-{}.{}", std::process::id(), ext));
+    let mut file = std::fs::File::create(&path)?;
+    file.write_all(&head)?;
+    std::io::copy(&mut stdin, &mut file)?;
+    Ok(path)
+}
+
+/// A named filter, sort and column layout that can be applied to any tab.
+#[derive(Serialize, Deserialize, Clone)]
+struct SavedView {
+    name: String,
+    filter: String,
+    sort: String,
+    column_order: Vec<String>,
+    hidden_columns: Vec<String>,
+}
+
+/// A view written to a file to share, with the page it was on and the schema it was made against so importing
+/// it elsewhere can tell what changed.
+#[derive(Serialize, Deserialize)]
+struct ViewFile {
+    #[serde(flatten)]
+    view: SavedView,
+    source: String,
+    fields: Vec<SchemaField>,
+    page: Option<usize>,
+    page_size: Option<usize>,
+}
+
+impl ViewFile {
+    /// Columns of the view's schema that `fields` lacks or types differently.
+    fn mismatches(&self, fields: &[SchemaField]) -> Vec<String> {
+        self.fields.iter().filter_map(|f| match fields.iter().find(|c| c.name == f.name) {
+            None => Some(format!("{} is missing", f.name)),
+            Some(c) if c.data_type != f.data_type => Some(format!("{} is {} here, {} in the view", f.name, c.data_type, f.data_type)),
+            Some(_) => None,
+        }).collect()
+    }
+}
+
+/// Where a tab's loading has got to. Message handling moves it along with `next`, and `text` is the one place
+/// it is put into words.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+enum LoadState {
+    #[default]
+    Idle,
+    Opening,
+    Reopening,
+    FileBusy { attempt: usize, attempts: usize },
+    ReadingSchema,
+    Counting,
+    FirstPage,
+    LoadingPage { page: usize },
+    Applying,
+    Failed,
+}
+
+/// What a backend answer means for `LoadState`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum LoadEvent { FileOpened, Schema, Counted, PageLoaded, Failed }
+
+impl LoadState {
+    /// Steps of opening a file; DuckDB doesn't report how far a scan has got, so these stand in.
+    const OPEN_STEPS: usize = 4;
+
+    /// Opening only ever moves forward, so a schema that never arrives can't hold up the count behind it, and
+    /// a page or a failure always ends the load.
+    fn next(self, event: LoadEvent) -> Self {
+        use LoadState::*;
+        match (self, event) {
+            (_, LoadEvent::Failed) => Failed,
+            (_, LoadEvent::PageLoaded) => Idle,
+            (Opening | Reopening | FileBusy { .. }, LoadEvent::FileOpened) => ReadingSchema,
+            (Opening | Reopening | FileBusy { .. } | ReadingSchema, LoadEvent::Schema) => Counting,
+            (Opening | Reopening | FileBusy { .. } | ReadingSchema | Counting, LoadEvent::Counted) => FirstPage,
+            (state, _) => state,
+        }
+    }
+
+    fn is_loading(self) -> bool { !matches!(self, LoadState::Idle | LoadState::Failed) }
+
+    /// Which of `OPEN_STEPS` is running while a file is being opened.
+    fn open_step(self) -> Option<usize> {
+        match self {
+            LoadState::Opening | LoadState::Reopening | LoadState::FileBusy { .. } => Some(0),
+            LoadState::ReadingSchema => Some(1),
+            LoadState::Counting => Some(2),
+            LoadState::FirstPage => Some(3),
+            _ => None,
+        }
+    }
+
+    fn text(self) -> String {
+        match self {
+            LoadState::Idle => String::new(),
+            LoadState::Opening => "Opening...".to_string(),
+            LoadState::Reopening => "Reloading...".to_string(),
+            LoadState::FileBusy { attempt, attempts } => format!("File is busy, retrying… ({}/{})", attempt, attempts),
+            LoadState::ReadingSchema => "Reading schema...".to_string(),
+            LoadState::Counting => "Counting rows...".to_string(),
+            LoadState::FirstPage => "Loading first page...".to_string(),
+            LoadState::LoadingPage { page } => format!("Loading {}...", page),
+            LoadState::Applying => "Applying...".to_string(),
+            LoadState::Failed => "Error".to_string(),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 struct Tab {
     path: String,
     name: String,
+    /// A merged tab's files are lined up by column name rather than position, a file missing a column reading it as NULL.
+    #[serde(default)]
+    union_by_name: bool,
     #[serde(skip)]
     schema: Vec<String>,
+    /// Column names of the loaded page as the query returned them; the grid and page copies follow these.
+    #[serde(skip)]
+    columns: Vec<String>,
     #[serde(skip)]
-    data: Vec<Vec<String>>,
+    fields: Vec<SchemaField>,
+    #[serde(skip)]
+    data: PageData,
     #[serde(skip)]
     row_count: usize,
     #[serde(skip)]
-    status: String,
+    state: LoadState,
     current_page: usize,
     page_size: usize,
     #[serde(skip)]
     total_rows: usize,
     filter: String,
     sort: String,
+    /// Display overrides keyed by column name.
+    #[serde(default)]
+    column_formats: HashMap<String, ColumnFormat>,
+    /// Conditional row tints, first match wins.
+    #[serde(default)]
+    row_rules: Vec<RowRule>,
+    /// Display order of columns by name, set by dragging headers; empty means file order.
+    #[serde(default)]
+    column_order: Vec<String>,
+    /// Columns left out of the grid, by name.
+    #[serde(default)]
+    hidden_columns: Vec<String>,
+    #[serde(skip)]
+    show_rules: bool,
     #[serde(skip)]
     last_error: Option<String>,
+    /// A column the sort names that the file doesn't have, found before running it.
+    #[serde(skip)]
+    sort_unknown: Option<String>,
+    /// The load `last_error` came from; answers to later loads clear it, ones racing it under the same id don't.
+    #[serde(skip)]
+    error_request: RequestId,
     #[serde(skip)]
     jump_page_buffer: String,
+    #[serde(skip)]
+    col_widths: Vec<f32>,
+    /// Selected rows as global indices into the filtered/sorted result, so selections survive paging.
+    #[serde(skip)]
+    selected_rows: BTreeSet<usize>,
+    #[serde(skip)]
+    selected_cols: BTreeSet<usize>,
+    #[serde(skip)]
+    selection_anchor: Option<usize>,
+    #[serde(skip)]
+    selected_cells: Option<CellSelection>,
+    #[serde(skip)]
+    cell_detail: Option<CellDetail>,
+    /// Column waiting for the user to confirm a large whole-result copy.
+    #[serde(skip)]
+    pending_column_copy: Option<String>,
+    /// Format and row limit of a copy of every row, waiting for the user to confirm it.
+    #[serde(skip)]
+    pending_rows_copy: Option<(ExportFormat, usize)>,
+    /// Latest page and row-count loads issued; answers to older ones are dropped.
+    #[serde(skip)]
+    page_request: RequestId,
+    #[serde(skip)]
+    count_request: RequestId,
+    /// Request whose row count `total_rows` holds, so a count still running isn't taken for the page's.
+    #[serde(skip)]
+    counted: RequestId,
+    /// The latest row count hasn't answered yet, so `total_rows` may be stale or zero.
+    #[serde(skip)]
+    count_pending: bool,
+    /// The latest row count failed, e.g. on a filter typo, so `total_rows` belongs to an older query.
+    #[serde(skip)]
+    count_failed: bool,
+    /// The file was opened without counting its rows, and nobody has asked for the count since.
+    #[serde(skip)]
+    count_skipped: bool,
+    /// The page on screen stopped at the row cap.
+    #[serde(skip)]
+    truncated: bool,
+    /// Rows the count says the page on screen should have had, when it came back with fewer.
+    #[serde(skip)]
+    short_page: Option<usize>,
+    /// Parquet row-group layout, fetched the first time the row-group map is shown.
+    #[serde(skip)]
+    row_groups: Option<Vec<RowGroupInfo>>,
+    /// The Ctrl+G "jump to column" input while it is open.
+    #[serde(skip)]
+    column_jump: Option<String>,
+    /// Left and right edge of each header cell from the last frame, relative to the start of the scrolled content.
+    #[serde(skip)]
+    col_spans: Vec<(f32, f32)>,
+    #[serde(skip)]
+    scroll_to_x: Option<f32>,
+    /// Column order and first laid-out column the grid's stored widths belong to.
+    #[serde(skip)]
+    grid_layout: Option<(Vec<usize>, usize)>,
+    /// Column index briefly highlighted after a jump, with the time it started.
+    #[serde(skip)]
+    col_flash: Option<(usize, f64)>,
+    /// When the tab was last brought forward by reopening its file, so its title can flash.
+    #[serde(skip)]
+    title_flash: Option<std::time::Instant>,
+    /// Time of the last filter/sort keystroke not yet checked by the live filter.
+    #[serde(skip)]
+    live_edit: Option<f64>,
+    #[serde(skip)]
+    live_check: RequestId,
+    /// Why the filter being typed doesn't bind, shown instead of failing the grid.
+    #[serde(skip)]
+    live_error: Option<String>,
+    /// The condition being edited in the filter builder while it is open.
+    #[serde(skip)]
+    filter_builder: Option<FilterTerm>,
+    /// The file has been copied into an in-memory table that grid queries read instead.
+    #[serde(skip)]
+    in_memory: bool,
+    #[serde(skip)]
+    caching: bool,
+    #[serde(skip)]
+    page_cache: PageCache,
+    /// The query behind the page on screen, and the one a page load in flight was issued for.
+    #[serde(skip)]
+    shown_key: Option<PageKey>,
+    #[serde(skip)]
+    loading_key: Option<(RequestId, PageKey)>,
+    /// Latest progress of the slow grid query in flight: its request, percent done if known, and time so far.
+    #[serde(skip)]
+    progress: Option<(RequestId, Option<f32>, std::time::Duration)>,
+    /// The page load in flight and when the grid first showed it loading, for the elapsed counter.
+    #[serde(skip)]
+    load_started: Option<(RequestId, std::time::Instant)>,
+    /// The row count in flight and when it was first seen, for its own elapsed counter.
+    #[serde(skip)]
+    count_started: Option<(RequestId, std::time::Instant)>,
+    /// How long the latest page loads and counts took, each with which of the two it was, newest last.
+    #[serde(skip)]
+    load_times: VecDeque<(&'static str, std::time::Duration)>,
+    /// Name typed in the Views menu for saving the current state.
+    #[serde(skip)]
+    view_name: String,
+    /// One scrollbar over the whole result, loading rows as they come into view, instead of pages.
+    #[serde(default)]
+    scroll_mode: bool,
+    #[serde(skip)]
+    scroll_blocks: ScrollBlocks,
+    /// The page load whose rows are streaming into `data`; its complete answer replaces them.
+    #[serde(skip)]
+    streaming: Option<RequestId>,
+    /// Show the page with each column as a row, for wide files with only a few rows.
+    #[serde(default)]
+    transposed: bool,
 }
 
 impl Tab {
     fn new(path: String) -> Self {
-        let name = std::path::Path::new(&path).file_name().and_then(|n| n.to_str()).unwrap_or(&path).to_string();
+        let files = source_files(&path);
+        let name = match dataset_dir(files[0]) {
+            Some(dir) => format!("{}/ (dataset)", dir.file_name().and_then(|n| n.to_str()).unwrap_or(files[0])),
+            None => std::path::Path::new(files[0]).file_name().and_then(|n| n.to_str()).unwrap_or(files[0]).to_string(),
+        };
+        let name = if files.len() > 1 { format!("{} + {} more", name, files.len() - 1) } else { name };
         Self {
-            path, name, schema: Vec::new(), data: Vec::new(), row_count: 0, status: "Opening...".to_string(),
+            path, name, union_by_name: false, schema: Vec::new(), columns: Vec::new(), fields: Vec::new(), data: PageData::default(), row_count: 0, state: LoadState::Opening,
             current_page: 1, page_size: 1000, total_rows: 0, filter: String::new(), sort: String::new(),
-            last_error: None, jump_page_buffer: "1".to_string(),
+            column_formats: HashMap::new(), row_rules: Vec::new(), column_order: Vec::new(), hidden_columns: Vec::new(), show_rules: false, last_error: None, sort_unknown: None, error_request: 0, jump_page_buffer: "1".to_string(), col_widths: Vec::new(),
+            selected_rows: BTreeSet::new(), selected_cols: BTreeSet::new(), selection_anchor: None, selected_cells: None,
+            cell_detail: None, pending_column_copy: None, pending_rows_copy: None, page_request: 0, count_request: 0, counted: 0, count_pending: true, count_failed: false, count_skipped: false, truncated: false, short_page: None, row_groups: None, column_jump: None, col_spans: Vec::new(), scroll_to_x: None, grid_layout: None, col_flash: None, title_flash: None, live_edit: None, live_check: 0, live_error: None, filter_builder: None, in_memory: false, caching: false, page_cache: PageCache::default(), shown_key: None, loading_key: None, progress: None, load_started: None, count_started: None, load_times: VecDeque::new(), view_name: String::new(), scroll_mode: false, scroll_blocks: ScrollBlocks::default(), streaming: None, transposed: false,
+        }
+    }
+
+    fn view(&self, name: String) -> SavedView {
+        SavedView { name, filter: self.filter.clone(), sort: self.sort.clone(), column_order: self.column_order.clone(), hidden_columns: self.hidden_columns.clone() }
+    }
+
+    /// Takes a saved view's filter, sort and columns; the caller applies the filter.
+    fn apply_view(&mut self, view: &SavedView) {
+        self.filter = view.filter.clone(); self.sort = view.sort.clone();
+        self.column_order = view.column_order.clone(); self.hidden_columns = view.hidden_columns.clone();
+        self.live_edit = None; self.live_error = None; self.live_check = 0;
+    }
+
+    /// Indices into `columns` in display order, without the hidden ones.
+    fn visible_order(&self) -> Vec<usize> {
+        display_order(&self.columns, &self.column_order).into_iter().filter(|&c| !self.hidden_columns.contains(&self.columns[c])).collect()
+    }
+
+    fn total_pages(&self) -> usize { page_count(self.total_rows, self.page_size) }
+
+    /// Drops the error shown once a load issued after the one that failed has answered.
+    fn clear_error_before(&mut self, request: RequestId) {
+        if self.last_error.is_some() && self.error_request < request {
+            self.last_error = None;
+            if self.state == LoadState::Failed { self.state = LoadState::Idle; }
         }
     }
+
+    /// `total_rows` answers the query on screen: its count has come back and didn't fail.
+    fn count_known(&self) -> bool { !self.count_pending && !self.count_failed && !self.count_skipped }
+
+    /// Whether Next has a page to go to; without a count, a full page is taken to mean there may be more.
+    fn has_next_page(&self) -> bool {
+        if self.count_skipped { self.data.len() == self.page_size } else { self.count_known() && self.current_page < self.total_pages() }
+    }
+
+    /// Compares the page on screen with the row count once both answer the latest loads; a page short of it
+    /// means the file changed since it was counted.
+    fn check_page_rows(&mut self) {
+        let expected = self.total_rows.saturating_sub((self.current_page - 1) * self.page_size).min(self.page_size);
+        self.short_page = (!self.truncated && self.streaming.is_none() && self.counted >= self.count_request && self.row_count < expected).then_some(expected);
+    }
+
+    /// Ends the progress readout once `request` has answered, whether with rows or an error.
+    fn clear_progress(&mut self, request: RequestId) {
+        if self.progress.is_some_and(|(r, _, _)| r == request) { self.progress = None; }
+    }
+
+    /// Stops the elapsed counter of the page load `request` answered, keeping how long it took.
+    fn page_answered(&mut self, request: RequestId) {
+        if let Some((_, started)) = self.load_started.take_if(|(r, _)| *r == request) { self.keep_load_time("page", started); }
+    }
+
+    /// Stops the elapsed counter of the row count `request` answered, keeping how long it took. A page and
+    /// its count share a request id, so each has its own counter.
+    fn count_answered(&mut self, request: RequestId) {
+        if let Some((_, started)) = self.count_started.take_if(|(r, _)| *r == request) { self.keep_load_time("count", started); }
+    }
+
+    fn keep_load_time(&mut self, what: &'static str, started: std::time::Instant) {
+        self.load_times.push_back((what, started.elapsed()));
+        if self.load_times.len() > 20 { self.load_times.pop_front(); }
+    }
+
+    /// Starts the elapsed counters the first frame a page load or a row count is seen in flight.
+    fn track_load(&mut self) {
+        let loading = self.state.is_loading() && self.last_error.is_none();
+        if !loading { self.load_started = None; }
+        else if self.load_started.is_none_or(|(r, _)| r != self.page_request) { self.load_started = Some((self.page_request, std::time::Instant::now())); }
+        if !self.count_pending { self.count_started = None; }
+        else if self.count_started.is_none_or(|(r, _)| r != self.count_request) { self.count_started = Some((self.count_request, std::time::Instant::now())); }
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -93,10 +689,59 @@ struct ParquetApp {
     tx_to_ui: mpsc::Sender<BackendMessage>,
     tabs: HashMap<String, Tab>,
     dock_state: DockState<String>,
+    settings: Settings,
     #[serde(skip)]
     logs: Vec<LogEntry>,
     #[serde(skip)]
     show_console: bool,
+    #[serde(skip)]
+    export_dialog: Option<ExportDialog>,
+    #[serde(skip)]
+    exports: Vec<ExportJob>,
+    #[serde(skip)]
+    next_job_id: usize,
+    #[serde(skip)]
+    paste_to_open: bool,
+    /// Help > About text while the dialog is open.
+    #[serde(skip)]
+    about: Option<Vec<(&'static str, String)>>,
+    #[serde(skip)]
+    settings_dialog: Option<SettingsDialog>,
+    #[serde(skip)]
+    perf_panel: Option<PerfPanel>,
+    /// A close was held back because exports are still running, waiting on the user's choice.
+    #[serde(skip)]
+    quit_prompt: bool,
+    /// Close the window as soon as the last running export ends.
+    #[serde(skip)]
+    quit_after_exports: bool,
+    /// Latest memory readings from `BackendMessage::MemoryUsage`: DuckDB's, spilled, and the process's.
+    #[serde(skip)]
+    memory: Option<(u64, u64, Option<u64>)>,
+    /// When memory was last asked for; it's read again every `MEMORY_POLL`.
+    #[serde(skip)]
+    memory_polled: Option<std::time::Instant>,
+    /// Set while a memory reading is under way, so a slow one isn't joined by another every poll.
+    #[serde(skip)]
+    memory_polling: Arc<std::sync::atomic::AtomicBool>,
+    /// Last title sent to the window, so it is only set again when the focused tab changes.
+    #[serde(skip)]
+    window_title: String,
+    /// Files picked together, waiting on the choice between separate tabs and one merged table, and whether
+    /// merging matches their columns by name.
+    #[serde(skip)]
+    merge_prompt: Option<(Vec<String>, bool)>,
+    /// Files opened from dataset folders, each waiting in turn on the choice between it alone and the folder it
+    /// belongs to.
+    #[serde(skip)]
+    dataset_prompts: VecDeque<(String, std::path::PathBuf)>,
+    /// Buffered stdin files; never persisted and deleted on exit.
+    #[serde(skip)]
+    temp_files: Vec<std::path::PathBuf>,
+    /// Native file dialogs still open, by the request id their answer carries.
+    #[serde(skip)]
+    dialogs: HashMap<RequestId, PendingDialog>,
+    views: Vec<SavedView>,
 }
 
 impl Default for ParquetApp {
@@ -104,148 +749,899 @@ impl Default for ParquetApp {
         let (tx, rx) = mpsc::channel();
         Self {
             backend: Arc::new(Backend::new()), rx, tx_to_ui: tx, tabs: HashMap::new(),
-            dock_state: DockState::new(Vec::new()), logs: Vec::new(), show_console: false,
+            dock_state: DockState::new(Vec::new()), settings: Settings::default(), logs: Vec::new(), show_console: false,
+            export_dialog: None, exports: Vec::new(), next_job_id: 0, paste_to_open: false, about: None, settings_dialog: None, perf_panel: None, quit_prompt: false, quit_after_exports: false, window_title: String::new(), memory: None, memory_polled: None, memory_polling: Arc::default(), merge_prompt: None, dataset_prompts: VecDeque::new(), temp_files: Vec::new(), dialogs: HashMap::new(), views: Vec::new(),
         }
     }
 }
 
 impl ParquetApp {
-    fn new(cc: &eframe::CreationContext<'_>) -> Self {
+    fn new(cc: &eframe::CreationContext<'_>, paths: Vec<String>, temp_files: Vec<std::path::PathBuf>) -> Self {
         let mut visuals = egui::Visuals::dark();
         visuals.selection.bg_fill = egui::Color32::from_rgb(0, 120, 215); 
         cc.egui_ctx.set_visuals(visuals);
         setup_fonts(&cc.egui_ctx);
         
         let mut app: Self = if let Some(storage) = cc.storage { eframe::get_value(storage, eframe::APP_KEY).unwrap_or_default() } else { Default::default() };
-        let (tx, rx) = mpsc::channel();
+        // Every sender goes through a relay that wakes the UI, since `update` only drains the channel when a frame
+        // runs and an idle window would otherwise sit on finished results until the next input event
+        let repaint = cc.egui_ctx.clone();
+        let (tx, rx) = waking_channel(move || repaint.request_repaint());
         app.tx_to_ui = tx; app.rx = rx; app.backend = Arc::new(Backend::new());
+        app.backend.set_progress_sink(app.tx_to_ui.clone());
+        app.backend.set_query_timeout(app.settings.query_timeout_secs);
+        app.backend.set_cell_fetch_chars(app.settings.cell_fetch_chars);
+        app.backend.set_row_cap(app.settings.max_result_rows);
+        if let Err(e) = app.backend.warm_up(app.settings.engine(), app.settings.extensions(), app.tx_to_ui.clone()) {
+            app.logs.push(LogEntry { time: chrono::Local::now().format("%H:%M:%S").to_string(), path: "Settings".to_string(), sql: "Apply DuckDB settings".to_string(), error: Some(e) });
+        }
+        match app.backend.prepare_spill_directory() {
+            Ok(0) => {}
+            Ok(n) => app.logs.push(LogEntry { time: chrono::Local::now().format("%H:%M:%S").to_string(), path: "Settings".to_string(), sql: format!("Removed {} spill files left by an earlier session", n), error: None }),
+            Err(e) => app.logs.push(LogEntry { time: chrono::Local::now().format("%H:%M:%S").to_string(), path: "Settings".to_string(), sql: "Prepare temp directory".to_string(), error: Some(e) }),
+        }
 
-        for (path, tab) in app.tabs.iter_mut() {
-            tab.last_error = None; tab.status = "Reloading...".to_string();
-            let (tx_c, b_c, path_c, f_c, s_c, p, ps) = (app.tx_to_ui.clone(), app.backend.clone(), path.clone(), tab.filter.clone(), tab.sort.clone(), tab.current_page, tab.page_size);
-            std::thread::spawn(move || {
-                let _ = tx_c.send(BackendMessage::FileOpened { path: path_c.clone() });
-                if let Ok(s_msg) = b_c.get_schema(path_c.clone()) { let _ = tx_c.send(s_msg); }
-                let f = if f_c.trim().is_empty() { None } else { Some(f_c.clone()) };
-                if let Ok(msg) = b_c.get_row_count(path_c.clone(), f.clone()) { let _ = tx_c.send(msg); }
-                let s = if s_c.trim().is_empty() { None } else { Some(s_c) };
-                let offset = (p - 1) * ps;
-                if let Ok(q_msg) = b_c.run_query(path_c, f, s, Some(ps), Some(offset)) { let _ = tx_c.send(q_msg); }
-            });
+        // Schemas and row counts of files unchanged since the last session come back without asking DuckDB
+        if let Some(facts) = cc.storage.and_then(|s| eframe::get_value(s, FILE_FACTS_KEY)) { app.backend.set_file_facts(facts); }
+        for tab in app.tabs.values_mut() { ParquetTabViewer::reopen(app.tx_to_ui.clone(), app.backend.clone(), tab, !app.settings.skip_open_count); }
+        // Tabs skipped by save() (buffered stdin) leave dangling ids in the restored layout
+        app.dock_state.retain_tabs(|id| app.tabs.contains_key(id));
+        for path in paths {
+            let from_stdin = temp_files.iter().any(|t| t.to_string_lossy() == path);
+            app.open_path(path.clone());
+            if from_stdin { if let Some(t) = app.tabs.get_mut(&path) { t.name = "stdin".to_string(); } }
         }
+        app.temp_files = temp_files;
         app
     }
 
+    /// Waits for a native dialog on a helper thread so the window keeps rendering while it's up. An empty
+    /// answer means it was cancelled.
+    fn await_dialog(&mut self, pending: PendingDialog, answer: impl std::future::Future<Output = Vec<std::path::PathBuf>> + Send + 'static) {
+        let request = next_request();
+        self.dialogs.insert(request, pending);
+        let tx_c = self.tx_to_ui.clone();
+        std::thread::spawn(move || { let paths = pollster::block_on(answer); let _ = tx_c.send(BackendMessage::FileDialog { request, paths }); });
+    }
+
     fn open_file_dialog(&mut self) {
-        let (backend, tx) = (self.backend.clone(), self.tx_to_ui.clone());
-        if let Some(paths) = rfd::FileDialog::new().add_filter("Data Files", &["parquet", "pqt", "csv", "json", "gz"]).pick_files() {
-            for path_buf in paths {
-                let path = path_buf.to_string_lossy().to_string();
-                if !self.tabs.contains_key(&path) {
-                    self.tabs.insert(path.clone(), Tab::new(path.clone()));
-                    self.dock_state.push_to_focused_leaf(path.clone());
-                    let (b_c, tx_c, p_c) = (backend.clone(), tx.clone(), path.clone());
-                    std::thread::spawn(move || {
-                        match b_c.open_file(p_c.clone()) {
-                            Ok(msg) => {
-                                let _ = tx_c.send(msg);
-                                if let Ok(s_msg) = b_c.get_schema(p_c.clone()) { let _ = tx_c.send(s_msg); }
-                                if let Ok(msg) = b_c.get_row_count(p_c.clone(), None) { let _ = tx_c.send(msg); }
-                                if let Ok(q_msg) = b_c.run_query(p_c, None, None, Some(1000), Some(0)) { let _ = tx_c.send(q_msg); }
-                            }
-                            Err(e) => { let _ = tx_c.send(BackendMessage::Error { path: Some(p_c), message: e, sql: None }); }
-                        }
-                    });
-                }
+        let picked = rfd::AsyncFileDialog::new().add_filter("Data Files", &self.settings.open_file_types()).add_filter("All Files", &["*"]).pick_files();
+        self.await_dialog(PendingDialog::Open, async move { handle_paths(picked.await.into_iter().flatten()) });
+    }
+
+    fn dialog_answered(&mut self, pending: PendingDialog, paths: Vec<std::path::PathBuf>) {
+        let Some(target) = paths.first().cloned() else { return };
+        match pending {
+            PendingDialog::Open => {
+                let paths: Vec<String> = paths.iter().map(|p| p.to_string_lossy().to_string()).collect();
+                if paths.len() > 1 { self.merge_prompt = Some((paths, false)); } else { for path in paths { self.open_path(path); } }
             }
+            PendingDialog::Schema(path) => self.write_schema(&path, target),
+            PendingDialog::Profile(path) => self.write_profile(&path, target),
+            PendingDialog::Export(dialog) => self.run_export(dialog, target),
+            PendingDialog::ExportView(path) => self.write_view(&path, target),
+            PendingDialog::ImportView(path) => self.read_view(&path, target),
         }
     }
-}
 
-struct ParquetTabViewer<'a> { tabs: &'a mut HashMap<String, Tab>, tx: mpsc::Sender<BackendMessage>, backend: Arc<Backend> }
+    /// Opens a local path or URL in a new tab, or brings its tab forward if it is already open. A file from a
+    /// dataset folder asks first whether to open the whole dataset instead.
+    fn open_path(&mut self, path: String) {
+        match dataset_root(&path) {
+            Some(root) if !self.tabs.contains_key(&path) && !self.tabs.contains_key(&dataset_path(&root)) => {
+                if !self.dataset_prompts.iter().any(|(file, _)| *file == path) { self.dataset_prompts.push_back((path, root)); }
+            }
+            _ => self.open_tab(path, false),
+        }
+    }
 
-impl<'a> ParquetTabViewer<'a> {
-    fn load_page(tx: mpsc::Sender<BackendMessage>, backend: Arc<Backend>, path: String, page: usize, page_size: usize, filter: String, sort: String) {
-        std::thread::spawn(move || {
-            let offset = (page - 1) * page_size;
-            let f = if filter.trim().is_empty() { None } else { Some(filter) };
-            let s = if sort.trim().is_empty() { None } else { Some(sort) };
-            match backend.run_query(path.clone(), f, s, Some(page_size), Some(offset)) {
-                Ok(msg) => { let _ = tx.send(msg); }
-                Err(e) => { let _ = tx.send(BackendMessage::Error { path: Some(path), message: e, sql: None }); }
+    fn open_tab(&mut self, path: String, union_by_name: bool) {
+        if self.tabs.contains_key(&path) { self.focus_tab(&path); return; }
+        let mut tab = Tab::new(path.clone());
+        tab.union_by_name = union_by_name;
+        let request = next_request(); tab.page_request = request; tab.count_request = request; tab.loading_key = Some((request, PageKey::of(&tab)));
+        let count = !self.settings.skip_open_count;
+        tab.count_skipped = !count; tab.count_pending = count;
+        self.tabs.insert(path.clone(), tab);
+        self.dock_state.push_to_focused_leaf(path.clone());
+        let (b_c, tx_c, p_c) = (self.backend.clone(), self.tx_to_ui.clone(), path.clone());
+        self.backend.submit(&path, None, move || {
+            match b_c.open_file(p_c.clone(), union_by_name) {
+                Ok(msg) => {
+                    let _ = tx_c.send(msg);
+                    if let Ok(s_msg) = b_c.get_schema(p_c.clone()) { let _ = tx_c.send(s_msg); }
+                    if count { if let Ok(msg) = b_c.get_row_count(request, p_c.clone(), None) { let _ = tx_c.send(msg); } }
+                    if let Ok(q_msg) = b_c.run_query(request, p_c, None, None, Some(1000), Some(0), Some(&tx_c)) { let _ = tx_c.send(q_msg); }
+                }
+                Err(e) => { let _ = tx_c.send(BackendMessage::Error { request: Some(request), path: Some(p_c), message: e, sql: None }); }
             }
         });
     }
 
-    fn refresh_data(tx: mpsc::Sender<BackendMessage>, backend: Arc<Backend>, path: String, filter: String, sort: String, page_size: usize) {
-        let (tx_c, b_c, p_c, f_c) = (tx.clone(), backend.clone(), path.clone(), filter.clone());
-        std::thread::spawn(move || {
-            let f = if f_c.trim().is_empty() { None } else { Some(f_c) };
-            match b_c.get_row_count(p_c.clone(), f) {
-                Ok(msg) => { let _ = tx_c.send(msg); }
-                Err(e) => { let _ = tx_c.send(BackendMessage::Error { path: Some(p_c), message: e, sql: None }); }
+    /// Makes an open tab the active one in its leaf, focuses that leaf and flashes the title so it can be spotted.
+    fn focus_tab(&mut self, path: &str) {
+        let Some((surface, node, index)) = self.dock_state.find_tab(&path.to_string()) else { return };
+        self.dock_state.set_active_tab((surface, node, index));
+        self.dock_state.set_focused_node_and_surface((surface, node));
+        if let Some(tab) = self.tabs.get_mut(path) { tab.title_flash = Some(std::time::Instant::now()); }
+    }
+
+    /// Opens pasted text if it is a single existing file path or a URL DuckDB can read.
+    fn open_pasted(&mut self, text: &str) {
+        match pasted_path(text) {
+            Some(path) => self.open_path(path),
+            None => {
+                let time = chrono::Local::now().format("%H:%M:%S").to_string();
+                self.logs.push(LogEntry { time, path: "Clipboard".to_string(), sql: "Open from Clipboard".to_string(), error: Some(format!("Not a file path or URL: {}", text.trim().chars().take(200).collect::<String>())) });
+                self.show_console = true;
             }
-        });
-        Self::load_page(tx, backend, path, 1, page_size, filter, sort);
+        }
     }
-}
 
-impl<'a> TabViewer for ParquetTabViewer<'a> {
-    type Tab = String;
+    fn export_schema(&mut self, path: &str) {
+        let Some(tab) = self.tabs.get(path) else { return };
+        let stem = file_stem(&tab.path).unwrap_or("schema");
+        let saved = rfd::AsyncFileDialog::new().add_filter("JSON (.json)", &["json"]).add_filter("Arrow IPC (.arrow)", &["arrow"]).set_file_name(format!("{}.schema.json", stem)).save_file();
+        self.await_dialog(PendingDialog::Schema(path.to_string()), async move { handle_paths(saved.await) });
+    }
 
-    fn title(&mut self, tab_id: &mut Self::Tab) -> egui::WidgetText {
-        if let Some(tab) = self.tabs.get(tab_id) {
-            if tab.name.chars().count() > 20 { format!("{}...", tab.name.chars().take(17).collect::<String>()).into() } else { tab.name.clone().into() }
-        } else { "Loading...".into() }
+    fn write_schema(&mut self, path: &str, target: std::path::PathBuf) {
+        let Some(tab) = self.tabs.get(path) else { return };
+        let result = export::write_schema(&target, &tab.path, &tab.fields);
+        let time = chrono::Local::now().format("%H:%M:%S").to_string();
+        self.logs.push(LogEntry { time, path: target.display().to_string(), sql: format!("Exported schema of {} ({} columns)", tab.name, tab.fields.len()), error: result.err() });
     }
 
-    fn on_close(&mut self, tab_id: &mut Self::Tab) -> OnCloseResponse { self.tabs.remove(tab_id); OnCloseResponse::Close }
+    fn export_view(&mut self, path: &str) {
+        let Some(tab) = self.tabs.get(path) else { return };
+        let stem = file_stem(&tab.path).unwrap_or("view");
+        let saved = rfd::AsyncFileDialog::new().add_filter("JSON (.json)", &["json"]).set_file_name(format!("{}.view.json", stem)).save_file();
+        self.await_dialog(PendingDialog::ExportView(path.to_string()), async move { handle_paths(saved.await) });
+    }
 
-    fn ui(&mut self, ui: &mut egui::Ui, tab_id: &mut Self::Tab) {
-        if let Some(tab) = self.tabs.get_mut(tab_id) {
-            ui.vertical(|ui| {
-                ui.add_space(4.0);
-                ui.horizontal(|ui| {
-                    ui.add_space(8.0); ui.label("WHERE");
-                    let f_in = ui.add(egui::TextEdit::singleline(&mut tab.filter).hint_text("filter").desired_width(200.0));
-                    ui.add_space(8.0); ui.label("ORDER BY");
-                    let s_in = ui.add(egui::TextEdit::singleline(&mut tab.sort).hint_text("sort").desired_width(150.0));
-                    if ui.button("Apply").clicked() || (f_in.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter))) || (s_in.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter))) {
-                        tab.current_page = 1; tab.jump_page_buffer = "1".to_string(); tab.status = "Applying...".to_string();
-                        Self::refresh_data(self.tx.clone(), self.backend.clone(), tab.path.clone(), tab.filter.clone(), tab.sort.clone(), tab.page_size);
-                    }
-                });
-                ui.add_space(4.0); ui.separator();
+    fn import_view(&mut self, path: &str) {
+        let picked = rfd::AsyncFileDialog::new().add_filter("JSON (.json)", &["json"]).add_filter("All Files", &["*"]).pick_file();
+        self.await_dialog(PendingDialog::ImportView(path.to_string()), async move { handle_paths(picked.await) });
+    }
 
-                if !tab.status.is_empty() && tab.last_error.is_none() && !tab.data.is_empty() {
-                    ui.horizontal(|ui| { ui.add_space(8.0); ui.add(egui::Spinner::new().size(14.0)); ui.label(egui::RichText::new(&tab.status).color(ui.visuals().warn_fg_color).small()); });
-                    ui.separator();
-                }
+    fn write_view(&mut self, path: &str, target: std::path::PathBuf) {
+        let Some(tab) = self.tabs.get(path) else { return };
+        let name = target.file_stem().and_then(|n| n.to_str()).map_or("view", |n| n.trim_end_matches(".view")).to_string();
+        let file = ViewFile { view: tab.view(name), source: tab.name.clone(), fields: tab.fields.clone(), page: Some(tab.current_page), page_size: Some(tab.page_size) };
+        let result = std::fs::File::create(&target).map_err(|e| e.to_string()).and_then(|f| serde_json::to_writer_pretty(std::io::BufWriter::new(f), &file).map_err(|e| e.to_string()));
+        let time = chrono::Local::now().format("%H:%M:%S").to_string();
+        self.logs.push(LogEntry { time, path: target.display().to_string(), sql: format!("Exported view of {}", tab.name), error: result.err() });
+    }
 
-                egui::TopBottomPanel::bottom(format!("footer_{}", tab.path)).frame(egui::Frame::NONE.inner_margin(egui::Margin::symmetric(8, 6))).show_inside(ui, |ui| {
-                    ui.horizontal(|ui| {
-                        let total_p = (tab.total_rows as f64 / tab.page_size as f64).ceil() as usize;
-                        let total_p = if total_p == 0 { 1 } else { total_p };
-                        ui.label(egui::RichText::new(format!("Showing {}-{} of {} | Page {}/{}", (tab.current_page-1)*tab.page_size+1, ((tab.current_page-1)*tab.page_size+tab.data.len()), tab.total_rows, tab.current_page, total_p)).weak());
-                        
-                            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                                ui.add_space(4.0);
-                                if ui.add_enabled(tab.current_page < total_p, egui::Button::new("Next ▶")).clicked() {
-                                    tab.current_page += 1; tab.jump_page_buffer = tab.current_page.to_string(); tab.status = format!("Loading {}...", tab.current_page);
-                                    Self::load_page(self.tx.clone(), self.backend.clone(), tab.path.clone(), tab.current_page, tab.page_size, tab.filter.clone(), tab.sort.clone());
-                                }
-                                if ui.add_enabled(tab.current_page > 1, egui::Button::new("◀ Prev")).clicked() {
-                                    tab.current_page -= 1; tab.jump_page_buffer = tab.current_page.to_string(); tab.status = format!("Loading {}...", tab.current_page);
-                                    Self::load_page(self.tx.clone(), self.backend.clone(), tab.path.clone(), tab.current_page, tab.page_size, tab.filter.clone(), tab.sort.clone());
-                                }
-                                
-                                ui.separator();
-                                
-                                // Jump to Page Group
+    /// Applies a view file to the tab, warning in the console about columns its schema doesn't share with the tab's.
+    fn read_view(&mut self, path: &str, target: std::path::PathBuf) {
+        let Some(tab) = self.tabs.get_mut(path) else { return };
+        let time = chrono::Local::now().format("%H:%M:%S").to_string();
+        let file = std::fs::read(&target).map_err(|e| e.to_string()).and_then(|b| serde_json::from_slice::<ViewFile>(&b).map_err(|e| format!("Not a view file: {}", e)));
+        let file = match file {
+            Ok(f) => f,
+            Err(e) => { self.logs.push(LogEntry { time, path: target.display().to_string(), sql: "Import view".to_string(), error: Some(e) }); self.show_console = true; return; }
+        };
+        let mismatches = file.mismatches(&tab.fields);
+        tab.apply_view(&file.view);
+        // Columns this file doesn't have would only sit unused in the layout
+        let known = |c: &String| tab.schema.is_empty() || tab.schema.contains(c);
+        tab.column_order.retain(known); tab.hidden_columns.retain(known);
+        if let Some(size) = file.page_size.filter(|&s| s > 0) { tab.page_size = size; }
+        ParquetTabViewer::apply_filter(self.tx_to_ui.clone(), self.backend.clone(), tab);
+        // The row count clamps this once it answers
+        if let Some(page) = file.page.filter(|&p| p > 1) { ParquetTabViewer::goto_page(self.tx_to_ui.clone(), self.backend.clone(), tab, page, &self.settings); }
+        let error = (!mismatches.is_empty()).then(|| format!("The view was made on {} and its schema differs from {}: {}", file.source, tab.name, mismatches.join("; ")));
+        self.show_console |= error.is_some();
+        self.logs.push(LogEntry { time, path: tab.path.clone(), sql: format!("Imported view {} from {}", file.view.name, target.display()), error });
+    }
+
+    /// Profiles the tab's filtered result in the background, listed in Transfers like any other export.
+    fn export_profile(&mut self, path: &str) {
+        let Some(tab) = self.tabs.get(path) else { return };
+        let stem = file_stem(&tab.path).unwrap_or("profile");
+        let saved = rfd::AsyncFileDialog::new().add_filter("CSV (.csv)", &["csv"]).add_filter("JSON (.json)", &["json"]).set_file_name(format!("{}.profile.csv", stem)).save_file();
+        self.await_dialog(PendingDialog::Profile(path.to_string()), async move { handle_paths(saved.await) });
+    }
+
+    fn write_profile(&mut self, path: &str, target: std::path::PathBuf) {
+        let Some(tab) = self.tabs.get(path) else { return };
+        let cancel = Arc::new(ExportCancel::default());
+        let job_id = self.next_job_id; self.next_job_id += 1;
+        let file = target.file_name().and_then(|n| n.to_str()).unwrap_or("profile").to_string();
+        self.exports.push(ExportJob { id: job_id, file, rows_done: 0, bytes_written: 0, files: None, started: std::time::Instant::now(), cancel: cancel.clone(), finished: None });
+
+        let (b_c, tx_c, source) = (self.backend.clone(), self.tx_to_ui.clone(), tab.path.clone());
+        let filter = Some(tab.filter.clone()).filter(|f| !f.trim().is_empty());
+        self.backend.spawn(path, "profile", move || {
+            let result = b_c.get_profile(&source, filter.as_deref(), &cancel).and_then(|report| {
+                let _ = tx_c.send(BackendMessage::SqlLog { path: source.clone(), sql: report.sql.clone() });
+                export::write_profile(&target, &source, filter.as_deref(), &report).map(|_| report.rows.len())
+            });
+            let msg = match result {
+                Ok(rows) => BackendMessage::ExportDone { job_id, rows, bytes_written: std::fs::metadata(&target).map(|m| m.len()).unwrap_or(0), file: target.to_string_lossy().to_string() },
+                Err(e) => { backend::note_failure(&e); let _ = std::fs::remove_file(&target); BackendMessage::ExportFailed { job_id, message: e, cancelled: cancel.is_cancelled() } }
+            };
+            let _ = tx_c.send(msg);
+        });
+    }
+
+    fn start_export(&mut self, dialog: ExportDialog) {
+        let Some(tab) = self.tabs.get(&dialog.path) else { return };
+        let stem = file_stem(&tab.path).unwrap_or("export");
+        let (format, ext) = (dialog.format, dialog.format.extension());
+        let split = if dialog.scope == ExportScope::WholeResult { dialog.split.clone() } else { ExportSplit::Single };
+        // Split exports write their parts and manifest into a folder
+        let target: std::pin::Pin<Box<dyn std::future::Future<Output = Option<rfd::FileHandle>> + Send>> = if split == ExportSplit::Single { Box::pin(rfd::AsyncFileDialog::new().add_filter(format.label(), &[ext]).set_file_name(format!("{}.{}", stem, ext)).save_file()) } else { Box::pin(rfd::AsyncFileDialog::new().set_title("Export parts to folder").pick_folder()) };
+        self.await_dialog(PendingDialog::Export(dialog), async move { handle_paths(target.await) });
+    }
+
+    fn run_export(&mut self, dialog: ExportDialog, target: std::path::PathBuf) {
+        let Some(tab) = self.tabs.get(&dialog.path) else { return };
+        let format = dialog.format;
+        let split = if dialog.scope == ExportScope::WholeResult { dialog.split.clone() } else { ExportSplit::Single };
+        let total_rows = tab.total_rows;
+
+        let page_start = (tab.current_page - 1) * tab.page_size;
+        let sql_cap = (format == ExportFormat::SqlInsert).then(|| dialog.sql_max_rows.trim().parse::<usize>().unwrap_or(SQL_DEFAULT_MAX_ROWS));
+        let sel: BTreeSet<usize> = tab.selected_rows.iter().copied().take(sql_cap.unwrap_or(usize::MAX)).collect();
+        let visible = (dialog.visible_only && !tab.hidden_columns.is_empty()).then(|| tab.visible_order());
+        let cols: Vec<usize> = if dialog.scope == ExportScope::Selection && !tab.selected_cols.is_empty() { tab.selected_cols.iter().copied().collect() } else { visible.clone().unwrap_or_else(|| (0..tab.columns.len()).collect()) };
+        let header: Vec<String> = cols.iter().filter_map(|&c| tab.columns.get(c).cloned()).collect();
+        let project = |r: &[Arc<str>]| cols.iter().map(|&c| r.get(c).map_or_else(String::new, |v| v.to_string())).collect::<Vec<String>>();
+
+        // Text formats serialize in-memory rows straight from the loaded page so the file matches the screen exactly
+        let on_page = |g: &usize| *g >= page_start && *g < page_start + tab.data.len();
+        // Unless the cell fetch cap cut some cells short, which only a fresh query has whole
+        let page_rows = if tab.data.has_capped_cells() { None } else { match dialog.scope {
+            ExportScope::CurrentPage if format.is_text() => Some(tab.data.iter().map(project).collect::<Vec<_>>()),
+            ExportScope::Selection if format.is_text() && sel.iter().all(on_page) => Some(sel.iter().filter_map(|g| tab.data.row(g - page_start)).map(project).collect()),
+            _ => None,
+        } };
+        let mut row_filter = None;
+        let (limit, offset) = match dialog.scope {
+            ExportScope::CurrentPage => (Some(tab.page_size), Some(page_start)),
+            ExportScope::Selection => {
+                let (range, filter) = selection_range(&sel);
+                row_filter = filter;
+                (Some(range.len()), Some(range.start))
+            }
+            // Applied whatever the count says, since a pending or skipped count can't tell a result that fits
+            ExportScope::WholeResult if format == ExportFormat::Xlsx && dialog.overflow == XlsxOverflow::FirstRows && split == ExportSplit::Single => {
+                (Some(dialog.row_limit.trim().parse::<usize>().unwrap_or(EXCEL_MAX_DATA_ROWS).min(EXCEL_MAX_DATA_ROWS)), None)
+            }
+            ExportScope::WholeResult => (None, None),
+        };
+        let limit = match sql_cap { Some(cap) if dialog.scope != ExportScope::Selection => Some(limit.map_or(cap, |l| l.min(cap))), _ => limit };
+        let selection = (dialog.scope == ExportScope::Selection).then_some((row_filter, cols));
+
+        let cancel = Arc::new(ExportCancel::default());
+        let job_id = self.next_job_id; self.next_job_id += 1;
+        let file = target.file_name().and_then(|n| n.to_str()).unwrap_or("export").to_string();
+        self.exports.push(ExportJob { id: job_id, file, rows_done: 0, bytes_written: 0, files: None, started: std::time::Instant::now(), cancel: cancel.clone(), finished: None });
+
+        // A selection picks its columns from the full rows itself
+        let projection = visible.filter(|_| dialog.scope != ExportScope::Selection).map(|v| v.iter().map(|&c| quote_ident(&tab.columns[c])).collect::<Vec<_>>().join(", "));
+        let spec = QuerySpec { path: tab.path.clone(), projection, filter: Some(tab.filter.clone()), sort: Some(tab.sort.clone()), limit, offset };
+        let (b_c, tx_c, overflow, table, fields, camel_case) = (self.backend.clone(), self.tx_to_ui.clone(), dialog.overflow, dialog.table_name.clone(), tab.fields.clone(), self.settings.json_camel_case);
+        self.backend.spawn(&tab.path, "export", move || {
+            // Progress is reported as a running total, so each part of a split export starts from what came before
+            let tx_p = tx_c.clone();
+            let mut make_sink = move |path: &std::path::Path, rows_before: usize, bytes_before: u64| -> Result<Box<dyn RowSink>, String> {
+                let tx_p = tx_p.clone();
+                let progress = move |n: usize, bytes: u64| { let _ = tx_p.send(BackendMessage::ExportProgress { job_id, rows_done: rows_before + n, bytes_written: bytes_before + bytes }); };
+                match format {
+                    ExportFormat::Xlsx => Ok(Box::new(XlsxSink::new(path.to_path_buf(), overflow, progress))),
+                    ExportFormat::SqlInsert => SqlSink::create(path, &table, fields.clone(), progress).map(|s| Box::new(s) as Box<dyn RowSink>),
+                    ExportFormat::Json => std::fs::File::create(path).map(|f| Box::new(JsonSink::new(std::io::BufWriter::new(f), camel_case, progress)) as Box<dyn RowSink>).map_err(|e| e.to_string()),
+                    ExportFormat::Parquet => Err("Parquet is written with COPY".to_string()),
+                    _ => TextSink::create(path, format, progress).map(|s| Box::new(s) as Box<dyn RowSink>),
+                }
+            };
+            if split != ExportSplit::Single {
+                let _ = tx_c.send(BackendMessage::SqlLog { path: spec.path.clone(), sql: spec.sql(&b_c) });
+                let tx_f = tx_c.clone();
+                let mut on_files = move |done, total| { let _ = tx_f.send(BackendMessage::ExportFiles { job_id, done, total }); };
+                // The folder and its manifest stay behind on failure, listing the parts that did finish
+                let msg = match export::write_split(&b_c, &spec, &split, format, &target, total_rows, &cancel, &mut make_sink, &mut on_files) {
+                    Ok((rows, bytes_written)) => BackendMessage::ExportDone { job_id, rows, bytes_written, file: target.to_string_lossy().to_string() },
+                    Err(e) => { backend::note_failure(&e); BackendMessage::ExportFailed { job_id, message: e, cancelled: cancel.is_cancelled() } }
+                };
+                let _ = tx_c.send(msg);
+                return;
+            }
+            let result = if let Some(rows) = page_rows {
+                std::fs::File::create(&target)
+                    .and_then(|f| export::write_text_rows(std::io::BufWriter::new(f), format, &header, &rows)?.flush())
+                    .map(|_| rows.len()).map_err(|e| e.to_string())
+            } else if format.uses_copy() {
+                let _ = tx_c.send(BackendMessage::SqlLog { path: spec.path.clone(), sql: spec.sql(&b_c) });
+                b_c.copy_to(&spec, &target.to_string_lossy(), "FORMAT PARQUET", &cancel)
+            } else {
+                let _ = tx_c.send(BackendMessage::SqlLog { path: spec.path.clone(), sql: spec.sql(&b_c) });
+                make_sink(&target, 0, 0).and_then(|mut sink| match selection {
+                    Some((rows, cols)) => {
+                        let mut sel = SelectionSink::new(sink.as_mut(), rows, cols);
+                        b_c.stream_query(&spec, &cancel, &mut sel)?;
+                        sel.finish().map(|_| sel.written())
+                    }
+                    None => b_c.stream_query(&spec, &cancel, sink.as_mut()).and_then(|rows| sink.finish().map(|_| sink.rows_kept().unwrap_or(rows))),
+                })
+            };
+            let msg = match result {
+                Ok(rows) => BackendMessage::ExportDone { job_id, rows, bytes_written: std::fs::metadata(&target).map(|m| m.len()).unwrap_or(0), file: target.to_string_lossy().to_string() },
+                Err(e) => { backend::note_failure(&e); let _ = std::fs::remove_file(&target); BackendMessage::ExportFailed { job_id, message: e, cancelled: cancel.is_cancelled() } }
+            };
+            let _ = tx_c.send(msg);
+        });
+    }
+}
+
+struct ParquetTabViewer<'a> { tabs: &'a mut HashMap<String, Tab>, settings: &'a mut Settings, views: &'a mut Vec<SavedView>, tx: mpsc::Sender<BackendMessage>, backend: Arc<Backend> }
+
+impl<'a> ParquetTabViewer<'a> {
+    #[allow(clippy::too_many_arguments)]
+    fn load_page(tx: mpsc::Sender<BackendMessage>, backend: Arc<Backend>, request: RequestId, path: String, page: usize, page_size: usize, filter: String, sort: String) {
+        backend.clone().submit(&path.clone(), Some("page"), move || {
+            let offset = (page - 1) * page_size;
+            let f = if filter.trim().is_empty() { None } else { Some(filter) };
+            let s = if sort.trim().is_empty() { None } else { Some(sort) };
+            match backend.run_query(request, path.clone(), f, s, Some(page_size), Some(offset), Some(&tx)) {
+                Ok(msg) => { let _ = tx.send(msg); }
+                Err(e) => { let _ = tx.send(BackendMessage::Error { request: Some(request), path: Some(path), message: e, sql: None }); }
+            }
+        });
+    }
+
+    /// Loads one block of rows for scroll mode.
+    fn load_block(tx: mpsc::Sender<BackendMessage>, backend: Arc<Backend>, request: RequestId, path: String, block: usize, filter: String, sort: String) {
+        backend.clone().submit(&path.clone(), Some("block"), move || {
+            let f = if filter.trim().is_empty() { None } else { Some(filter) };
+            let s = if sort.trim().is_empty() { None } else { Some(sort) };
+            // Blocks are small and fixed in size, and one cut short would leave rows that never load
+            match backend.run_query_past_cap(request, path.clone(), f, s, Some(SCROLL_BLOCK_ROWS), Some(block * SCROLL_BLOCK_ROWS), None) {
+                Ok(msg) => { let _ = tx.send(msg); }
+                Err(e) => { let _ = tx.send(BackendMessage::Error { request: Some(request), path: Some(path), message: e, sql: None }); }
+            }
+        });
+    }
+
+    /// Opens the tab's file again and reloads its schema, count and page, keeping filter, sort and page.
+    fn reopen(tx: mpsc::Sender<BackendMessage>, backend: Arc<Backend>, tab: &mut Tab, count: bool) {
+        tab.last_error = None; tab.state = LoadState::Reopening;
+        let request = next_request(); tab.page_request = request; tab.count_request = request; tab.loading_key = Some((request, PageKey::of(tab)));
+        tab.count_pending = count; tab.count_skipped = !count;
+        let (b_c, path_c, f_c, s_c, p, ps, by_name) = (backend.clone(), tab.path.clone(), tab.filter.clone(), tab.sort.clone(), tab.current_page, tab.page_size, tab.union_by_name);
+        backend.submit(&tab.path, None, move || {
+            // Recreates the file's view, which every later query reads from
+            match b_c.open_file(path_c.clone(), by_name) {
+                Ok(msg) => { let _ = tx.send(msg); }
+                Err(e) => { let _ = tx.send(BackendMessage::Error { request: Some(request), path: Some(path_c), message: e, sql: None }); return; }
+            }
+            if let Ok(s_msg) = b_c.get_schema(path_c.clone()) { let _ = tx.send(s_msg); }
+            let f = if f_c.trim().is_empty() { None } else { Some(f_c.clone()) };
+            if count { if let Ok(msg) = b_c.get_row_count(request, path_c.clone(), f.clone()) { let _ = tx.send(msg); } }
+            let s = if s_c.trim().is_empty() { None } else { Some(s_c) };
+            let offset = (p - 1) * ps;
+            if let Ok(q_msg) = b_c.run_query(request, path_c, f, s, Some(ps), Some(offset), Some(&tx)) { let _ = tx.send(q_msg); }
+        });
+    }
+
+    /// Reloads the page on screen with the row cap raised, for this one load.
+    fn reload_past_cap(tx: mpsc::Sender<BackendMessage>, backend: Arc<Backend>, tab: &mut Tab) {
+        tab.page_request = next_request(); tab.loading_key = None; tab.state = LoadState::LoadingPage { page: tab.current_page };
+        let (request, path, offset, page_size) = (tab.page_request, tab.path.clone(), (tab.current_page - 1) * tab.page_size, tab.page_size);
+        let f = Some(tab.filter.clone()).filter(|f| !f.trim().is_empty());
+        let s = Some(tab.sort.clone()).filter(|s| !s.trim().is_empty());
+        backend.clone().submit(&path.clone(), Some("page"), move || {
+            match backend.run_query_past_cap(request, path.clone(), f, s, Some(page_size), Some(offset), Some(&tx)) {
+                Ok(msg) => { let _ = tx.send(msg); }
+                Err(e) => { let _ = tx.send(BackendMessage::Error { request: Some(request), path: Some(path), message: e, sql: None }); }
+            }
+        });
+    }
+
+    fn load_cell(tx: mpsc::Sender<BackendMessage>, backend: Arc<Backend>, path: String, filter: String, sort: String, row: usize, column: String) {
+        backend.clone().submit(&path.clone(), Some("cell"), move || {
+            let f = if filter.trim().is_empty() { None } else { Some(filter) };
+            let s = if sort.trim().is_empty() { None } else { Some(sort) };
+            match backend.get_cell(path.clone(), f, s, row, column) {
+                Ok(msg) => { let _ = tx.send(msg); }
+                Err(e) => { let _ = tx.send(BackendMessage::Error { request: None, path: Some(path), message: e, sql: None }); }
+            }
+        });
+    }
+
+    /// Re-queries the selected rows so the clipboard gets native JSON types rather than display strings.
+    fn copy_selection_json(tx: mpsc::Sender<BackendMessage>, backend: Arc<Backend>, tab: &Tab, camel_case: bool) {
+        // The JSON is built in memory, so a selection past the row cap copies its first rows only
+        let selected: BTreeSet<usize> = tab.selected_rows.iter().copied().take(backend.row_cap()).collect();
+        let (range, rows) = selection_range(&selected);
+        let cols: Vec<usize> = if tab.selected_cols.is_empty() { (0..tab.columns.len()).collect() } else { tab.selected_cols.iter().copied().collect() };
+        let spec = QuerySpec { path: tab.path.clone(), projection: None, filter: Some(tab.filter.clone()), sort: Some(tab.sort.clone()), limit: Some(range.len()), offset: Some(range.start) };
+        let (count, note) = (selected.len(), if selected.len() < tab.selected_rows.len() { format!(" (first {} of {} selected; row cap)", selected.len(), tab.selected_rows.len()) } else { String::new() });
+        backend.clone().submit(&tab.path, None, move || {
+            let mut json = JsonSink::new(Vec::new(), camel_case, |_, _| {});
+            let result = {
+                let mut sel = SelectionSink::new(&mut json, rows, cols);
+                backend.stream_query(&spec, &ExportCancel::default(), &mut sel).and_then(|_| sel.finish())
+            };
+            let msg = match result {
+                Ok(()) => BackendMessage::ClipboardText { path: spec.path.clone(), text: String::from_utf8_lossy(&json.into_inner()).into_owned(), note: format!("Copied {} rows as JSON{}", count, note) },
+                Err(e) => BackendMessage::Error { request: None, path: None, message: e, sql: Some(spec.sql(&backend)) },
+            };
+            let _ = tx.send(msg);
+        });
+    }
+
+    /// Copies the loaded page's visible columns in grid order, re-querying it first when the cell fetch cap cut
+    /// any of its cells short.
+    fn copy_page(ctx: &egui::Context, tx: mpsc::Sender<BackendMessage>, backend: Arc<Backend>, tab: &Tab, format: ExportFormat) {
+        let shown: Vec<String> = tab.visible_order().into_iter().map(|c| tab.columns[c].clone()).collect();
+        if !tab.data.has_capped_cells() { ctx.copy_text(page_text(&tab.columns, &tab.data, &shown, format)); return; }
+        let note = format!("Copied page {} as {}", tab.current_page, format.extension().to_uppercase());
+        Self::copy_full_page(tx, backend, tab, note, move |columns, data| page_text(columns, data, &shown, format));
+    }
+
+    /// Copies every row of the filtered, sorted result, or its first `limit` rows; the row cap bounds it either way.
+    /// Like a page copy it holds the visible columns in grid order.
+    fn copy_rows(tx: mpsc::Sender<BackendMessage>, backend: Arc<Backend>, tab: &Tab, format: ExportFormat, limit: Option<usize>) {
+        let path = tab.path.clone();
+        let shown: Vec<String> = tab.visible_order().into_iter().map(|c| tab.columns[c].clone()).collect();
+        let f = Some(tab.filter.clone()).filter(|f| !f.trim().is_empty());
+        let s = Some(tab.sort.clone()).filter(|s| !s.trim().is_empty());
+        backend.clone().submit(&tab.path, None, move || {
+            let msg = match backend.run_query_full(next_request(), path.clone(), f, s, limit, None) {
+                Ok(BackendMessage::QueryData { columns, batch, truncated, .. }) => {
+                    let data = PageData::new(batch);
+                    let note = format!("Copied {} rows as {}{}", data.len(), format.extension().to_uppercase(), if truncated { " (row cap reached)" } else { "" });
+                    BackendMessage::ClipboardText { path, text: page_text(&columns, &data, &shown, format), note }
+                }
+                Ok(BackendMessage::Error { message, sql, .. }) => BackendMessage::Error { request: None, path: None, message, sql },
+                Ok(msg) => msg,
+                Err(e) => BackendMessage::Error { request: None, path: None, message: e, sql: None },
+            };
+            let _ = tx.send(msg);
+        });
+    }
+
+    /// Fetches the loaded page again with every value whole and puts what `render` makes of it on the clipboard.
+    fn copy_full_page(tx: mpsc::Sender<BackendMessage>, backend: Arc<Backend>, tab: &Tab, note: String, render: impl FnOnce(&[String], &PageData) -> String + Send + 'static) {
+        let path = tab.path.clone();
+        PageQuery::of(tab).fetch_full(tx, backend, move |columns, data| BackendMessage::ClipboardText { path, text: render(columns, data), note });
+    }
+
+    fn load_row_groups(tx: mpsc::Sender<BackendMessage>, backend: Arc<Backend>, path: String) {
+        backend.clone().submit(&path.clone(), None, move || {
+            // Not tied to a grid load, so a failure is only logged
+            match backend.get_row_groups(path) {
+                Ok(msg) => { let _ = tx.send(msg); }
+                Err(e) => { let _ = tx.send(BackendMessage::Error { request: None, path: None, message: format!("Reading row groups: {}", e), sql: None }); }
+            }
+        });
+    }
+
+    fn load_column_values(tx: mpsc::Sender<BackendMessage>, backend: Arc<Backend>, path: String, filter: String, sort: String, column: String) {
+        backend.clone().submit(&path.clone(), None, move || {
+            let f = if filter.trim().is_empty() { None } else { Some(filter) };
+            let s = if sort.trim().is_empty() { None } else { Some(sort) };
+            match backend.get_column_values(path.clone(), f, s, column) {
+                Ok(msg) => { let _ = tx.send(msg); }
+                Err(e) => { let _ = tx.send(BackendMessage::Error { request: None, path: Some(path), message: e, sql: None }); }
+            }
+        });
+    }
+
+    fn memory_cache(tx: mpsc::Sender<BackendMessage>, backend: Arc<Backend>, path: String, cache: bool) {
+        backend.clone().submit(&path.clone(), Some("cache"), move || {
+            let result = if cache { backend.cache_in_memory(&path) } else { backend.drop_memory_cache(&path) };
+            let msg = result.unwrap_or_else(|e| BackendMessage::MemoryCache { path, cached: false, sql: String::new(), error: Some(e) });
+            let _ = tx.send(msg);
+        });
+    }
+
+    /// Shows another page, straight from the tab's page cache when it was viewed recently.
+    fn goto_page(tx: mpsc::Sender<BackendMessage>, backend: Arc<Backend>, tab: &mut Tab, page: usize, settings: &Settings) {
+        // Only the Arrow buffers and the rows drawn so far are copied into the cache
+        if let Some(key) = tab.shown_key.take() { tab.page_cache.put(key, tab.columns.clone(), tab.data.clone(), settings.page_cache_pages, settings.page_cache_mb << 20); }
+        tab.current_page = page; tab.jump_page_buffer = page.to_string();
+        let key = PageKey::of(tab);
+        // A new request id either way, so a load still in flight for another page is dropped
+        tab.page_request = next_request();
+        if let Some((columns, data)) = tab.page_cache.take(&key) {
+            if tab.columns != columns { tab.col_widths.clear(); }
+            tab.columns = columns; tab.row_count = data.len(); tab.data = data; tab.state = LoadState::Idle;
+            tab.shown_key = Some(key.clone()); tab.check_page_rows();
+            if !settings.page_cache_revalidate { return; }
+        } else {
+            tab.state = LoadState::LoadingPage { page };
+        }
+        tab.loading_key = Some((tab.page_request, key));
+        Self::load_page(tx, backend, tab.page_request, tab.path.clone(), page, tab.page_size, tab.filter.clone(), tab.sort.clone());
+    }
+
+    /// The page turned on its side: a row per visible column, a column per row. Returns the cell double-clicked
+    /// as (row in the result, column index).
+    fn transposed_grid(ui: &mut egui::Ui, tab: &Tab, density: RowDensity, ordinal_base: Option<usize>, group_digits: bool) -> Option<(usize, usize)> {
+        let (order, start) = (tab.visible_order(), (tab.current_page - 1) * tab.page_size);
+        let mut open_cell = None;
+        let mut table = egui_extras::TableBuilder::new(ui).id_salt(("transposed", &tab.path)).striped(true).resizable(true).vscroll(true).auto_shrink([false, false]).cell_layout(egui::Layout::left_to_right(egui::Align::Center));
+        table = table.column(Column::initial(160.0).at_least(60.0)).columns(Column::initial(150.0).at_least(60.0), tab.data.len());
+        table.header(density.row_height() + 2.0, |mut h| {
+            h.col(|ui| { ui.strong("Column"); });
+            for i in 0..tab.data.len() { h.col(|ui| { ui.strong(format!("#{}", start + i + 1)); }); }
+        }).body(|b| b.rows(density.row_height(), order.len(), |mut r| {
+            let ci = order[r.index()];
+            let name = tab.columns.get(ci).map_or("", |n| n.as_str());
+            let (format, group) = (tab.column_formats.get(name).copied(), group_digits && tab.data.is_numeric_column(ci));
+            r.col(|ui| { ui.label(egui::RichText::new(column_header(name, ci, ordinal_base)).strong()); });
+            for (i, rd) in tab.data.iter().enumerate() { r.col(|ui| {
+                let Some(c) = rd.get(ci).map(|c| &**c) else { return };
+                let resp = if c == "(null)" { ui.add(egui::Label::new(egui::RichText::new(c).weak()).sense(egui::Sense::click())) }
+                else if let Some(shown) = format::display_cell(c, format, group) { ui.add(egui::Label::new(shown).sense(egui::Sense::click())).on_hover_text(c) }
+                else { ui.add(egui::Label::new(c).sense(egui::Sense::click())) };
+                if resp.double_clicked() { open_cell = Some((start + i, ci)); }
+            }); }
+        }));
+        open_cell
+    }
+
+    /// The running time of a page load or count in flight, turning into a warning with a way out once it's slow.
+    fn load_timer(ui: &mut egui::Ui, backend: &Backend, path: &str, started: Option<(RequestId, std::time::Instant)>, slow_secs: u64) {
+        let Some((_, started)) = started else { return };
+        let elapsed = started.elapsed().as_secs_f32();
+        ui.ctx().request_repaint_after(std::time::Duration::from_millis(100));
+        if slow_secs == 0 || elapsed < slow_secs as f32 { ui.label(egui::RichText::new(format!("… {:.1} s", elapsed)).weak()); return; }
+        ui.label(egui::RichText::new(format!("… {:.1} s", elapsed)).color(ui.visuals().warn_fg_color));
+        ui.label(egui::RichText::new("Slow query: consider adding a filter on a partition column, or cancel").color(ui.visuals().warn_fg_color).small());
+        if ui.small_button("Cancel").clicked() { backend.interrupt(path); }
+    }
+
+    /// Applies the filter and sort unless the sort names a column the file doesn't have, which is pointed out
+    /// under the box instead of being sent to DuckDB.
+    fn apply_checked(tx: mpsc::Sender<BackendMessage>, backend: Arc<Backend>, tab: &mut Tab) {
+        tab.sort_unknown = unknown_sort_column(&tab.sort, &tab.schema, tab.in_memory);
+        if tab.sort_unknown.is_none() { Self::apply_filter(tx, backend, tab); }
+    }
+
+    /// Restarts the grid at page one under the tab's current filter and sort.
+    fn apply_filter(tx: mpsc::Sender<BackendMessage>, backend: Arc<Backend>, tab: &mut Tab) {
+        tab.current_page = 1; tab.jump_page_buffer = "1".to_string(); tab.state = LoadState::Applying;
+        tab.selected_rows.clear(); tab.selection_anchor = None; tab.selected_cells = None;
+        // Applying is also how the user asks for fresh rows, so nothing cached is reused
+        tab.page_cache.clear(); tab.shown_key = None; tab.scroll_blocks.clear();
+        tab.page_request = next_request(); tab.count_request = tab.page_request; tab.count_pending = true; tab.count_skipped = false;
+        tab.loading_key = Some((tab.page_request, PageKey::of(tab)));
+        Self::refresh_data(tx, backend, tab.page_request, tab.path.clone(), tab.filter.clone(), tab.sort.clone(), tab.page_size);
+    }
+
+    /// Validates a live filter without touching the grid; the answer decides whether it gets applied.
+    fn check_filter(tx: mpsc::Sender<BackendMessage>, backend: Arc<Backend>, request: RequestId, path: String, filter: String, sort: String) {
+        backend.clone().submit(&path.clone(), Some("check"), move || {
+            let f = Some(filter.as_str()).filter(|f| !f.trim().is_empty());
+            let s = Some(sort.as_str()).filter(|s| !s.trim().is_empty());
+            let error = backend.validate_query(&path, f, s).err();
+            let _ = tx.send(BackendMessage::FilterChecked { request, path, error });
+        });
+    }
+
+    /// Reloads the row count and first page under one request id.
+    fn refresh_data(tx: mpsc::Sender<BackendMessage>, backend: Arc<Backend>, request: RequestId, path: String, filter: String, sort: String, page_size: usize) {
+        Self::count_rows(tx.clone(), backend.clone(), request, path.clone(), filter.clone());
+        Self::load_page(tx, backend, request, path, 1, page_size, filter, sort);
+    }
+
+    fn count_rows(tx: mpsc::Sender<BackendMessage>, backend: Arc<Backend>, request: RequestId, path: String, filter: String) {
+        let b_c = backend.clone();
+        backend.submit(&path.clone(), Some("count"), move || {
+            let f = if filter.trim().is_empty() { None } else { Some(filter) };
+            match b_c.get_row_count(request, path.clone(), f) {
+                Ok(msg) => { let _ = tx.send(msg); }
+                Err(e) => { let _ = tx.send(BackendMessage::Error { request: Some(request), path: Some(path), message: e, sql: None }); }
+            }
+        });
+    }
+
+    /// Counts the rows of a tab opened without counting them.
+    fn count_now(tx: mpsc::Sender<BackendMessage>, backend: Arc<Backend>, tab: &mut Tab) {
+        tab.count_request = next_request(); tab.count_pending = true; tab.count_skipped = false;
+        Self::count_rows(tx, backend, tab.count_request, tab.path.clone(), tab.filter.clone());
+    }
+}
+
+impl<'a> TabViewer for ParquetTabViewer<'a> {
+    type Tab = String;
+
+    fn title(&mut self, tab_id: &mut Self::Tab) -> egui::WidgetText {
+        if let Some(tab) = self.tabs.get(tab_id) {
+            let mut name = if tab.name.chars().count() > 20 { format!("{}...", tab.name.chars().take(17).collect::<String>()) } else { tab.name.clone() };
+            // What the view is doing to the file, so tabs can be told apart without opening their toolbars
+            if !tab.filter.trim().is_empty() { name += " 🔍"; }
+            if !tab.sort.trim().is_empty() { name += " ↕"; }
+            if !tab.hidden_columns.is_empty() { name += &format!(" ◫{}", tab.hidden_columns.len()); }
+            match tab.title_flash.map(|t| t.elapsed().as_secs_f32()).filter(|s| *s < TITLE_FLASH_SECS) {
+                Some(s) => egui::RichText::new(name).background_color(egui::Color32::from_rgb(255, 200, 0).gamma_multiply(1.0 - s / TITLE_FLASH_SECS)).into(),
+                None => name.into(),
+            }
+        } else { "Loading...".into() }
+    }
+
+    fn on_tab_button(&mut self, tab_id: &mut Self::Tab, response: &egui::Response) {
+        let Some(tab) = self.tabs.get(tab_id) else { return };
+        let mut lines = vec![tab.name.clone()];
+        if !tab.filter.trim().is_empty() { lines.push(format!("🔍 Filter: {}", tab.filter.trim())); }
+        if !tab.sort.trim().is_empty() { lines.push(format!("↕ Sort: {}", tab.sort.trim())); }
+        if !tab.hidden_columns.is_empty() { lines.push(format!("◫ Hidden: {}", tab.hidden_columns.join(", "))); }
+        response.clone().on_hover_text(lines.join("\n"));
+    }
+
+    fn on_close(&mut self, tab_id: &mut Self::Tab) -> OnCloseResponse { self.tabs.remove(tab_id); self.backend.close_path(tab_id); OnCloseResponse::Close }
+
+    fn context_menu(&mut self, ui: &mut egui::Ui, tab_id: &mut Self::Tab, _surface: egui_dock::SurfaceIndex, _node: egui_dock::NodeIndex) {
+        let files = source_files(tab_id);
+        let remote = files.iter().any(|f| is_remote(f));
+        let absolute: Vec<String> = files.iter().map(|f| if is_remote(f) { f.to_string() } else { std::path::absolute(f).map_or_else(|_| f.to_string(), |p| p.to_string_lossy().into_owned()) }).collect();
+        let label = match (remote, absolute.len()) { (true, 1) => "Copy URL", (true, _) => "Copy URLs", (false, 1) => "Copy path", (false, _) => "Copy paths" };
+        if ui.button(label).clicked() { ui.ctx().copy_text(absolute.join("\n")); ui.close(); }
+        if let Some(tab) = self.tabs.get_mut(tab_id).filter(|_| ui.button("Hard refresh").on_hover_text("Reopen the file and read its schema and row count again instead of reusing remembered ones").clicked()) {
+            self.backend.forget_file_facts(tab_id);
+            tab.page_cache.clear(); tab.shown_key = None;
+            Self::reopen(self.tx.clone(), self.backend.clone(), tab, !self.settings.skip_open_count);
+            ui.close();
+        }
+        if !remote && ui.button(REVEAL_LABEL).clicked() {
+            if let Err(e) = reveal_in_file_manager(dataset_dir(&absolute[0]).unwrap_or(std::path::Path::new(&absolute[0]))) {
+                let _ = self.tx.send(BackendMessage::Error { request: None, path: None, message: format!("{}: {}", REVEAL_LABEL, e), sql: None });
+            }
+            ui.close();
+        }
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui, tab_id: &mut Self::Tab) {
+        if let Some(tab) = self.tabs.get_mut(tab_id) {
+            if tab.title_flash.is_some_and(|t| t.elapsed().as_secs_f32() < TITLE_FLASH_SECS) { ui.ctx().request_repaint(); } else { tab.title_flash = None; }
+            ui.vertical(|ui| {
+                ui.add_space(4.0);
+                ui.horizontal(|ui| {
+                    ui.add_space(4.0);
+                    // Jump to Column lives in the toolbar, so it opens the toolbar while it's in use
+                    let collapsed = self.settings.toolbar_collapsed && tab.column_jump.is_none();
+                    if ui.small_button(if collapsed { "▸" } else { "▾" }).on_hover_text(if collapsed { "Show the filter toolbar" } else { "Hide the filter toolbar" }).clicked() { self.settings.toolbar_collapsed = !collapsed; }
+                    if collapsed {
+                        let summary = [("WHERE", &tab.filter), ("ORDER BY", &tab.sort)].iter().filter(|(_, v)| !v.trim().is_empty()).map(|(k, v)| format!("{} {}", k, v.trim())).collect::<Vec<_>>().join("  ·  ");
+                        if summary.is_empty() { ui.label(egui::RichText::new("No filter").weak()); }
+                        else { ui.add(egui::Label::new(egui::RichText::new(summary).monospace().color(ui.visuals().warn_fg_color)).truncate()); }
+                        return;
+                    }
+                    // A column DuckDB couldn't find is underlined where the filter or sort names it
+                    let bad_column = tab.sort_unknown.clone().or_else(|| tab.live_error.as_deref().or(tab.last_error.as_deref()).and_then(unknown_column).map(str::to_string));
+                    let mut layouter = |ui: &egui::Ui, text: &dyn egui::TextBuffer, width: f32| marked_layout(ui, text.as_str(), bad_column.as_deref(), width);
+                    ui.add_space(4.0); ui.label("WHERE");
+                    let f_in = ui.add(egui::TextEdit::singleline(&mut tab.filter).hint_text("filter").desired_width(200.0).layouter(&mut layouter));
+                    ui.add_space(8.0); ui.label("ORDER BY");
+                    let s_in = ui.add(egui::TextEdit::singleline(&mut tab.sort).hint_text("sort").desired_width(150.0).layouter(&mut layouter));
+                    if s_in.changed() { tab.sort_unknown = None; }
+                    if ui.button("Apply").clicked() || (f_in.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter))) || (s_in.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter))) {
+                        // Also drops a live check still in flight
+                        tab.live_edit = None; tab.live_error = None; tab.live_check = 0;
+                        Self::apply_checked(self.tx.clone(), self.backend.clone(), tab);
+                    } else if self.settings.live_filter {
+                        let now = ui.input(|i| i.time);
+                        if f_in.changed() || s_in.changed() { tab.live_edit = Some(now); }
+                        if let Some(edited) = tab.live_edit {
+                            let wait = self.settings.live_filter_delay_ms as f64 / 1000.0 - (now - edited);
+                            if wait > 0.0 { ui.ctx().request_repaint_after(std::time::Duration::from_secs_f64(wait)); } else {
+                                tab.live_edit = None; tab.live_check = next_request();
+                                Self::check_filter(self.tx.clone(), self.backend.clone(), tab.live_check, tab.path.clone(), tab.filter.clone(), tab.sort.clone());
+                            }
+                        }
+                    }
+                    if let Some(e) = &tab.live_error { ui.colored_label(ui.visuals().warn_fg_color, "⚠").on_hover_text(e); }
+                    if let Some(column) = bad_column.filter(|c| find_identifier(&tab.filter, c).or_else(|| find_identifier(&tab.sort, c)).is_some()) {
+                        let suggestion = closest_name(&column, &tab.schema).map(str::to_string);
+                        let note = match &suggestion { Some(s) => format!("No column {}; did you mean {}?", column, s), None => format!("No column {}", column) };
+                        ui.label(egui::RichText::new(note).color(ui.visuals().error_fg_color).small());
+                        if let Some(s) = suggestion.filter(|_| ui.small_button("Fix").on_hover_text("Use the suggested column and apply").clicked()) {
+                            for text in [&mut tab.filter, &mut tab.sort] { if let Some(r) = find_identifier(text, &column) { text.replace_range(r, &quote_ident(&s)); } }
+                            tab.live_edit = None; tab.live_error = None; tab.live_check = 0;
+                            Self::apply_checked(self.tx.clone(), self.backend.clone(), tab);
+                        }
+                    }
+                    if ui.add_enabled(!tab.schema.is_empty(), egui::Button::new("Build...")).on_hover_text("Add a condition to the filter without typing SQL").clicked() {
+                        tab.filter_builder = match tab.filter_builder { Some(_) => None, None => tab.fields.first().map(|f| FilterTerm::new(backend::field_paths(f).remove(0))) };
+                    }
+                    ui.separator();
+                    let rules_label = if tab.row_rules.is_empty() { "Rules".to_string() } else { format!("Rules ({})", tab.row_rules.len()) };
+                    if ui.selectable_label(tab.show_rules, rules_label).clicked() { tab.show_rules = !tab.show_rules; }
+                    ui.menu_button("Views", |ui| {
+                        let (mut apply, mut remove) = (None, None);
+                        for (i, view) in self.views.iter().enumerate() {
+                            ui.horizontal(|ui| {
+                                let summary = [("WHERE", &view.filter), ("ORDER BY", &view.sort)].iter().filter(|(_, v)| !v.trim().is_empty()).map(|(k, v)| format!("{} {}", k, v.trim())).chain((!view.hidden_columns.is_empty()).then(|| format!("{} hidden", view.hidden_columns.len()))).collect::<Vec<_>>().join("  ·  ");
+                                if ui.button(&view.name).on_hover_text(if summary.is_empty() { "No filter".to_string() } else { summary }).clicked() { apply = Some(i); ui.close(); }
+                                if ui.small_button("🗑").on_hover_text("Delete this view").clicked() { remove = Some(i); }
+                            });
+                        }
+                        if self.views.is_empty() { ui.label(egui::RichText::new("No saved views").weak()); }
+                        ui.separator();
+                        ui.horizontal(|ui| {
+                            let name_in = ui.add(egui::TextEdit::singleline(&mut tab.view_name).hint_text("view name").desired_width(140.0));
+                            let name = tab.view_name.trim().to_string();
+                            let exists = self.views.iter().any(|v| v.name == name);
+                            let save = ui.add_enabled(!name.is_empty(), egui::Button::new(if exists { "Replace" } else { "Save current" })).on_hover_text("Save this tab's filter, sort, column order and hidden columns").clicked()
+                                || (name_in.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) && !name.is_empty());
+                            if save {
+                                let view = tab.view(name.clone());
+                                match self.views.iter_mut().find(|v| v.name == name) { Some(v) => *v = view, None => self.views.push(view) }
+                                tab.view_name.clear(); ui.close();
+                            }
+                        });
+                        if let Some(i) = remove { self.views.remove(i); }
+                        if let Some(view) = apply.and_then(|i| self.views.get(i)) {
+                            tab.apply_view(view);
+                            Self::apply_filter(self.tx.clone(), self.backend.clone(), tab);
+                        }
+                    });
+                    if let Some(query) = &mut tab.column_jump {
+                        ui.separator();
+                        let input = ui.add(egui::TextEdit::singleline(query).hint_text("jump to column").desired_width(160.0));
+                        if !input.has_focus() && !input.lost_focus() { input.request_focus(); }
+                        let target = find_column(&tab.columns, query);
+                        if let Some(ci) = target { ui.label(egui::RichText::new(format!("→ {}", tab.columns[ci])).weak()); }
+                        if input.lost_focus() {
+                            if let (true, Some(ci)) = (ui.input(|i| i.key_pressed(egui::Key::Enter)), target) {
+                                // Jumping to a hidden column brings it back
+                                tab.hidden_columns.retain(|n| *n != tab.columns[ci]);
+                                // Fall back to the initial widths when the header hasn't been laid out yet
+                                let before = tab.visible_order().into_iter().take_while(|&c| c != ci);
+                                let left = tab.col_spans.get(ci).map(|s| s.0).unwrap_or_else(|| 40.0 + before.map(|c| tab.col_widths.get(c).copied().unwrap_or(150.0) + ui.spacing().item_spacing.x).sum::<f32>());
+                                tab.scroll_to_x = Some((left - 8.0).max(0.0));
+                                tab.col_flash = Some((ci, ui.input(|i| i.time)));
+                            }
+                            tab.column_jump = None;
+                        }
+                    }
+                    if tab.caching { ui.add(egui::Spinner::new().size(12.0)); ui.label(egui::RichText::new("Caching...").weak()); }
+                    else if tab.in_memory {
+                        if ui.selectable_label(true, "⚡ In memory").on_hover_text("Queries read a cached copy of the file. Click to drop it and read the file again.").clicked() { tab.caching = true; Self::memory_cache(self.tx.clone(), self.backend.clone(), tab.path.clone(), false); }
+                    } else if ui.button("Cache").on_hover_text("Load the whole file into memory so repeated filters and sorts don't re-read it. Needs memory for the full table.").clicked() {
+                        tab.caching = true; Self::memory_cache(self.tx.clone(), self.backend.clone(), tab.path.clone(), true);
+                    }
+                    if ui.button("Copy page").on_hover_text("Copy the current page as TSV (Ctrl+Shift+C)").clicked() { Self::copy_page(ui.ctx(), self.tx.clone(), self.backend.clone(), tab, ExportFormat::Tsv); }
+                    ui.menu_button("▾", |ui| {
+                        for f in [ExportFormat::Tsv, ExportFormat::Csv, ExportFormat::Markdown] { if ui.button(format!("Copy page as {}", f.extension().to_uppercase())).clicked() { Self::copy_page(ui.ctx(), self.tx.clone(), self.backend.clone(), tab, f); ui.close(); } }
+                        for f in [ExportFormat::Tsv, ExportFormat::Csv, ExportFormat::Markdown] {
+                            if ui.button(format!("Copy all rows as {}", f.extension().to_uppercase())).clicked() {
+                                // A huge paste can stall the app it lands in, so a big or uncounted result asks first
+                                if tab.count_known() && tab.total_rows <= self.settings.copy_all_rows_confirm { Self::copy_rows(self.tx.clone(), self.backend.clone(), tab, f, None); }
+                                else { tab.pending_rows_copy = Some((f, self.settings.copy_all_rows_confirm)); }
+                                ui.close();
+                            }
+                        }
+                        ui.separator();
+                        let what = if tab.selected_rows.is_empty() && tab.selected_cols.is_empty() { "page" } else { "selection" };
+                        if ui.button(format!("Copy {} as HTML", what)).clicked() {
+                            let view = HtmlView::of(tab, self.settings.max_cell_chars);
+                            if tab.data.has_capped_cells() {
+                                let (path, note) = (tab.path.clone(), format!("Copied page {} as HTML", tab.current_page));
+                                PageQuery::of(tab).fetch_full(self.tx.clone(), self.backend.clone(), move |columns, data| BackendMessage::ClipboardText { path, text: view.render(columns, data), note });
+                            } else { ui.ctx().copy_text(view.render(&tab.columns, &tab.data)); }
+                            ui.close();
+                        }
+                        if ui.button(format!("Save {} as HTML...", what)).clicked() {
+                            let view = HtmlView::of(tab, self.settings.max_cell_chars);
+                            let stem = file_stem(&tab.path).unwrap_or("export").to_string();
+                            let (saved, tx_c, backend) = (rfd::AsyncFileDialog::new().add_filter("HTML (.html)", &["html"]).set_file_name(format!("{}.html", stem)).save_file(), self.tx.clone(), self.backend.clone());
+                            // The page is rendered or its query taken now, so the file matches what was on screen when Save was
+                            // clicked; cells cut short by the fetch cap are fetched again whole
+                            let (html, query) = if tab.data.has_capped_cells() { (None, Some(PageQuery::of(tab))) } else { (Some(view.render(&tab.columns, &tab.data)), None) };
+                            std::thread::spawn(move || {
+                                let Some(target) = pollster::block_on(saved) else { return };
+                                let write = move |html: String| match std::fs::write(target.path(), html) {
+                                    Ok(()) => BackendMessage::SqlLog { path: "Export".to_string(), sql: format!("Saved {}", target.path().display()) },
+                                    Err(e) => BackendMessage::Error { request: None, path: None, message: format!("Saving {}: {}", target.path().display(), e), sql: None },
+                                };
+                                match (html, query) {
+                                    (Some(html), _) => { let _ = tx_c.send(write(html)); }
+                                    (None, Some(query)) => query.fetch_full(tx_c, backend, move |columns, data| write(view.render(columns, data))),
+                                    (None, None) => {}
+                                }
+                            });
+                            ui.close();
+                        }
+                    });
+                });
+                ui.add_space(4.0); ui.separator();
+
+                tab.track_load();
+                if tab.state.is_loading() && tab.last_error.is_none() && !tab.data.is_empty() {
+                    ui.horizontal(|ui| { ui.add_space(8.0); ui.add(egui::Spinner::new().size(14.0)); ui.label(egui::RichText::new(tab.state.text()).color(ui.visuals().warn_fg_color).small()); Self::load_timer(ui, &self.backend, &tab.path, tab.load_started, self.settings.slow_query_secs); });
+                    ui.separator();
+                }
+
+                egui::TopBottomPanel::bottom(format!("footer_{}", tab.path)).frame(egui::Frame::NONE.inner_margin(egui::Margin::symmetric(8, 6))).show_inside(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        let total_p = tab.total_pages();
+                        // Until the count answers, the old total (or zero, for a file just opened) would be wrong
+                        let (total, pages) = if tab.count_pending { ("…".to_string(), "…".to_string()) } else if tab.count_skipped { ("? rows".to_string(), "?".to_string()) } else if tab.count_failed { ("row count unavailable".to_string(), "?".to_string()) } else { (tab.total_rows.to_string(), total_p.to_string()) };
+                        if tab.scroll_mode {
+                            ui.label(egui::RichText::new(rows_shown(tab.scroll_blocks.shown.clone(), &total)).weak());
+                            if !tab.scroll_blocks.failed.is_empty() && ui.small_button("Retry").on_hover_text("Some rows failed to load; see the console. Load them again").clicked() { tab.scroll_blocks.failed.clear(); }
+                        }
+                        else { ui.label(egui::RichText::new(format!("{} | Page {}/{}", rows_shown(page_rows(tab.current_page, tab.page_size, tab.data.len()), &total), tab.current_page, pages)).weak()); }
+                        Self::load_timer(ui, &self.backend, &tab.path, tab.count_started, self.settings.slow_query_secs);
+                        if tab.count_skipped && ui.small_button("Count rows").on_hover_text("The row count was skipped on open; count it now").clicked() { Self::count_now(self.tx.clone(), self.backend.clone(), tab); }
+                        if !tab.selected_rows.is_empty() {
+                            ui.separator(); ui.label(egui::RichText::new(format!("{} selected", tab.selected_rows.len())).weak());
+                            if ui.small_button("Clear").clicked() { tab.selected_rows.clear(); tab.selected_cols.clear(); tab.selection_anchor = None; }
+                        }
+                        if let Some(sel) = &tab.selected_cells {
+                            ui.separator(); ui.label(egui::RichText::new(format!("{} cells of {} picked", sel.values.len(), tab.columns.get(sel.column).map_or("", |c| c.as_str()))).weak());
+                            if ui.small_button("Clear").clicked() { tab.selected_cells = None; }
+                        }
+                        if let Some((_, percent, elapsed)) = tab.progress {
+                            ui.separator();
+                            let elapsed = format!("{:.0}s", elapsed.as_secs_f32());
+                            match percent {
+                                Some(p) => { ui.add(egui::ProgressBar::new(p / 100.0).desired_width(120.0).desired_height(8.0)); ui.label(egui::RichText::new(format!("{:.0}% · {}", p, elapsed)).weak()); }
+                                None => { ui.add(egui::Spinner::new().size(12.0)); ui.label(egui::RichText::new(format!("Running · {}", elapsed)).weak()); }
+                            }
+                            ui.ctx().request_repaint_after(std::time::Duration::from_millis(250));
+                        }
+                        if tab.truncated && !tab.scroll_mode {
+                            ui.separator();
+                            ui.label(egui::RichText::new(format!("⚠ Showing first {} rows (result truncated)", tab.row_count)).color(ui.visuals().warn_fg_color).small())
+                                .on_hover_text("This page has more rows than the row cap set under View");
+                            if ui.small_button("Load all").on_hover_text("Reload this page with the cap raised").clicked() { Self::reload_past_cap(self.tx.clone(), self.backend.clone(), tab); }
+                        }
+                        if let Some(expected) = tab.short_page {
+                            ui.separator();
+                            ui.label(egui::RichText::new("⚠ Rows missing").color(ui.visuals().warn_fg_color).small())
+                                .on_hover_text(format!("This page returned {} of the {} rows the row count expects; the file may have changed since it was counted.", tab.row_count, expected));
+                            if ui.small_button("Reload").clicked() { Self::apply_filter(self.tx.clone(), self.backend.clone(), tab); }
+                        }
+                        if let Some((_, last)) = tab.load_times.back().filter(|_| tab.load_started.is_none() && tab.count_started.is_none()) {
+                            ui.separator();
+                            let history = tab.load_times.iter().rev().map(|(what, d)| format!("{} {:.2} s", what, d.as_secs_f32())).collect::<Vec<_>>().join("\n");
+                            ui.label(egui::RichText::new(format!("⏱ {:.2} s", last.as_secs_f32())).weak()).on_hover_text(format!("Recent page loads and counts, newest first:\n{}", history));
+                        }
+                        if !tab.data.is_empty() {
+                            ui.separator();
+                            let (formatted, unshared) = tab.data.formatted_size();
+                            ui.label(egui::RichText::new(format!("Page {}", human_bytes((tab.data.memory_size() + formatted) as f64))).weak())
+                                .on_hover_text(format!("Arrow data: {}\nFormatted cells: {} ({} if repeated values weren't shared)", human_bytes(tab.data.memory_size() as f64), human_bytes(formatted as f64), human_bytes(unshared as f64)));
+                        }
+                        if tab.page_cache.len() > 0 {
+                            ui.separator();
+                            ui.label(egui::RichText::new(format!("{} cached · {}", tab.page_cache.len(), human_bytes(tab.page_cache.memory_size() as f64))).weak()).on_hover_text("Recently viewed pages kept in memory so paging back is instant");
+                        }
+                        
+                            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                ui.add_space(4.0);
+                                if ui.selectable_label(tab.scroll_mode, "↕ Scroll").on_hover_text("Scroll through every row instead of paging; rows load as they come into view").clicked() {
+                                    tab.scroll_mode = !tab.scroll_mode; tab.scroll_blocks.clear();
+                                    // Scrolling sizes itself by the row count
+                                    if tab.scroll_mode && tab.count_skipped { Self::count_now(self.tx.clone(), self.backend.clone(), tab); }
+                                }
+                                if tab.scroll_mode { return; }
+                                let hint = if tab.data.len() <= TRANSPOSE_MAX_ROWS { "Show each column as a row and each row as a column".to_string() } else { format!("Only pages of up to {} rows can be transposed", TRANSPOSE_MAX_ROWS) };
+                                if ui.add_enabled(tab.data.len() <= TRANSPOSE_MAX_ROWS, egui::Button::selectable(tab.transposed, "⤡ Transpose")).on_hover_text(hint.clone()).on_disabled_hover_text(hint).clicked() { tab.transposed = !tab.transposed; }
+                                ui.separator();
+                                if ui.add_enabled(tab.has_next_page(), egui::Button::new("Next ▶")).on_hover_text("Alt+Right").on_disabled_hover_text(if tab.count_failed { "The row count failed; fix the filter and apply it again" } else { "Alt+Right" }).clicked() {
+                                    Self::goto_page(self.tx.clone(), self.backend.clone(), tab, tab.current_page + 1, self.settings);
+                                }
+                                if ui.add_enabled(tab.current_page > 1, egui::Button::new("◀ Prev")).on_hover_text("Alt+Left").clicked() {
+                                    Self::goto_page(self.tx.clone(), self.backend.clone(), tab, tab.current_page - 1, self.settings);
+                                }
+                                
+                                ui.separator();
+                                
+                                // Jump to Page Group
                                 if ui.add(egui::TextEdit::singleline(&mut tab.jump_page_buffer).desired_width(40.0)).lost_focus() || ui.input(|i| i.key_pressed(egui::Key::Enter)) {
                                     if let Ok(p) = tab.jump_page_buffer.parse::<usize>() {
-                                        let p = p.clamp(1, total_p); tab.current_page = p; tab.jump_page_buffer = p.to_string();
-                                        Self::load_page(self.tx.clone(), self.backend.clone(), tab.path.clone(), tab.current_page, tab.page_size, tab.filter.clone(), tab.sort.clone());
+                                        // Without a count there's no last page yet; the count clamps the page once it answers
+                                        let p = if tab.count_known() { p.clamp(1, total_p) } else { p.max(1) };
+                                        Self::goto_page(self.tx.clone(), self.backend.clone(), tab, p, self.settings);
                                     }
                                 }
                                 ui.label(egui::RichText::new("Go to:").weak());
@@ -259,32 +1655,381 @@ impl<'a> TabViewer for ParquetTabViewer<'a> {
                                     for s in [100, 500, 1000, 5000, 10000] { if ui.selectable_value(&mut ts, s, s.to_string()).clicked() { c = true; } }
                                     c
                                 }).inner.unwrap_or(false) {
-                                    tab.page_size = ts; tab.current_page = 1; tab.jump_page_buffer = "1".to_string();
-                                    Self::refresh_data(self.tx.clone(), self.backend.clone(), tab.path.clone(), tab.filter.clone(), tab.sort.clone(), tab.page_size);
+                                    // Stay on the page holding the first row shown, which the row count may have moved past
+                                    let page = (tab.current_page - 1) * tab.page_size / ts + 1;
+                                    let page = if tab.count_known() { clamp_page(page, tab.total_rows, ts) } else { page };
+                                    tab.page_size = ts;
+                                    Self::goto_page(self.tx.clone(), self.backend.clone(), tab, page, self.settings);
                                 }
                                 ui.label(egui::RichText::new("Page Size:").weak());
                             });
                     });
                 });
 
+                if self.settings.show_row_groups && tab.row_groups.is_none() && !tab.fields.is_empty() {
+                    tab.row_groups = Some(Vec::new());
+                    Self::load_row_groups(self.tx.clone(), self.backend.clone(), tab.path.clone());
+                }
+                // Row groups only line up with the grid while rows are in file order
+                let file_order = tab.filter.trim().is_empty() && tab.sort.trim().is_empty();
+                if let Some(groups) = tab.row_groups.as_ref().filter(|g| self.settings.show_row_groups && file_order && g.len() > 1 && tab.total_rows > 0) {
+                    egui::SidePanel::right(format!("row_groups_{}", tab.path)).exact_width(10.0).resizable(false).frame(egui::Frame::NONE).show_inside(ui, |ui| {
+                        let rect = ui.max_rect();
+                        let resp = ui.allocate_rect(rect, egui::Sense::hover());
+                        let total = tab.total_rows as f32;
+                        let y = |row: usize| rect.top() + rect.height() * (row as f32 / total).min(1.0);
+                        let (start, end) = if tab.scroll_mode { (tab.scroll_blocks.shown.start, tab.scroll_blocks.shown.end) } else { ((tab.current_page - 1) * tab.page_size, (tab.current_page - 1) * tab.page_size + tab.data.len()) };
+                        let painter = ui.painter();
+                        painter.rect_filled(rect, 0.0, ui.visuals().extreme_bg_color);
+                        painter.rect_filled(egui::Rect::from_x_y_ranges(rect.x_range(), y(start)..=y(end).max(y(start) + 2.0)), 0.0, ui.visuals().selection.bg_fill.gamma_multiply(0.6));
+                        for g in groups.iter().skip(1) { painter.hline(rect.x_range(), y(g.first_row), egui::Stroke::new(1.0, ui.visuals().weak_text_color())); }
+                        if let Some(pos) = resp.hover_pos() {
+                            let row = ((pos.y - rect.top()) / rect.height() * total) as usize;
+                            if let Some((i, g)) = groups.iter().enumerate().find(|(_, g)| row < g.first_row + g.rows) {
+                                let spanned = groups.iter().filter(|g| g.first_row < end && start < g.first_row + g.rows).count();
+                                let file_bytes: u64 = groups.iter().map(|g| g.bytes).sum();
+                                resp.on_hover_text(format!("Row group {} of {}: rows {}–{} ({} rows, {})\nThis page spans {} row group(s)\nFile: {} uncompressed", i + 1, groups.len(), g.first_row + 1, g.first_row + g.rows, g.rows, human_bytes(g.bytes as f64), spanned, human_bytes(file_bytes as f64)));
+                            }
+                        }
+                    });
+                }
+
                 egui::CentralPanel::default().frame(egui::Frame::NONE).show_inside(ui, |ui| {
-                    if tab.data.is_empty() && !tab.status.is_empty() && tab.last_error.is_none() {
-                        ui.centered_and_justified(|ui| { ui.vertical_centered(|ui| { ui.add(egui::Spinner::new().size(32.0)); ui.heading(&tab.status); }); });
+                    if tab.data.is_empty() && tab.state.is_loading() && tab.last_error.is_none() {
+                        ui.centered_and_justified(|ui| { ui.vertical_centered(|ui| { ui.add(egui::Spinner::new().size(32.0)); ui.heading(tab.state.text());
+                            if let Some(step) = tab.state.open_step() { ui.add(egui::ProgressBar::new(step as f32 / LoadState::OPEN_STEPS as f32).desired_width(240.0).text(format!("Step {} of {}", step + 1, LoadState::OPEN_STEPS))); }
+                            ui.horizontal_wrapped(|ui| Self::load_timer(ui, &self.backend, &tab.path, tab.load_started, self.settings.slow_query_secs)); }); });
+                    } else if let (true, Some(err)) = (tab.data.is_empty(), &tab.last_error) {
+                        let (message, details) = err.split_once(ERROR_DETAILS_SEPARATOR).map_or((err.as_str(), None), |(m, d)| (m, Some(d)));
+                        ui.add_space(24.0);
+                        ui.vertical_centered(|ui| {
+                            ui.heading(egui::RichText::new("Could not load file").color(ui.visuals().error_fg_color));
+                            ui.add_space(8.0); ui.label(message);
+                            if let Some(d) = details { egui::CollapsingHeader::new("Details").id_salt(("error_details", &tab.path)).show(ui, |ui| { ui.label(egui::RichText::new(d).monospace().small()); }); }
+                        });
+                    } else if tab.data.is_empty() && !tab.scroll_mode && !tab.count_pending {
+                        ui.add_space(24.0);
+                        ui.vertical_centered(|ui| {
+                            let filtered = !tab.filter.trim().is_empty();
+                            let message = if tab.total_rows > 0 { "No rows on this page" } else if filtered { "No rows match the current filter" } else { "This file has no rows" };
+                            ui.heading(egui::RichText::new(message).weak());
+                            if filtered && tab.total_rows == 0 && ui.button("Clear filter").clicked() { tab.filter.clear(); Self::apply_filter(self.tx.clone(), self.backend.clone(), tab); }
+                        });
+                    } else if tab.transposed && !tab.scroll_mode && tab.data.len() <= TRANSPOSE_MAX_ROWS {
+                        let area = egui::ScrollArea::horizontal().id_salt(format!("transposed_scroll_{}", tab.path)).auto_shrink([false, false]);
+                        if let Some((row, ci)) = area.show(ui, |ui| Self::transposed_grid(ui, tab, self.settings.row_density, self.settings.ordinal_base(), self.settings.group_digits)).inner {
+                            if let Some(column) = tab.columns.get(ci).cloned() {
+                                tab.cell_detail = Some(CellDetail { row, column: column.clone(), value: None });
+                                Self::load_cell(self.tx.clone(), self.backend.clone(), tab.path.clone(), tab.filter.clone(), tab.sort.clone(), row, column);
+                            }
+                        }
                     } else {
-                        egui::ScrollArea::both().id_salt(format!("scroll_{}", tab.path)).show(ui, |ui| {
-                            let mut table = egui_extras::TableBuilder::new(ui).striped(true).resizable(true).cell_layout(egui::Layout::left_to_right(egui::Align::Center));
+                        // The outer area only scrolls horizontally; vertical scrolling belongs to the table body
+                        // so the header row stays pinned. Wrapping the table in a vertical ScrollArea gives it
+                        // unbounded height and the header scrolls away with the rows.
+                        let mut area = egui::ScrollArea::horizontal().id_salt(format!("scroll_{}", tab.path)).auto_shrink([false, false]);
+                        if let Some(x) = tab.scroll_to_x.take() { area = area.horizontal_scroll_offset(x); }
+                        area.show(ui, |ui| {
+                            let origin = ui.max_rect().left();
+                            tab.col_spans.resize(tab.columns.len(), (0.0, 0.0));
+                            let now = ui.input(|i| i.time);
+                            let flash = tab.col_flash.filter(|(_, t)| now - t < 1.5);
+                            if flash.is_some() { ui.ctx().request_repaint(); } else { tab.col_flash = None; }
+                            let density = self.settings.row_density;
+                            if density != RowDensity::Normal { for style in [egui::TextStyle::Body, egui::TextStyle::Button] { if let Some(f) = ui.style_mut().text_styles.get_mut(&style) { f.size *= density.font_scale(); } } }
+                            // Sample widths from the first page that actually has rows
+                            if tab.col_widths.len() != tab.columns.len() && !tab.data.is_empty() { tab.col_widths = initial_col_widths(ui, &tab.columns, &tab.data); }
+                            let order = tab.visible_order();
+                            let separator = self.settings.grid_lines.then(|| ui.visuals().widgets.noninteractive.bg_stroke);
+                            // Only the columns in view are laid out, with spacers standing in for the rest so the scrollbar
+                            // keeps its length; a file with thousands of columns would otherwise stall every frame
+                            let spacing = ui.spacing().item_spacing.x;
+                            let widths: Vec<f32> = order.iter().map(|&ci| tab.col_widths.get(ci).copied().unwrap_or(150.0).max(100.0)).collect();
+                            let lefts: Vec<f32> = widths.iter().scan(40.0 + spacing, |x, w| { let left = *x; *x += w + spacing; Some(left) }).collect();
+                            for ((&ci, &left), &w) in order.iter().zip(&lefts).zip(&widths) { if let Some(span) = tab.col_spans.get_mut(ci) { *span = (left, left + w); } }
+                            let view = ui.clip_rect().x_range();
+                            let visible = visible_columns(&lefts, &widths, view.min - origin, view.max - origin);
+                            let end = lefts.last().map_or(0.0, |l| l + widths[widths.len() - 1] + spacing);
+                            let lead = lefts.get(visible.start).map_or(0.0, |l| l - (40.0 + spacing));
+                            let trail = lefts.get(visible.end).map_or(0.0, |l| end - l);
+                            // The salt stays fixed so the body keeps its scroll offset; when the laid-out columns shift, only the stored
+                            // widths are reset, and since resized widths are written back to col_widths the new columns pick them up
+                            let mut table = egui_extras::TableBuilder::new(ui).id_salt(("grid", &tab.path)).striped(true).resizable(true).vscroll(true).auto_shrink([false, false]).cell_layout(egui::Layout::left_to_right(egui::Align::Center));
+                            let layout = (order.clone(), visible.start);
+                            if tab.grid_layout.as_ref() != Some(&layout) { table.reset(); tab.grid_layout = Some(layout); }
                             table = table.column(Column::initial(40.0).at_least(40.0));
-                            for _ in 0..tab.schema.len() { table = table.column(Column::initial(150.0).at_least(100.0)); }
-                            table.header(28.0, |mut h| { h.col(|ui| { ui.strong("#"); }); for n in &tab.schema { h.col(|ui| { ui.strong(n); }); } }).body(|b| {
-                                let start = (tab.current_page - 1) * tab.page_size;
-                                b.rows(26.0, tab.data.len(), |mut r| {
-                                    let i = r.index(); r.col(|ui| { ui.label((start + i + 1).to_string()); });
-                                    if let Some(rd) = tab.data.get(i) { for c in rd { r.col(|ui| { if c == "(null)" { ui.label(egui::RichText::new(c).weak()); } else { ui.label(c); } }); } }
+                            if lead > 0.0 { table = table.column(Column::exact((lead - spacing).max(0.0))); }
+                            for &w in &widths[visible.clone()] { table = table.column(Column::initial(w).at_least(100.0)); }
+                            if trail > 0.0 { table = table.column(Column::exact((trail - spacing).max(0.0))); }
+                            let (mut clicked_row, mut clicked_col, mut format_change, mut open_cell, mut column_copy, mut copy_json) = (None, None, None, None, None, None);
+                            let (mut picked_cell, mut in_filter) = (None, None);
+                            let (mut reorder, mut reset_order, mut hide, mut show_all) = (None, false, None, false);
+                            let mut shown: Option<std::ops::Range<usize>> = None;
+                            let ordinal_base = self.settings.ordinal_base();
+                            let group_digits = self.settings.group_digits;
+                            let settings = &mut *self.settings;
+                            let paint_separator = |ui: &mut egui::Ui| if let Some(stroke) = separator { let rect = ui.max_rect(); ui.painter().vline(rect.right(), rect.y_range(), stroke); };
+                            table.header(density.row_height() + 2.0, |mut h| {
+                                h.col(|ui| { paint_separator(ui); ui.strong("#"); });
+                                if lead > 0.0 { h.col(|_| {}); }
+                                for (pos, &ci) in order.iter().enumerate().skip(visible.start).take(visible.len()) { let n = &tab.columns[ci]; h.col(|ui| {
+                                    paint_separator(ui);
+                                    let rect = ui.max_rect();
+                                    if let Some(span) = tab.col_spans.get_mut(ci) { *span = (rect.left() - origin, rect.right() - origin); }
+                                    if let Some(w) = tab.col_widths.get_mut(ci) { *w = rect.width(); }
+                                    if let Some((_, t)) = flash.filter(|(c, _)| *c == ci) { ui.painter().rect_filled(rect, 2.0, ui.visuals().selection.bg_fill.gamma_multiply(1.0 - ((now - t) / 1.5) as f32)); }
+                                    let current = tab.column_formats.get(n).copied();
+                                    let mut resp = ui.dnd_drag_source(egui::Id::new(("col_drag", &tab.path, ci)), pos, |ui| ui.selectable_label(tab.selected_cols.contains(&ci), egui::RichText::new(column_header(n, ci, ordinal_base)).strong())).response;
+                                    if let Some((min, max)) = tab.data.numeric_range(ci).filter(|_| !tab.scroll_mode) {
+                                        // Where the page's values fall on a scale that always includes zero
+                                        let (lo, hi) = (min.min(0.0), max.max(0.0));
+                                        let x = |v: f64| rect.left() + 4.0 + (rect.width() - 8.0) * if hi > lo { ((v - lo) / (hi - lo)) as f32 } else { 0.0 };
+                                        let y = rect.bottom() - 2.0;
+                                        ui.painter().hline(rect.left() + 4.0..=rect.right() - 4.0, y, egui::Stroke::new(2.0, ui.visuals().extreme_bg_color));
+                                        ui.painter().hline(x(min)..=x(max).max(x(min) + 2.0), y, egui::Stroke::new(2.0, ui.visuals().selection.bg_fill.gamma_multiply(0.8)));
+                                        resp = resp.on_hover_text(format!("This page: {} to {}", min, max));
+                                    }
+                                    if resp.clicked() { clicked_col = Some(ci); }
+                                    // Dropping marks the edge the column will land on
+                                    let drop = ui.interact(rect, egui::Id::new(("col_drop", &tab.path, ci)), egui::Sense::hover());
+                                    if let Some(from) = drop.dnd_hover_payload::<usize>().filter(|from| **from != pos) {
+                                        let x = if *from < pos { rect.right() } else { rect.left() };
+                                        ui.painter().vline(x, rect.y_range(), ui.visuals().selection.stroke);
+                                    }
+                                    if let Some(from) = drop.dnd_release_payload::<usize>() { reorder = Some((*from, pos)); }
+                                    resp.context_menu(|ui| {
+                                        ui.label(egui::RichText::new("Display format").weak());
+                                        if ui.radio(current.is_none(), "Default").clicked() { format_change = Some((n.clone(), None)); ui.close(); }
+                                        for f in ColumnFormat::ALL { if ui.radio(current == Some(f), f.label()).clicked() { format_change = Some((n.clone(), Some(f))); ui.close(); } }
+                                        if !tab.column_order.is_empty() { ui.separator(); if ui.button("Reset column order").clicked() { reset_order = true; ui.close(); } }
+                                        ui.separator();
+                                        if ui.add_enabled(order.len() > 1, egui::Button::new("Hide column")).clicked() { hide = Some(n.clone()); ui.close(); }
+                                        if !tab.hidden_columns.is_empty() && ui.button(format!("Show all columns ({} hidden)", tab.hidden_columns.len())).clicked() { show_all = true; ui.close(); }
+                                        ui.separator();
+                                        ui.menu_button("Copy column values", |ui| {
+                                            if ui.button("Current page").clicked() { column_copy = Some((ci, false)); ui.close(); }
+                                            if ui.button("Entire result").clicked() { column_copy = Some((ci, true)); ui.close(); }
+                                            ui.separator();
+                                            ui.checkbox(&mut settings.copy_as_in_list, "Quote and comma-separate (IN list)");
+                                            ui.checkbox(&mut settings.copy_skip_nulls, "Skip NULLs");
+                                        });
+                                    });
+                                }); }
+                                if trail > 0.0 { h.col(|_| {}); }
+                            }).body(|b| {
+                                // Scroll mode numbers rows from the top of the result and reads them from whichever block holds them
+                                let (start, rows) = if tab.scroll_mode { (0, tab.total_rows) } else { ((tab.current_page - 1) * tab.page_size, tab.data.len()) };
+                                let blocks = &tab.scroll_blocks;
+                                let page_of = |i: usize| if tab.scroll_mode { blocks.get(i / SCROLL_BLOCK_ROWS) } else { Some(&tab.data) };
+                                let row_data = |i: usize| page_of(i).and_then(|d| d.row(if tab.scroll_mode { i % SCROLL_BLOCK_ROWS } else { i }));
+                                b.rows(density.row_height(), rows, |mut r| {
+                                    let i = r.index(); r.set_selected(tab.selected_rows.contains(&(start + i)));
+                                    shown = Some(shown.take().map_or(i..i + 1, |s| s.start.min(i)..s.end.max(i + 1)));
+                                    let tint = row_data(i).and_then(|rd| rules::row_color(&tab.row_rules, &tab.columns, rd)).map(|c| c.gamma_multiply(0.35));
+                                    let paint_tint = |ui: &mut egui::Ui| if let Some(c) = tint { ui.painter().rect_filled(ui.max_rect(), 0.0, c); };
+                                    r.col(|ui| {
+                                        paint_tint(ui); paint_separator(ui);
+                                        let resp = ui.add(egui::Label::new((start + i + 1).to_string()).sense(egui::Sense::click()));
+                                        if resp.clicked() { clicked_row = Some(start + i); }
+                                        resp.context_menu(|ui| {
+                                            if ui.button("Copy selection as JSON").clicked() { copy_json = Some(start + i); ui.close(); }
+                                            ui.checkbox(&mut settings.json_camel_case, "camelCase keys");
+                                        });
+                                    });
+                                    if lead > 0.0 { r.col(|_| {}); }
+                                    if let Some(rd) = row_data(i) { for &ci in &order[visible.clone()] { r.col(|ui| {
+                                        paint_tint(ui); paint_separator(ui);
+                                        let Some(c) = rd.get(ci).map(|c| &**c) else { return };
+                                        let picked = tab.selected_cells.as_ref().is_some_and(|s| s.column == ci && s.values.contains_key(&(start + i)));
+                                        if picked { ui.painter().rect_filled(ui.max_rect(), 0.0, ui.visuals().selection.bg_fill.gamma_multiply(0.6)); }
+                                        let resp = if c == "(null)" { ui.add(egui::Label::new(egui::RichText::new(c).weak()).sense(egui::Sense::click())) }
+                                        else if let Some(shown) = format::display_cell(c, tab.columns.get(ci).and_then(|n| tab.column_formats.get(n)).copied(), group_digits && page_of(i).is_some_and(|d| d.is_numeric_column(ci))) { ui.add(egui::Label::new(shown).sense(egui::Sense::click())).on_hover_text(c) }
+                                        else if is_capped(c) { ui.add(egui::Label::new(c).sense(egui::Sense::click())).on_hover_text("Cut short by the cell fetch cap; double-click for the full value") }
+                                        else if is_rtl(c) { ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| ui.add(egui::Label::new(rtl_visual(c)).sense(egui::Sense::click()))).inner.on_hover_text(c) }
+                                        else { ui.add(egui::Label::new(c).sense(egui::Sense::click())) };
+                                        if resp.double_clicked() { open_cell = Some((start + i, ci)); }
+                                        if resp.clicked() && ui.input(|i| i.modifiers.command) { picked_cell = Some((start + i, ci, c.to_string())); }
+                                        resp.context_menu(|ui| {
+                                            // Right-clicking outside the picked cells filters on just this one
+                                            let values: Vec<String> = match &tab.selected_cells { Some(s) if picked => s.values.values().cloned().collect(), _ => vec![c.to_string()] };
+                                            let name = tab.columns.get(ci).map_or("", |n| n.as_str());
+                                            if values.iter().any(|v| is_capped(v)) { ui.label(egui::RichText::new("A value is cut short by the cell fetch cap").weak()); return; }
+                                            // The grid only has a blob's size, not its bytes
+                                            if tab.fields.get(ci).is_some_and(|f| f.data_type == "BLOB") { ui.label(egui::RichText::new("BLOB columns can't be filtered on by value").weak()); return; }
+                                            if ui.button(format!("Filter: {} IN ({} values)", name, values.len())).on_hover_text("Replace the filter; Ctrl/Cmd-click cells in this column to pick more values").clicked() { in_filter = Some((ci, values.clone(), false)); ui.close(); }
+                                            if !tab.filter.trim().is_empty() && ui.button("AND with filter").clicked() { in_filter = Some((ci, values, true)); ui.close(); }
+                                        });
+                                    }); } } else if tab.scroll_mode {
+                                        for _ in visible.clone() { r.col(|ui| { paint_separator(ui); ui.painter().rect_filled(ui.max_rect().shrink2(egui::vec2(4.0, 5.0)), 2.0, ui.visuals().widgets.inactive.bg_fill); }); }
+                                    }
+                                    if trail > 0.0 { r.col(|_| {}); }
                                 });
                             });
+                            if let Some(shown) = shown.filter(|_| tab.scroll_mode) {
+                                let needed = shown.start / SCROLL_BLOCK_ROWS..=(shown.end - 1) / SCROLL_BLOCK_ROWS;
+                                for block in needed.clone() { tab.scroll_blocks.touch(block); }
+                                if let Some(block) = needed.into_iter().find(|&b| tab.scroll_blocks.missing(b)).filter(|_| tab.scroll_blocks.loading.is_none()) {
+                                    let request = next_request(); tab.scroll_blocks.loading = Some((request, block));
+                                    Self::load_block(self.tx.clone(), self.backend.clone(), request, tab.path.clone(), block, tab.filter.clone(), tab.sort.clone());
+                                }
+                                tab.scroll_blocks.shown = shown;
+                            }
+                            if let Some((from, to)) = reorder.filter(|(from, to)| from != to && *from < order.len()) {
+                                let mut names: Vec<String> = order.iter().map(|&c| tab.columns[c].clone()).collect();
+                                let moved = names.remove(from); names.insert(to, moved);
+                                tab.column_order = names;
+                            }
+                            if reset_order { tab.column_order.clear(); }
+                            if let Some(n) = hide { tab.selected_cols.clear(); tab.hidden_columns.push(n); }
+                            if show_all { tab.hidden_columns.clear(); }
+                            let mods = ui.input(|i| i.modifiers);
+                            if let Some(g) = clicked_row { select_row(&mut tab.selected_rows, &mut tab.selection_anchor, g, mods); }
+                            if let Some(g) = copy_json {
+                                // Right-clicking outside the selection copies just that row
+                                if !tab.selected_rows.contains(&g) { tab.selected_rows = BTreeSet::from([g]); tab.selection_anchor = Some(g); }
+                                Self::copy_selection_json(self.tx.clone(), self.backend.clone(), tab, settings.json_camel_case);
+                            }
+                            if let Some(c) = clicked_col { if !tab.selected_cols.remove(&c) { tab.selected_cols.insert(c); } }
+                            match format_change { Some((n, Some(f))) => { tab.column_formats.insert(n, f); } Some((n, None)) => { tab.column_formats.remove(&n); } None => {} }
+                            match column_copy {
+                                Some((ci, false)) => {
+                                    let numeric = tab.fields.get(ci).is_some_and(|f| export::is_numeric_type(&f.data_type));
+                                    let (in_list, skip_nulls) = (settings.copy_as_in_list, settings.copy_skip_nulls);
+                                    let text = move |data: &PageData| export::column_values_text(data.iter().map(|r| r.get(ci).map(|c| &**c).filter(|c| *c != export::NULL_DISPLAY)), numeric, in_list, skip_nulls);
+                                    if tab.data.iter().any(|r| r.get(ci).is_some_and(|c| is_capped(c))) {
+                                        let note = format!("Copied page {} values of {}", tab.current_page, tab.columns.get(ci).map_or("", |c| c.as_str()));
+                                        Self::copy_full_page(self.tx.clone(), self.backend.clone(), tab, note, move |_, data| text(data));
+                                    } else { ui.ctx().copy_text(text(&tab.data)); }
+                                }
+                                Some((ci, true)) => if let Some(column) = tab.columns.get(ci).cloned() {
+                                    // A count not yet known could be any size, so it asks as well
+                                    if !tab.count_known() || tab.total_rows > settings.copy_confirm_rows { tab.pending_column_copy = Some(column); }
+                                    else { Self::load_column_values(self.tx.clone(), self.backend.clone(), tab.path.clone(), tab.filter.clone(), tab.sort.clone(), column); }
+                                },
+                                None => {}
+                            }
+                            if let Some((row, ci, value)) = picked_cell {
+                                // Picking in another column starts over, since the filter is on one column
+                                let sel = tab.selected_cells.get_or_insert_with(|| CellSelection { column: ci, values: BTreeMap::new() });
+                                if sel.column != ci { *sel = CellSelection { column: ci, values: BTreeMap::new() }; }
+                                if sel.values.remove(&row).is_none() { sel.values.insert(row, value); }
+                                if sel.values.is_empty() { tab.selected_cells = None; }
+                            }
+                            if let Some((ci, values, and)) = in_filter {
+                                let numeric = tab.fields.get(ci).is_some_and(|f| export::is_numeric_type(&f.data_type));
+                                let clause = in_list_filter(&tab.columns[ci], &values, numeric);
+                                tab.filter = if and { format!("({}) AND {}", tab.filter.trim(), clause) } else { clause };
+                                Self::apply_filter(self.tx.clone(), self.backend.clone(), tab);
+                            }
+                            if let Some((row, ci)) = open_cell {
+                                if let Some(column) = tab.columns.get(ci).cloned() {
+                                    tab.cell_detail = Some(CellDetail { row, column: column.clone(), value: None });
+                                    Self::load_cell(self.tx.clone(), self.backend.clone(), tab.path.clone(), tab.filter.clone(), tab.sort.clone(), row, column);
+                                }
+                            }
                         });
                     }
                 });
+
+                let mut open = tab.cell_detail.is_some();
+                if let Some(d) = &tab.cell_detail {
+                    egui::Window::new(format!("{} — row {}", d.column, d.row + 1)).id(egui::Id::new(("cell_detail", &tab.path))).open(&mut open).default_size([420.0, 300.0]).show(ui.ctx(), |ui| {
+                        match &d.value {
+                            None => { ui.add(egui::Spinner::new()); }
+                            Some(None) => { ui.label(egui::RichText::new("This row no longer exists; the file may have changed. Try reloading.").color(ui.visuals().warn_fg_color)); }
+                            Some(Some(v)) => {
+                                if ui.button("Copy").clicked() { ui.ctx().copy_text(v.clone()); }
+                                egui::ScrollArea::vertical().show(ui, |ui| { ui.add(egui::TextEdit::multiline(&mut v.as_str()).code_editor().desired_width(f32::INFINITY)); });
+                            }
+                        }
+                    });
+                }
+                if !open { tab.cell_detail = None; }
+
+                if tab.show_rules {
+                    let (mut open, mut remove) = (true, None);
+                    egui::Window::new(format!("Row rules: {}", tab.name)).id(egui::Id::new(("row_rules", &tab.path))).open(&mut open).resizable(false).show(ui.ctx(), |ui| {
+                        for (ri, rule) in tab.row_rules.iter_mut().enumerate() {
+                            ui.horizontal(|ui| {
+                                ui.checkbox(&mut rule.enabled, "");
+                                egui::ComboBox::from_id_salt(("rule_col", ri)).selected_text(&rule.column).width(120.0).show_ui(ui, |ui| { for n in &tab.schema { ui.selectable_value(&mut rule.column, n.clone(), n); } });
+                                egui::ComboBox::from_id_salt(("rule_op", ri)).selected_text(rule.op.label()).width(80.0).show_ui(ui, |ui| { for op in RuleOp::ALL { ui.selectable_value(&mut rule.op, op, op.label()); } });
+                                ui.add_enabled(rule.op.takes_value(), egui::TextEdit::singleline(&mut rule.value).desired_width(100.0));
+                                ui.color_edit_button_srgba(&mut rule.color);
+                                if ui.small_button("🗑").clicked() { remove = Some(ri); }
+                            });
+                        }
+                        if tab.row_rules.is_empty() { ui.label(egui::RichText::new("No rules yet. Rows matching a rule are tinted with its color.").weak()); }
+                        if ui.add_enabled(!tab.schema.is_empty(), egui::Button::new("Add rule")).clicked() { tab.row_rules.push(RowRule::new(tab.schema[0].clone())); }
+                    });
+                    if let Some(ri) = remove { tab.row_rules.remove(ri); }
+                    if !open { tab.show_rules = false; }
+                }
+
+                if let Some(term) = &mut tab.filter_builder {
+                    let (mut open, mut add) = (true, None);
+                    // Struct fields and list elements are offered along with the columns they're in
+                    let paths: Vec<backend::FieldPath> = tab.fields.iter().flat_map(backend::field_paths).collect();
+                    let pick = |ui: &mut egui::Ui, id: &str, current: &mut backend::FieldPath| {
+                        egui::ComboBox::from_id_salt(id).selected_text(&current.label).width(160.0).height(400.0).show_ui(ui, |ui| {
+                            for p in &paths { if ui.selectable_label(*current == *p, &p.label).on_hover_text(&p.data_type).clicked() { *current = p.clone(); } }
+                        });
+                    };
+                    egui::Window::new(format!("Filter builder: {}", tab.name)).id(egui::Id::new(("filter_builder", &tab.path))).open(&mut open).resizable(false).show(ui.ctx(), |ui| {
+                        ui.horizontal(|ui| {
+                            pick(ui, "builder_col", &mut term.column);
+                            egui::ComboBox::from_id_salt("builder_op").selected_text(term.op.label()).width(80.0).show_ui(ui, |ui| { for op in RuleOp::ALL { ui.selectable_value(&mut term.op, op, op.label()); } });
+                            ui.add_enabled_ui(term.op.takes_value(), |ui| {
+                                let is_column = matches!(term.rhs, Operand::Column(_));
+                                if ui.selectable_label(!is_column, "value").clicked() && is_column { term.rhs = Operand::Value(String::new()); }
+                                if ui.selectable_label(is_column, "column").clicked() && !is_column { term.rhs = Operand::Column(term.column.clone()); }
+                                match &mut term.rhs {
+                                    Operand::Value(v) => { ui.add(egui::TextEdit::singleline(v).desired_width(120.0)); }
+                                    Operand::Column(c) => pick(ui, "builder_rhs", c),
+                                }
+                            });
+                        });
+                        let sql = term.sql(export::is_numeric_type(&term.column.data_type));
+                        ui.label(egui::RichText::new(&sql).monospace().weak());
+                        ui.horizontal(|ui| {
+                            if ui.add_enabled(!tab.filter.trim().is_empty(), egui::Button::new("AND with filter")).clicked() { add = Some(format!("({}) AND {}", tab.filter.trim(), sql)); }
+                            if ui.button("Replace filter").clicked() { add = Some(sql); }
+                        });
+                    });
+                    if let Some(filter) = add { tab.filter = filter; Self::apply_filter(self.tx.clone(), self.backend.clone(), tab); }
+                    if !open { tab.filter_builder = None; }
+                }
+
+                if let Some(column) = tab.pending_column_copy.clone() {
+                    let (mut confirmed, mut cancelled) = (false, false);
+                    egui::Window::new("Copy column values").id(egui::Id::new(("column_copy", &tab.path))).collapsible(false).resizable(false).show(ui.ctx(), |ui| {
+                        let values = if tab.count_known() { format!("{} values", tab.total_rows) } else { "every value (not counted)".to_string() };
+                        ui.label(format!("Copy {} of \"{}\" to the clipboard?", values, column));
+                        ui.horizontal(|ui| { confirmed = ui.button("Copy").clicked(); cancelled = ui.button("Cancel").clicked(); });
+                    });
+                    if confirmed { Self::load_column_values(self.tx.clone(), self.backend.clone(), tab.path.clone(), tab.filter.clone(), tab.sort.clone(), column); }
+                    if confirmed || cancelled { tab.pending_column_copy = None; }
+                }
+
+                if let Some((format, mut limit)) = tab.pending_rows_copy {
+                    let (mut copy, mut cancelled) = (None, false);
+                    egui::Window::new("Copy rows").id(egui::Id::new(("rows_copy", &tab.path))).collapsible(false).resizable(false).show(ui.ctx(), |ui| {
+                        let rows = if tab.count_known() { format!("{} rows", tab.total_rows) } else { "every row (not counted)".to_string() };
+                        ui.label(format!("Copy {} as {} to the clipboard?", rows, format.extension().to_uppercase()));
+                        ui.label(egui::RichText::new("Pasting this much can freeze the app it goes into.").weak());
+                        ui.horizontal(|ui| {
+                            if ui.button("Copy first").clicked() { copy = Some(Some(limit)); }
+                            ui.add(egui::DragValue::new(&mut limit).range(1..=self.backend.row_cap()).speed(100).suffix(" rows"));
+                        });
+                        ui.horizontal(|ui| {
+                            if ui.button("Copy all").on_hover_text(format!("Up to the row cap of {}", self.backend.row_cap())).clicked() { copy = Some(None); }
+                            cancelled = ui.button("Cancel").clicked();
+                        });
+                    });
+                    tab.pending_rows_copy = Some((format, limit));
+                    if let Some(limit) = copy { Self::copy_rows(self.tx.clone(), self.backend.clone(), tab, format, limit); }
+                    if copy.is_some() || cancelled { tab.pending_rows_copy = None; }
+                }
             });
         }
     }
@@ -292,30 +2037,550 @@ impl<'a> TabViewer for ParquetTabViewer<'a> {
 
 impl eframe::App for ParquetApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        // Picked up by the next query, so edits in the View menu apply without a restart
+        self.backend.set_query_timeout(self.settings.query_timeout_secs);
+        self.backend.set_cell_fetch_chars(self.settings.cell_fetch_chars);
+        self.backend.set_row_cap(self.settings.max_result_rows);
+        let dropped: Vec<std::path::PathBuf> = ctx.input(|i| i.raw.dropped_files.iter().filter_map(|f| f.path.clone()).collect());
+        if !dropped.is_empty() {
+            let (accepted, rejected): (Vec<_>, Vec<_>) = dropped.into_iter().partition(|p| self.settings.accepts_file(p));
+            for p in rejected {
+                self.logs.push(LogEntry { time: chrono::Local::now().format("%H:%M:%S").to_string(), path: p.to_string_lossy().to_string(), sql: "N/A".to_string(), error: Some("Not one of the open file types; add its ending under File > Settings to open it".to_string()) });
+                self.show_console = true;
+            }
+            if !accepted.is_empty() { self.dialog_answered(PendingDialog::Open, accepted); }
+        }
         while let Ok(msg) = self.rx.try_recv() {
             let ts = chrono::Local::now().format("%H:%M:%S").to_string();
             match msg {
-                BackendMessage::FileOpened { path } => { if let Some(t) = self.tabs.get_mut(&path) { t.status = "Opening...".to_string(); } }
-                BackendMessage::Schema { path, columns } => { if let Some(t) = self.tabs.get_mut(&path) { t.schema = columns; } }
-                BackendMessage::RowCount { path, count, sql } => { self.logs.push(LogEntry { time: ts.clone(), path: path.clone(), sql, error: None }); if let Some(t) = self.tabs.get_mut(&path) { t.total_rows = count; } }
-                BackendMessage::QueryData { path, rows, sql } => { self.logs.push(LogEntry { time: ts, path: path.clone(), sql, error: None }); if let Some(t) = self.tabs.get_mut(&path) { t.data = rows; t.row_count = t.data.len(); t.status.clear(); } }
+                BackendMessage::MemoryUsage { duckdb, spilled, process } => { self.memory = Some((duckdb, spilled, process)); }
+                BackendMessage::FileDialog { request, paths } => { if let Some(pending) = self.dialogs.remove(&request) { self.dialog_answered(pending, paths); } }
+                BackendMessage::FileBusy { path, attempt, attempts } => { if let Some(t) = self.tabs.get_mut(&path) { t.state = LoadState::FileBusy { attempt, attempts }; } }
+                BackendMessage::FileOpened { path } => { if let Some(t) = self.tabs.get_mut(&path) { t.state = t.state.next(LoadEvent::FileOpened); } }
+                BackendMessage::Schema { path, fields } => { if let Some(t) = self.tabs.get_mut(&path) {
+                    t.schema = fields.iter().map(|f| f.name.clone()).collect();
+                    // A saved order only survives reopening the same set of columns
+                    let (mut saved, mut names) = (t.column_order.clone(), t.schema.clone()); saved.sort(); names.sort();
+                    if saved != names { t.column_order.clear(); }
+                    t.fields = fields; t.col_widths.clear(); t.page_cache.clear(); t.shown_key = None;
+                    t.state = t.state.next(LoadEvent::Schema);
+                    // Nothing to count, so straight on to the first page
+                    if t.count_skipped { t.state = t.state.next(LoadEvent::Counted); }
+                } }
+                BackendMessage::RowCount { request, path, count, sql } => {
+                    self.logs.push(LogEntry { time: ts.clone(), path: path.clone(), sql, error: None });
+                    if let Some(t) = self.tabs.get_mut(&path) { t.clear_progress(request); t.count_answered(request); }
+                    if let Some(t) = self.tabs.get_mut(&path).filter(|t| request >= t.count_request) {
+                        t.total_rows = count; t.counted = request; t.count_pending = false; t.count_failed = false; t.clear_error_before(request); t.state = t.state.next(LoadEvent::Counted);
+                        // A filter or a changed file can leave fewer pages than the one on screen
+                        let page = clamp_page(t.current_page, t.total_rows, t.page_size);
+                        if page != t.current_page && !t.scroll_mode { ParquetTabViewer::goto_page(self.tx_to_ui.clone(), self.backend.clone(), t, page, &self.settings); } else { t.check_page_rows(); }
+                    }
+                }
+                BackendMessage::Progress { request, path, percent, elapsed } => {
+                    if let Some(t) = self.tabs.get_mut(&path).filter(|t| request == t.page_request || request == t.count_request) { t.progress = Some((request, percent, elapsed)); }
+                }
+                BackendMessage::CellValue { path, row, column, value } => {
+                    if let Some(d) = self.tabs.get_mut(&path).and_then(|t| t.cell_detail.as_mut()) { if d.row == row && d.column == column { d.value = Some(value); } }
+                }
+                BackendMessage::MemoryCache { path, cached, sql, error } => {
+                    // Nothing to report once the tab is gone; its table was dropped with it
+                    let Some(t) = self.tabs.get_mut(&path) else { continue };
+                    t.caching = false; t.in_memory = cached;
+                    if error.is_some() { self.show_console = true; }
+                    self.logs.push(LogEntry { time: ts, path, sql: if sql.is_empty() { "Load into memory".to_string() } else { sql }, error });
+                }
+                BackendMessage::ClipboardText { path, text, note } => { ctx.copy_text(text); self.logs.push(LogEntry { time: ts, path, sql: note, error: None }); }
+                BackendMessage::ColumnValues { path, column, values, sql, truncated } => {
+                    let numeric = values.iter().all(|v| v.is_numeric() || *v == Cell::Null);
+                    let text: Vec<Option<String>> = values.iter().map(Cell::display).collect();
+                    ctx.copy_text(export::column_values_text(text.iter().map(|v| v.as_deref()), numeric, self.settings.copy_as_in_list, self.settings.copy_skip_nulls));
+                    // A short copy is reported where it will be seen, not just noted in passing
+                    let error = truncated.then(|| format!("Copied only the first {} values: the result has more than the row cap. Raise it under View > Row cap.", values.len()));
+                    if error.is_some() { self.show_console = true; }
+                    self.logs.push(LogEntry { time: ts, path, sql: format!("{} -- copied {} values of {}", sql, values.len(), column), error });
+                }
+                BackendMessage::FilterChecked { request, path, error } => {
+                    // Dropped if the text changed again since the check started
+                    if let Some(t) = self.tabs.get_mut(&path).filter(|t| t.live_check == request && t.live_edit.is_none()) {
+                        t.live_error = error;
+                        if t.live_error.is_none() { ParquetTabViewer::apply_filter(self.tx_to_ui.clone(), self.backend.clone(), t); }
+                    }
+                }
+                // Rows of the current page still loading are shown as they land; an older load's are dropped
+                BackendMessage::QueryData { request, path, columns, batch, complete: false, .. } => {
+                    if let Some(t) = self.tabs.get_mut(&path).filter(|t| request == t.page_request) {
+                        if t.streaming == Some(request) { t.data.append(batch); }
+                        else { t.streaming = Some(request); t.clear_error_before(request); if t.columns != columns { t.col_widths.clear(); } t.columns = columns; t.data = PageData::new(batch); }
+                        t.row_count = t.data.len();
+                    }
+                }
+                BackendMessage::QueryData { request, path, columns, batch, sql, truncated, .. } => {
+                    self.logs.push(LogEntry { time: ts, path: path.clone(), sql, error: None });
+                    if let Some(t) = self.tabs.get_mut(&path) { t.clear_progress(request); t.page_answered(request); }
+                    // A block load from before Scroll was toggled or the view reset has no rows to fill
+                    if self.tabs.get_mut(&path).is_some_and(|t| t.scroll_blocks.was_dropped(request)) {}
+                    else if let Some(t) = self.tabs.get_mut(&path).filter(|t| t.scroll_blocks.loading.is_some_and(|(r, _)| r == request)) { t.scroll_blocks.loaded(PageData::new(batch)); }
+                    else if let Some(t) = self.tabs.get_mut(&path).filter(|t| request >= t.page_request) {
+                        t.clear_error_before(request); if t.columns != columns { t.col_widths.clear(); } t.columns = columns; t.data = PageData::new(batch); t.row_count = t.data.len(); t.truncated = truncated; t.state = t.state.next(LoadEvent::PageLoaded); t.streaming = None;
+                        // Pages from loads that didn't go through the cache aren't kept when paging away
+                        t.shown_key = t.loading_key.take().filter(|(r, _)| *r == request).map(|(_, k)| k);
+                        t.check_page_rows();
+                    }
+                }
+                BackendMessage::RowGroups { path, groups, sql } => {
+                    if !sql.is_empty() { self.logs.push(LogEntry { time: ts, path: path.clone(), sql, error: None }); }
+                    if let Some(t) = self.tabs.get_mut(&path) { t.row_groups = Some(groups); }
+                }
                 BackendMessage::SqlLog { path, sql } => { self.logs.push(LogEntry { time: ts, path, sql, error: None }); }
-                BackendMessage::Error { path, message, sql } => { self.logs.push(LogEntry { time: ts, path: path.clone().unwrap_or_else(|| "Global".to_string()), sql: sql.unwrap_or_else(|| "N/A".to_string()), error: Some(message.clone()) }); self.show_console = true; if let Some(p) = path { if let Some(t) = self.tabs.get_mut(&p) { t.last_error = Some(message); t.status = "Error".to_string(); } } }
+                BackendMessage::ExportProgress { job_id, rows_done, bytes_written } => { if let Some(j) = self.exports.iter_mut().find(|j| j.id == job_id) { j.rows_done = rows_done; j.bytes_written = bytes_written; } }
+                BackendMessage::ExportFiles { job_id, done, total } => { if let Some(j) = self.exports.iter_mut().find(|j| j.id == job_id) { j.files = Some((done, total)); } }
+                BackendMessage::ExportDone { job_id, rows, bytes_written, file } => {
+                    if let Some(j) = self.exports.iter_mut().find(|j| j.id == job_id) { j.rows_done = rows; j.bytes_written = bytes_written; j.finished = Some((j.started.elapsed(), None)); }
+                    self.logs.push(LogEntry { time: ts, path: file.clone(), sql: format!("Exported {} rows to {}", rows, file), error: None });
+                }
+                BackendMessage::ExportFailed { job_id, message, cancelled } => {
+                    let mut file = String::new();
+                    if let Some(j) = self.exports.iter_mut().find(|j| j.id == job_id) { file = j.file.clone(); j.finished = Some((j.started.elapsed(), Some(message.clone()))); }
+                    // A cancel is the user's own doing, so it doesn't pop the console open
+                    if !cancelled { self.show_console = true; }
+                    self.logs.push(LogEntry { time: ts, path: file, sql: "Export".to_string(), error: Some(message) });
+                }
+                // The interrupt that frees a closed tab's connection surfaces as an error nobody needs to see; anything
+                // else a closed tab's job ran into is still logged
+                BackendMessage::Error { path: Some(ref p), ref message, .. } if !self.tabs.contains_key(p) && backend::is_closed_error(p, message) => {}
+                BackendMessage::Error { request, path, message, sql } => {
+                    self.logs.push(LogEntry { time: ts, path: path.clone().unwrap_or_else(|| "Global".to_string()), sql: sql.unwrap_or_else(|| "N/A".to_string()), error: Some(message.clone()) }); self.show_console = true;
+                    // A failed load that has since been superseded stays in the console but doesn't mark the tab
+                    if let (Some(t), Some(r)) = (path.as_ref().and_then(|p| self.tabs.get_mut(p)), request) {
+                        // The error doesn't say whether the page or the count failed; neither is still running
+                        t.clear_progress(r); t.page_answered(r); t.count_answered(r);
+                        // Pages share their count's request id, so a count that already answered can't have failed
+                        if r == t.count_request && t.counted < r { t.count_pending = false; t.count_failed = true; }
+                        if t.streaming == Some(r) { t.streaming = None; }
+                        // A block that failed isn't retried every frame; the footer offers a retry
+                        if t.scroll_blocks.loading.is_some_and(|(l, _)| l == r) { t.scroll_blocks.failed(); }
+                        t.scroll_blocks.was_dropped(r);
+                    }
+                    let current = |t: &&mut Tab| request.is_none_or(|r| r == t.page_request || r == t.count_request);
+                    if let Some(t) = path.and_then(|p| self.tabs.get_mut(&p)).filter(current) { t.last_error = Some(message); t.error_request = request.unwrap_or(0); t.state = t.state.next(LoadEvent::Failed); }
+                }
             }
             if self.logs.len() > 100 { self.logs.remove(0); }
         }
+        // Keep only the ten most recent finished transfers
+        while self.exports.iter().filter(|j| j.finished.is_some()).count() > 10 {
+            if let Some(i) = self.exports.iter().position(|j| j.finished.is_some()) { self.exports.remove(i); }
+        }
 
+        let focused_path = self.dock_state.find_active_focused().map(|(_, p)| p.clone());
+        if self.memory_polled.is_none_or(|t| t.elapsed() >= MEMORY_POLL) && !self.memory_polling.swap(true, std::sync::atomic::Ordering::Relaxed) {
+            self.memory_polled = Some(std::time::Instant::now());
+            let (b_c, tx_c, polling) = (self.backend.clone(), self.tx_to_ui.clone(), self.memory_polling.clone());
+            // Off the UI thread, since it waits on the main connection's lock
+            self.backend.spawn("", "memory", move || {
+                if let Ok((duckdb, spilled)) = b_c.memory_usage() { let _ = tx_c.send(BackendMessage::MemoryUsage { duckdb, spilled, process: backend::resident_memory() }); }
+                polling.store(false, std::sync::atomic::Ordering::Relaxed);
+            });
+        }
+        ctx.request_repaint_after(MEMORY_POLL);
+        // Names the focused file so several open windows can be told apart, flagging one whose load failed
+        let title = match focused_path.as_ref().and_then(|p| self.tabs.get(p)) {
+            Some(t) => format!("{}{} — ParquetGrip", if t.last_error.is_some() { "⚠ " } else { "" }, t.name),
+            None => "ParquetGrip".to_string(),
+        };
+        if title != self.window_title { ctx.send_viewport_cmd(egui::ViewportCommand::Title(title.clone())); self.window_title = title; }
+        // Ctrl+Shift+V or File > Open from Clipboard; a focused text field keeps plain pastes to itself
+        let pasted = ctx.input(|i| i.events.iter().find_map(|e| if let egui::Event::Paste(t) = e { Some(t.clone()) } else { None }));
+        if let Some(text) = pasted {
+            let shortcut = ctx.input(|i| i.modifiers.command && i.modifiers.shift) && !ctx.wants_keyboard_input();
+            if std::mem::take(&mut self.paste_to_open) || shortcut { self.open_pasted(&text); }
+        }
+        // egui-winit turns Ctrl+Shift+C into a Copy event rather than a key press, so match on that.
+        if ctx.input(|i| i.modifiers.command && i.modifiers.shift && i.events.iter().any(|e| matches!(e, egui::Event::Copy))) {
+            if let Some(tab) = focused_path.as_ref().and_then(|p| self.tabs.get(p)) { ParquetTabViewer::copy_page(ctx, self.tx_to_ui.clone(), self.backend.clone(), tab, ExportFormat::Tsv); }
+        }
+        if ctx.input_mut(|i| i.consume_shortcut(&egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::G))) {
+            if let Some(t) = focused_path.as_ref().and_then(|p| self.tabs.get_mut(p)) { t.column_jump = Some(String::new()); }
+        }
+        // PageUp/PageDown stay with the grid, which scrolls the page on screen with them, and a focused text
+        // field keeps Alt+arrows for moving by word
+        let (next, prev) = if ctx.wants_keyboard_input() { (false, false) } else { ctx.input_mut(|i| (i.consume_shortcut(&egui::KeyboardShortcut::new(egui::Modifiers::ALT, egui::Key::ArrowRight)), i.consume_shortcut(&egui::KeyboardShortcut::new(egui::Modifiers::ALT, egui::Key::ArrowLeft)))) };
+        if let Some(t) = focused_path.as_ref().and_then(|p| self.tabs.get_mut(p)).filter(|t| (next || prev) && !t.scroll_mode) {
+            if next && t.has_next_page() { ParquetTabViewer::goto_page(self.tx_to_ui.clone(), self.backend.clone(), t, t.current_page + 1, &self.settings); }
+            if prev && t.current_page > 1 { ParquetTabViewer::goto_page(self.tx_to_ui.clone(), self.backend.clone(), t, t.current_page - 1, &self.settings); }
+        }
         egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
             egui::MenuBar::new().ui(ui, |ui| {
-                ui.menu_button("File", |ui| { if ui.button("Open File...").clicked() { self.open_file_dialog(); ui.close(); } if ui.button("Quit").clicked() { std::process::exit(0); } });
+                ui.menu_button("File", |ui| {
+                    if ui.button("Open File...").clicked() { self.open_file_dialog(); ui.close(); }
+                    if ui.add(egui::Button::new("Open from Clipboard").shortcut_text("Ctrl+Shift+V")).clicked() {
+                        // The clipboard arrives as a Paste event on a later frame
+                        self.paste_to_open = true; ctx.send_viewport_cmd(egui::ViewportCommand::RequestPaste); ui.close();
+                    }
+                    if ui.add_enabled(focused_path.is_some(), egui::Button::new("Export...")).clicked() {
+                        if let Some(path) = focused_path.clone() { self.export_dialog = Some(ExportDialog::new(path)); }
+                        ui.close();
+                    }
+                    if ui.add_enabled(focused_path.as_ref().is_some_and(|p| self.tabs.get(p).is_some_and(|t| !t.fields.is_empty())), egui::Button::new("Export Schema...")).clicked() {
+                        if let Some(path) = focused_path.clone() { self.export_schema(&path); }
+                        ui.close();
+                    }
+                    if ui.add_enabled(focused_path.is_some(), egui::Button::new("Export Profile...")).clicked() {
+                        if let Some(path) = focused_path.clone() { self.export_profile(&path); }
+                        ui.close();
+                    }
+                    if ui.add_enabled(focused_path.is_some(), egui::Button::new("Export View...")).on_hover_text("Save this tab's filter, sort, columns and page to a file others can import").clicked() {
+                        if let Some(path) = focused_path.clone() { self.export_view(&path); }
+                        ui.close();
+                    }
+                    if ui.add_enabled(focused_path.is_some(), egui::Button::new("Import View...")).on_hover_text("Apply a view file to this tab").clicked() {
+                        if let Some(path) = focused_path.clone() { self.import_view(&path); }
+                        ui.close();
+                    }
+                    ui.separator();
+                    if ui.button("Settings...").clicked() { self.settings_dialog = Some(SettingsDialog::new(&self.settings)); ui.close(); }
+                    if ui.button("Quit").clicked() { ctx.send_viewport_cmd(egui::ViewportCommand::Close); }
+                });
+                ui.menu_button("View", |ui| {
+                    if ui.add_enabled(focused_path.is_some(), egui::Button::new("Jump to Column...").shortcut_text("Ctrl+G")).clicked() {
+                        if let Some(t) = focused_path.as_ref().and_then(|p| self.tabs.get_mut(p)) { t.column_jump = Some(String::new()); }
+                        ui.close();
+                    }
+                    ui.separator();
+                    ui.checkbox(&mut self.settings.grid_lines, "Grid lines").on_hover_text("Draw separators between columns");
+                    ui.checkbox(&mut self.settings.group_digits, "Thousands separators").on_hover_text("Show numbers as 1,234,567; set a column's display format to plain to keep IDs ungrouped");
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut self.settings.column_ordinals, "Column positions").on_hover_text("Show each column's position in the file before its name, e.g. [3] price");
+                        ui.add_enabled_ui(self.settings.column_ordinals, |ui| ui.checkbox(&mut self.settings.ordinals_from_zero, "from 0"));
+                    });
+                    ui.checkbox(&mut self.settings.show_row_groups, "Row-group map").on_hover_text("Mark Parquet row-group boundaries beside the grid (file order only)");
+                    ui.checkbox(&mut self.settings.live_filter, "Live filter").on_hover_text("Apply WHERE and ORDER BY once typing pauses, if they are valid");
+                    ui.add_enabled_ui(self.settings.live_filter, |ui| {
+                        ui.horizontal(|ui| { ui.label("Delay"); ui.add(egui::DragValue::new(&mut self.settings.live_filter_delay_ms).range(100..=5000).speed(10).suffix(" ms")); });
+                    });
+                    ui.separator();
+                    ui.label(egui::RichText::new("Page cache").weak());
+                    ui.horizontal(|ui| {
+                        ui.add(egui::DragValue::new(&mut self.settings.page_cache_pages).range(0..=50).suffix(" pages"));
+                        ui.add(egui::DragValue::new(&mut self.settings.page_cache_mb).range(16..=4096).speed(4).suffix(" MiB"));
+                    });
+                    ui.checkbox(&mut self.settings.page_cache_revalidate, "Refresh cached pages").on_hover_text("Show a cached page at once, then re-query it in the background");
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        ui.label("Query timeout");
+                        ui.add(egui::DragValue::new(&mut self.settings.query_timeout_secs).range(0..=3600).suffix(" s")).on_hover_text("Stop page loads and row counts that run longer than this; 0 means never");
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Slow query warning");
+                        ui.add(egui::DragValue::new(&mut self.settings.slow_query_secs).range(0..=3600).suffix(" s")).on_hover_text("Flag a page load as slow after this long; 0 never does");
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Cell fetch cap");
+                        ui.add(egui::DragValue::new(&mut self.settings.cell_fetch_chars).range(0..=1_000_000).speed(100).suffix(" chars")).on_hover_text("Pages bring back only the start of longer text; the cell popup, copies and exports still get it whole. 0 fetches everything");
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Row cap");
+                        ui.add(egui::DragValue::new(&mut self.settings.max_result_rows).range(1_000..=MAX_ROW_CAP).speed(1000).suffix(" rows")).on_hover_text("Most rows a page or clipboard copy loads into memory; results cut short say so. Exports written by DuckDB are not limited");
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Ask before copying a column");
+                        ui.add(egui::DragValue::new(&mut self.settings.copy_confirm_rows).range(1..=MAX_ROW_CAP).speed(1000).suffix(" rows")).on_hover_text("Copying every value of a column bigger than this asks first");
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Ask before copying all rows");
+                        ui.add(egui::DragValue::new(&mut self.settings.copy_all_rows_confirm).range(1..=MAX_ROW_CAP).speed(1000).suffix(" rows")).on_hover_text("Copying all rows of a result bigger than this asks first, and offers to copy fewer");
+                    });
+                    ui.separator();
+                    ui.label(egui::RichText::new("Row density").weak());
+                    for d in RowDensity::ALL { if ui.radio(self.settings.row_density == d, d.label()).clicked() { self.settings.row_density = d; ui.close(); } }
+                });
+                ui.menu_button("Help", |ui| {
+                    if ui.button("Performance").on_hover_text("Time page loads, counts and other backend work, for reporting slowness").clicked() {
+                        // Recording only starts once the panel has been opened
+                        self.backend.set_perf_recording(true); self.perf_panel = Some(PerfPanel { sort: 0, descending: true }); ui.close();
+                    }
+                    if ui.button("About ParquetGrip").clicked() { self.about = Some(diagnostics(&self.backend)); ui.close(); }
+                });
                 ui.separator(); if ui.selectable_label(self.show_console, "Console").clicked() { self.show_console = !self.show_console; }
+                if !self.exports.is_empty() {
+                    ui.separator();
+                    let active = self.exports.iter().filter(|j| j.finished.is_none()).count();
+                    if active > 0 { ui.add(egui::Spinner::new().size(12.0)); ctx.request_repaint_after(std::time::Duration::from_secs(1)); }
+                    let mut clear = false;
+                    ui.menu_button(if active > 0 { format!("Transfers ({})", active) } else { "Transfers".to_string() }, |ui| {
+                        ui.set_min_width(340.0);
+                        for job in self.exports.iter().rev() {
+                            let elapsed = job.finished.as_ref().map(|(d, _)| *d).unwrap_or_else(|| job.started.elapsed()).as_secs();
+                            let size = if job.bytes_written > 0 { format!(", {}", human_bytes(job.bytes_written as f64)) } else { String::new() };
+                            let size = match job.files { Some((done, total)) => format!("{}, {}/{} files", size, done, total), None => size };
+                            ui.horizontal(|ui| match &job.finished {
+                                None => {
+                                    ui.add(egui::Spinner::new().size(12.0));
+                                    ui.label(format!("{}: {} rows{} · {}:{:02}", job.file, job.rows_done, size, elapsed / 60, elapsed % 60));
+                                    if ui.small_button("Cancel").clicked() { job.cancel.cancel(); }
+                                }
+                                Some((_, None)) => { ui.label(format!("✔ {}: {} rows{} in {}:{:02}", job.file, job.rows_done, size, elapsed / 60, elapsed % 60)); }
+                                Some((_, Some(e))) => { ui.label(egui::RichText::new(format!("✖ {}: {}", job.file, e)).color(ui.visuals().error_fg_color)); }
+                            });
+                        }
+                        if active < self.exports.len() { ui.separator(); clear = ui.small_button("Clear finished").clicked(); }
+                    });
+                    if clear { self.exports.retain(|j| j.finished.is_none()); }
+                }
+                if let Some((duckdb, spilled, process)) = self.memory {
+                    let pages: usize = self.tabs.values().map(|t| t.data.memory_size() + t.page_cache.memory_size()).sum();
+                    let mut detail = format!("DuckDB: {}\nPages on screen and cached: {}", human_bytes(duckdb as f64), human_bytes(pages as f64));
+                    if spilled > 0 { detail += &format!("\nSpilled to disk: {}", human_bytes(spilled as f64)); }
+                    if let Some(p) = process { detail += &format!("\nWhole app: {}", human_bytes(p as f64)); }
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        ui.label(egui::RichText::new(format!("RAM {}", human_bytes(process.unwrap_or(duckdb) as f64))).weak()).on_hover_text(detail);
+                    });
+                }
             });
         });
 
+        let mut start_export = false;
+        if let Some(dialog) = &mut self.export_dialog {
+            let mut open = true;
+            let (name, total, counted, page_rows, selected, columns, hidden) = self.tabs.get(&dialog.path).map(|t| (t.name.clone(), t.total_rows, t.count_known(), t.data.len(), t.selected_rows.len(), t.schema.clone(), t.hidden_columns.len())).unwrap_or_default();
+            egui::Window::new("Export").open(&mut open).collapsible(false).resizable(false).show(ctx, |ui| {
+                ui.label(egui::RichText::new(&name).strong());
+                egui::ComboBox::from_label("Format").selected_text(dialog.format.label()).show_ui(ui, |ui| {
+                    for f in ExportFormat::ALL { ui.selectable_value(&mut dialog.format, f, f.label()); }
+                });
+                ui.radio_value(&mut dialog.scope, ExportScope::WholeResult, format!("Whole result ({} rows)", total));
+                ui.radio_value(&mut dialog.scope, ExportScope::CurrentPage, format!("This page only ({} rows)", page_rows));
+                // COPY can't pick scattered rows, so Parquet exports whole pages or results only
+                ui.add_enabled_ui(selected > 0 && !dialog.format.uses_copy(), |ui| { ui.radio_value(&mut dialog.scope, ExportScope::Selection, format!("Selection ({} rows)", selected)); });
+                if dialog.format.uses_copy() && dialog.scope == ExportScope::Selection { dialog.scope = ExportScope::WholeResult; }
+                if dialog.scope == ExportScope::WholeResult {
+                    ui.separator();
+                    if ui.radio(dialog.split == ExportSplit::Single, "Single file").clicked() { dialog.split = ExportSplit::Single; }
+                    ui.horizontal(|ui| {
+                        if ui.radio(matches!(dialog.split, ExportSplit::MaxRows(_)), "Split every").clicked() && !matches!(dialog.split, ExportSplit::MaxRows(_)) { dialog.split = ExportSplit::MaxRows(1_000_000); }
+                        if let ExportSplit::MaxRows(n) = &mut dialog.split { ui.add(egui::DragValue::new(n).range(1..=usize::MAX).speed(1000)); }
+                        ui.label("rows");
+                    });
+                    ui.horizontal(|ui| {
+                        if ui.radio(matches!(dialog.split, ExportSplit::ByColumn(_)), "One file per value of").clicked() && !matches!(dialog.split, ExportSplit::ByColumn(_)) { dialog.split = ExportSplit::ByColumn(columns.first().cloned().unwrap_or_default()); }
+                        if let ExportSplit::ByColumn(c) = &mut dialog.split {
+                            egui::ComboBox::from_id_salt("split_column").selected_text(c.as_str()).show_ui(ui, |ui| { for col in &columns { ui.selectable_value(c, col.clone(), col); } });
+                        }
+                    });
+                    if let ExportSplit::MaxRows(n) = dialog.split { ui.label(egui::RichText::new(format!("About {} files of part-0001.{} …, plus {}", total.div_ceil(n.max(1)).max(1), dialog.format.extension(), export::MANIFEST_FILE)).weak()); }
+                }
+                if dialog.format == ExportFormat::Xlsx && dialog.scope == ExportScope::WholeResult && (total > EXCEL_MAX_DATA_ROWS || !counted) {
+                    let warning = if counted { format!("{} rows exceed Excel's limit of 1,048,576 rows per sheet.", total) } else { "The row count isn't known, so the result may exceed Excel's limit of 1,048,576 rows per sheet.".to_string() };
+                    ui.label(egui::RichText::new(warning).color(ui.visuals().warn_fg_color));
+                    ui.horizontal(|ui| {
+                        ui.radio_value(&mut dialog.overflow, XlsxOverflow::FirstRows, "Only the first");
+                        ui.add(egui::TextEdit::singleline(&mut dialog.row_limit).desired_width(80.0)); ui.label("rows");
+                    });
+                    ui.radio_value(&mut dialog.overflow, XlsxOverflow::SplitSheets, "Split into multiple sheets");
+                }
+                if hidden > 0 { ui.checkbox(&mut dialog.visible_only, "Export visible columns only").on_hover_text(format!("Leave out the {} column(s) hidden in the grid", hidden)); }
+                if dialog.format == ExportFormat::Json { ui.checkbox(&mut self.settings.json_camel_case, "camelCase keys"); }
+                if dialog.format == ExportFormat::SqlInsert {
+                    ui.horizontal(|ui| { ui.label("Table name"); ui.add(egui::TextEdit::singleline(&mut dialog.table_name).desired_width(160.0)); });
+                    ui.horizontal(|ui| { ui.label("Max rows"); ui.add(egui::TextEdit::singleline(&mut dialog.sql_max_rows).desired_width(80.0)); });
+                    let cap = dialog.sql_max_rows.trim().parse::<usize>().unwrap_or(SQL_DEFAULT_MAX_ROWS);
+                    let rows = match dialog.scope { ExportScope::WholeResult => total, ExportScope::CurrentPage => page_rows, ExportScope::Selection => selected };
+                    if rows > cap { ui.label(egui::RichText::new(format!("INSERT scripts don't scale: only the first {} of {} rows will be written.", cap, rows)).color(ui.visuals().warn_fg_color)); }
+                }
+                ui.add_space(4.0);
+                if ui.button("Export...").clicked() { start_export = true; }
+            });
+            if !open { self.export_dialog = None; }
+        }
+        if start_export { if let Some(dialog) = self.export_dialog.take() { self.start_export(dialog); } }
+
+        // Quitting mid-export would leave a half-written file, so the close waits on the user
+        let exporting = self.exports.iter().any(|j| j.finished.is_none());
+        // Once the user chose to quit when done, further close requests are held off quietly until the exports end
+        if ctx.input(|i| i.viewport().close_requested()) && exporting {
+            ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+            if !self.quit_after_exports { self.quit_prompt = true; }
+        }
+        if self.quit_after_exports && !exporting { ctx.send_viewport_cmd(egui::ViewportCommand::Close); }
+        if self.quit_after_exports && exporting {
+            egui::Window::new("Quitting when exports finish").collapsible(false).resizable(false).anchor(egui::Align2::RIGHT_BOTTOM, [-8.0, -32.0]).show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(format!("{} export(s) still running.", self.exports.iter().filter(|j| j.finished.is_none()).count()));
+                    if ui.button("Don't quit").clicked() { self.quit_after_exports = false; }
+                });
+            });
+        }
+        if self.quit_prompt {
+            let running = self.exports.iter().filter(|j| j.finished.is_none()).count();
+            if running == 0 { self.quit_prompt = false; }
+            egui::Window::new("Exports still running").collapsible(false).resizable(false).anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0]).show(ctx, |ui| {
+                ui.label(format!("{} export(s) haven't finished. Quitting now would leave incomplete files.", running));
+                ui.horizontal(|ui| {
+                    if ui.button("Quit when done").clicked() { self.quit_after_exports = true; self.quit_prompt = false; }
+                    if ui.button("Cancel exports and quit").clicked() {
+                        for j in self.exports.iter().filter(|j| j.finished.is_none()) { j.cancel.cancel(); }
+                        self.quit_after_exports = true; self.quit_prompt = false;
+                    }
+                    if ui.button("Keep working").clicked() { self.quit_prompt = false; }
+                });
+            });
+        }
+
+        if let Some(info) = &self.about {
+            let mut open = true;
+            egui::Window::new("About ParquetGrip").open(&mut open).collapsible(false).resizable(false).show(ctx, |ui| {
+                ui.label("A high-performance Parquet file viewer.");
+                ui.add_space(4.0);
+                egui::Grid::new("about_grid").num_columns(2).striped(true).show(ui, |ui| {
+                    for (k, v) in info { ui.label(egui::RichText::new(*k).weak()); ui.label(egui::RichText::new(v).monospace()); ui.end_row(); }
+                });
+                ui.add_space(4.0);
+                if ui.button("Copy diagnostics").clicked() { ctx.copy_text(info.iter().map(|(k, v)| format!("{}: {}", k, v)).collect::<Vec<_>>().join("\n")); }
+            });
+            if !open { self.about = None; }
+        }
+
+        if let Some(panel) = &mut self.perf_panel {
+            let mut open = true;
+            ctx.request_repaint_after(std::time::Duration::from_secs(1));
+            egui::Window::new("Performance").open(&mut open).default_size([820.0, 360.0]).show(ctx, |ui| {
+                let mut records = self.backend.perf_records();
+                let name = |r: &PerfRecord| self.tabs.get(&r.path).map_or_else(|| source_files(&r.path).first().map_or("", |f| f.rsplit(['/', '\\']).next().unwrap_or(f)).to_string(), |t| t.name.clone());
+                match panel.sort {
+                    1 => records.sort_by(|a, b| a.kind.cmp(b.kind)),
+                    2 => records.sort_by_cached_key(|r| name(r)),
+                    3 => records.sort_by_key(|r| r.duration),
+                    4 => records.sort_by_key(|r| r.rows),
+                    5 => records.sort_by(|a, b| a.failure.cmp(&b.failure)),
+                    6 => records.sort_by(|a, b| a.sql.cmp(&b.sql)),
+                    _ => records.sort_by_key(|r| r.started),
+                }
+                if panel.descending { records.reverse(); }
+                let rows: Vec<Vec<String>> = records.iter().map(|r| perf_cells(r, &name(r))).collect();
+                ui.horizontal(|ui| {
+                    let mut recording = self.backend.perf_recording();
+                    if ui.checkbox(&mut recording, "Record").on_hover_text("Jobs are timed only while this is on").changed() { self.backend.set_perf_recording(recording); }
+                    ui.label(egui::RichText::new(format!("{} job(s)", rows.len())).weak());
+                    if ui.add_enabled(!rows.is_empty(), egui::Button::new("Copy as CSV")).on_hover_text("For attaching to a bug report").clicked() {
+                        let columns = PERF_COLUMNS.map(String::from);
+                        if let Ok(csv) = export::write_text_rows(Vec::new(), ExportFormat::Csv, &columns, &rows) { ctx.copy_text(String::from_utf8_lossy(&csv).into_owned()); }
+                    }
+                    if ui.button("Clear").clicked() { self.backend.clear_perf_records(); }
+                });
+                ui.separator();
+                let widths = [90.0, 60.0, 140.0, 90.0, 60.0, 140.0];
+                let mut table = egui_extras::TableBuilder::new(ui).striped(true).resizable(true).vscroll(true).auto_shrink([false, false]).cell_layout(egui::Layout::left_to_right(egui::Align::Center));
+                for w in widths { table = table.column(Column::initial(w).at_least(40.0).clip(true)); }
+                table.column(Column::remainder().at_least(120.0).clip(true)).header(20.0, |mut h| {
+                    for (i, title) in PERF_COLUMNS.iter().enumerate() { h.col(|ui| {
+                        let arrow = if panel.sort != i { "" } else if panel.descending { " ⏷" } else { " ⏶" };
+                        if ui.add(egui::Button::new(egui::RichText::new(format!("{}{}", title, arrow)).strong()).frame(false)).clicked() {
+                            if panel.sort == i { panel.descending = !panel.descending; } else { panel.sort = i; panel.descending = i == 0 || i == 3; }
+                        }
+                    }); }
+                }).body(|b| b.rows(18.0, rows.len(), |mut r| {
+                    let (cells, failed) = (&rows[r.index()], records[r.index()].failure.is_some());
+                    for (i, c) in cells.iter().enumerate() { r.col(|ui| {
+                        let text = if i == 5 && failed { egui::RichText::new(c).color(ui.visuals().error_fg_color) } else if i == 6 { egui::RichText::new(c).monospace() } else { egui::RichText::new(c) };
+                        ui.add(egui::Label::new(text).truncate()).on_hover_text(c);
+                    }); }
+                }));
+            });
+            if !open { self.perf_panel = None; }
+        }
+
+        if let Some((files, by_name)) = &mut self.merge_prompt {
+            let (mut open, mut choice) = (true, None);
+            egui::Window::new(format!("Open {} files", files.len())).open(&mut open).collapsible(false).resizable(false).show(ctx, |ui| {
+                for f in files.iter().take(8) { ui.label(egui::RichText::new(std::path::Path::new(f).file_name().and_then(|n| n.to_str()).unwrap_or(f)).monospace()); }
+                if files.len() > 8 { ui.label(egui::RichText::new(format!("and {} more", files.len() - 8)).weak()); }
+                ui.add_space(4.0);
+                ui.horizontal(|ui| {
+                    if ui.button("Separate tabs").clicked() { choice = Some(false); }
+                    if ui.button("Merge into one table").on_hover_text("Read all the files as one dataset, e.g. the shards of one export. They need the same columns unless matched by name.").clicked() { choice = Some(true); }
+                });
+                ui.checkbox(by_name, "Match columns by name").on_hover_text("For shards whose columns changed over time: columns are lined up by name, and a file missing one reads it as NULL");
+            });
+            if let Some(merge) = choice {
+                let (files, by_name) = self.merge_prompt.take().unwrap_or_default();
+                if merge { self.open_tab(merged_path(&files), by_name); } else { for path in files { self.open_path(path); } }
+            } else if !open { self.merge_prompt = None; }
+        }
+
+        if let Some((file, root)) = self.dataset_prompts.front() {
+            let (mut open, mut choice) = (true, None);
+            egui::Window::new("Open dataset?").open(&mut open).collapsible(false).resizable(false).show(ctx, |ui| {
+                ui.label(format!("{} is part of the dataset in", std::path::Path::new(file).file_name().and_then(|n| n.to_str()).unwrap_or(file)));
+                ui.label(egui::RichText::new(root.display().to_string()).monospace());
+                ui.label(egui::RichText::new("Opening the folder reads all of its parts as one table, with the partition folders as columns.").weak());
+                ui.add_space(4.0);
+                ui.horizontal(|ui| {
+                    if ui.button("Open dataset").on_hover_text("Read every Parquet file in the folder as one table; column=value folders become columns").clicked() { choice = Some(true); }
+                    if ui.button("Just this file").clicked() { choice = Some(false); }
+                });
+            });
+            if let Some(dataset) = choice {
+                let (file, root) = self.dataset_prompts.pop_front().unwrap_or_default();
+                // Other files from the same folder are covered once it is open as a dataset
+                if dataset { self.dataset_prompts.retain(|(_, r)| *r != root); }
+                self.open_tab(if dataset { dataset_path(&root) } else { file }, false);
+            } else if !open { self.dataset_prompts.pop_front(); }
+        }
+
+        if let Some(dialog) = &mut self.settings_dialog {
+            let mut open = true;
+            egui::Window::new("Settings").open(&mut open).collapsible(false).resizable(false).show(ctx, |ui| {
+                ui.label(egui::RichText::new("DuckDB").strong());
+                egui::Grid::new("engine_grid").num_columns(2).show(ui, |ui| {
+                    ui.label("Memory limit");
+                    ui.add(egui::TextEdit::singleline(&mut dialog.memory_limit).hint_text("auto").desired_width(120.0)).on_hover_text("e.g. 4GB or 512 MiB; empty lets DuckDB pick (80% of RAM)");
+                    ui.end_row();
+                    ui.label("Threads");
+                    ui.add(egui::DragValue::new(&mut dialog.threads).range(0..=1024).custom_formatter(|n, _| if n == 0.0 { "auto".to_string() } else { n.to_string() })).on_hover_text("0 means one per CPU core");
+                    ui.end_row();
+                    ui.label("Temp directory");
+                    ui.add(egui::TextEdit::singleline(&mut dialog.temp_directory).hint_text(backend::default_spill_directory().map_or_else(|| "default".to_string(), |d| d.display().to_string())).desired_width(240.0)).on_hover_text("Where sorts and joins too big for the memory limit spill to disk");
+                    ui.end_row();
+                    ui.label("Session SQL");
+                    ui.add(egui::TextEdit::multiline(&mut dialog.session_sql).code_editor().hint_text("SET TimeZone = 'UTC';").desired_rows(3).desired_width(240.0)).on_hover_text("SET, RESET and PRAGMA statements run on every connection, open ones included, after the settings above");
+                    ui.end_row();
+                    ui.label("Preload extensions");
+                    ui.add(egui::TextEdit::singleline(&mut dialog.extensions).hint_text("parquet, httpfs").desired_width(240.0)).on_hover_text("Installed and loaded in the background when the app starts, so the first file opened doesn't wait for them. Takes effect at the next launch");
+                    ui.end_row();
+                });
+                ui.add_space(8.0);
+                ui.label(egui::RichText::new("Files").strong());
+                egui::Grid::new("files_grid").num_columns(2).show(ui, |ui| {
+                    ui.label("Row count");
+                    ui.checkbox(&mut self.settings.skip_open_count, "Skip on open").on_hover_text("Open files without counting their rows, which can take a while for big CSV and JSON files; count them from the bottom bar");
+                    ui.end_row();
+                    ui.label("Open file types");
+                    ui.add(egui::TextEdit::singleline(&mut self.settings.open_file_types).hint_text("parquet, pqt, parq").desired_width(240.0)).on_hover_text("Listed by File > Open and accepted when dropped on the window; endings like parquet.gz work too");
+                    ui.end_row();
+                });
+                if let Some(e) = &dialog.error { ui.label(egui::RichText::new(e).color(ui.visuals().error_fg_color)); }
+                ui.horizontal(|ui| {
+                    if ui.button("Apply").clicked() {
+                        let draft = Settings { duckdb_memory_limit: dialog.memory_limit.trim().to_string(), duckdb_threads: dialog.threads, duckdb_temp_directory: dialog.temp_directory.trim().to_string(), duckdb_session_sql: dialog.session_sql.trim().to_string(), duckdb_extensions: dialog.extensions.trim().to_string(), ..self.settings.clone() };
+                        match self.backend.set_engine_config(draft.engine()) {
+                            Ok(()) => { self.settings = draft; dialog.error = None; }
+                            Err(e) => dialog.error = Some(e),
+                        }
+                    }
+                    if ui.button("Reset to auto").clicked() { dialog.memory_limit.clear(); dialog.threads = 0; dialog.temp_directory.clear(); }
+                });
+            });
+            if !open { self.settings_dialog = None; }
+        }
+
         if self.show_console {
             egui::TopBottomPanel::bottom("global_console").resizable(true).default_height(150.0).show(ctx, |ui| {
                 ui.vertical(|ui| {
-                    ui.horizontal(|ui| { ui.heading("Console"); ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| { if ui.button("Clear").clicked() { self.logs.clear(); } if ui.button("X").clicked() { self.show_console = false; } }); });
+                    ui.horizontal(|ui| { ui.heading("Console"); ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.button("Clear").clicked() { self.logs.clear(); }
+                        if ui.button("X").clicked() { self.show_console = false; }
+                    }); });
                     ui.separator(); egui::ScrollArea::vertical().stick_to_bottom(true).show(ui, |ui| {
                         for log in &self.logs {
                             ui.horizontal_top(|ui| {
@@ -334,10 +2599,480 @@ impl eframe::App for ParquetApp {
 
         egui::CentralPanel::default().show(ctx, |ui| {
             if self.tabs.is_empty() { ui.centered_and_justified(|ui| { ui.vertical_centered(|ui| { ui.heading("📊 ParquetGrip"); if ui.button("📁 Open File...").clicked() { self.open_file_dialog(); } }); }); }
-            else { let mut tv = ParquetTabViewer { tx: self.tx_to_ui.clone(), backend: self.backend.clone(), tabs: &mut self.tabs }; let mut style = Style::from_egui(ctx.style().as_ref()); style.tab_bar.height = 32.0; DockArea::new(&mut self.dock_state).style(style).show_inside(ui, &mut tv); }
+            else { let mut tv = ParquetTabViewer { tx: self.tx_to_ui.clone(), backend: self.backend.clone(), tabs: &mut self.tabs, settings: &mut self.settings, views: &mut self.views }; let mut style = Style::from_egui(ctx.style().as_ref()); style.tab_bar.height = 32.0; DockArea::new(&mut self.dock_state).style(style).show_inside(ui, &mut tv); }
         });
     }
-    fn save(&mut self, storage: &mut dyn eframe::Storage) { eframe::set_value(storage, eframe::APP_KEY, self); }
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        // Leave buffered stdin out of the session; its file is gone on the next start
+        let temp: Vec<(String, Tab)> = self.temp_files.iter().filter_map(|p| self.tabs.remove_entry(&*p.to_string_lossy())).collect();
+        eframe::set_value(storage, eframe::APP_KEY, self);
+        eframe::set_value(storage, FILE_FACTS_KEY, &self.backend.file_facts());
+        self.tabs.extend(temp);
+    }
+
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        self.backend.shutdown();
+        for p in &self.temp_files { let _ = std::fs::remove_file(p); }
+    }
+}
+
+/// Version and platform details for bug reports, as shown in Help > About.
+fn diagnostics(backend: &Backend) -> Vec<(&'static str, String)> {
+    let mut rows = vec![
+        ("ParquetGrip", env!("CARGO_PKG_VERSION").to_string()),
+        ("DuckDB", backend.duckdb_version().unwrap_or_else(|e| format!("unavailable ({})", e))),
+        ("duckdb crate", env!("PG_DUCKDB_CRATE_VERSION").to_string()),
+        ("egui", env!("PG_EGUI_VERSION").to_string()),
+        ("eframe", env!("PG_EFRAME_VERSION").to_string()),
+        ("egui_dock", env!("PG_EGUI_DOCK_VERSION").to_string()),
+        ("OS", format!("{} {} ({})", std::env::consts::OS, std::env::consts::ARCH, std::env::consts::FAMILY)),
+        ("Temp directory", backend.spill_directory().map_or_else(|| "DuckDB default".to_string(), |d| format!("{} ({} free)", d.display(), backend::free_space(&d).map_or_else(|| "unknown".to_string(), |b| human_bytes(b as f64))))),
+    ];
+    let problems = backend.warm_up_problems();
+    if problems.is_empty() { rows.push(("Startup", "no problems".to_string())); }
+    rows.extend(problems.into_iter().map(|p| ("Startup problem", p)));
+    rows
+}
+
+/// Whether a path is a URL DuckDB reads over the network rather than a local file.
+fn is_remote(path: &str) -> bool {
+    ["http://", "https://", "s3://", "gs://", "gcs://", "az://", "hf://"].iter().any(|p| path.starts_with(p))
+}
+
+#[cfg(target_os = "macos")]
+const REVEAL_LABEL: &str = "Reveal in Finder";
+#[cfg(windows)]
+const REVEAL_LABEL: &str = "Show in Explorer";
+#[cfg(not(any(target_os = "macos", windows)))]
+const REVEAL_LABEL: &str = "Open containing folder";
+
+/// Opens the system file manager at `file`, selecting it where the platform allows.
+fn reveal_in_file_manager(file: &std::path::Path) -> std::io::Result<()> {
+    let mut cmd = if cfg!(target_os = "macos") {
+        let mut c = std::process::Command::new("open"); c.arg("-R").arg(file); c
+    } else if cfg!(windows) {
+        let mut select = std::ffi::OsString::from("/select,"); select.push(file);
+        let mut c = std::process::Command::new("explorer"); c.arg(select); c
+    } else {
+        // There's no common way to select a file across Linux file managers, so this opens its folder
+        let mut c = std::process::Command::new("xdg-open"); c.arg(file.parent().unwrap_or(file)); c
+    };
+    // Reaped in the background so it doesn't linger as a zombie
+    cmd.spawn().map(|mut child| { std::thread::spawn(move || child.wait()); })
+}
+
+/// The path or URL in pasted text: one line, optionally quoted, naming an existing file or a remote location.
+fn pasted_path(text: &str) -> Option<String> {
+    let t = text.trim().trim_matches(|c| c == '"' || c == '\'');
+    if t.is_empty() || t.contains('\n') { return None; }
+    if is_remote(t) { return Some(t.to_string()); }
+    let local = t.strip_prefix("file://").unwrap_or(t);
+    std::path::Path::new(local).is_file().then(|| local.to_string())
+}
+
+/// Pages needed for `total_rows` at `page_size` rows each; an empty result still has its one empty page.
+fn page_count(total_rows: usize, page_size: usize) -> usize {
+    total_rows.div_ceil(page_size.max(1)).max(1)
+}
+
+/// `page` moved into the pages `total_rows` fills, for when a filter or new page size leaves fewer of them.
+fn clamp_page(page: usize, total_rows: usize, page_size: usize) -> usize {
+    page.clamp(1, page_count(total_rows, page_size))
+}
+
+/// The result rows (0-based, end exclusive) that `page` holds when it loaded `len` rows.
+fn page_rows(page: usize, page_size: usize, len: usize) -> std::ops::Range<usize> {
+    let first = (page.max(1) - 1) * page_size;
+    first..first + len
+}
+
+/// The footer's readout for result rows `shown` (0-based, end exclusive) out of `total`.
+fn rows_shown(shown: std::ops::Range<usize>, total: &str) -> String {
+    if shown.is_empty() { format!("Showing 0 of {}", total) } else { format!("Showing {}-{} of {}", shown.start + 1, shown.end, total) }
+}
+
+/// A lowercase identifier from a file name, e.g. `Sales 2024.parquet` -> `sales_2024`.
+fn sql_table_name(stem: &str) -> String {
+    let name: String = stem.chars().map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' }).collect();
+    let name = name.trim_matches('_').to_string();
+    if name.is_empty() { "data".to_string() } else if name.starts_with(|c: char| c.is_ascii_digit()) { format!("t_{}", name) } else { name }
+}
+
+/// A column name as the grid headers show it, with its schema position in front when positions are on.
+fn column_header(name: &str, index: usize, ordinal_base: Option<usize>) -> String {
+    match ordinal_base { Some(base) => format!("[{}] {}", index + base, name), None => name.to_string() }
+}
+
+/// What a file saved from a tab is named after: its dataset folder, or its first file without the extension.
+fn file_stem(path: &str) -> Option<&str> {
+    let first = source_files(path)[0];
+    match dataset_dir(first) {
+        Some(dir) => dir.file_name()?.to_str(),
+        None => std::path::Path::new(first).file_stem()?.to_str(),
+    }
+}
+
+/// What of the page goes into an HTML table: the selected rows on this page and the selected columns, if any.
+struct HtmlView {
+    cols: Vec<usize>,
+    /// Rows kept, counted from the top of the page; `None` keeps them all.
+    rows: Option<BTreeSet<usize>>,
+    numeric: Vec<bool>,
+    max_chars: usize,
+}
+
+impl HtmlView {
+    fn of(tab: &Tab, max_chars: usize) -> Self {
+        let start = (tab.current_page - 1) * tab.page_size;
+        let cols: Vec<usize> = if tab.selected_cols.is_empty() { (0..tab.columns.len()).collect() } else { tab.selected_cols.iter().copied().collect() };
+        let rows = (!tab.selected_rows.is_empty()).then(|| tab.selected_rows.iter().filter_map(|r| r.checked_sub(start)).collect());
+        let numeric = cols.iter().map(|&c| tab.fields.get(c).is_some_and(|f| export::is_numeric_type(&f.data_type))).collect();
+        Self { cols, rows, numeric, max_chars }
+    }
+
+    fn render(&self, columns: &[String], data: &PageData) -> String {
+        let rows: Vec<Vec<String>> = data.iter().enumerate()
+            .filter(|(i, _)| self.rows.as_ref().is_none_or(|rows| rows.contains(i)))
+            .map(|(_, r)| self.cols.iter().map(|&c| r.get(c).map_or_else(String::new, |v| v.to_string())).collect()).collect();
+        let header: Vec<String> = self.cols.iter().filter_map(|&c| columns.get(c).cloned()).collect();
+        export::html_table(&header, &self.numeric, &rows, self.max_chars)
+    }
+}
+
+/// `column IN (...)` for the given cell values, quoting text and leaving numbers bare; a NULL among them adds `OR column IS NULL`.
+fn in_list_filter(column: &str, values: &[String], numeric: bool) -> String {
+    let column = quote_ident(column);
+    let mut distinct: Vec<&str> = Vec::new();
+    for v in values.iter().map(|v| v.as_str()).filter(|v| *v != export::NULL_DISPLAY) { if !distinct.contains(&v) { distinct.push(v); } }
+    let has_null = values.iter().any(|v| v == export::NULL_DISPLAY);
+    if distinct.is_empty() { return format!("{} IS NULL", column); }
+    let list = format!("{} IN {}", column, export::column_values_text(distinct.into_iter().map(Some), numeric, true, true));
+    if has_null { format!("({} OR {} IS NULL)", list, column) } else { list }
+}
+
+/// The loaded page with a header row, serialized exactly like a file export. Only the `shown` columns are
+/// written, in that order.
+fn page_text(columns: &[String], data: &PageData, shown: &[String], format: ExportFormat) -> String {
+    let cols: Vec<usize> = shown.iter().filter_map(|n| columns.iter().position(|c| c == n)).collect();
+    let header: Vec<String> = cols.iter().map(|&c| columns[c].clone()).collect();
+    let rows = data.iter().map(|r| cols.iter().map(|&c| r.get(c).cloned().unwrap_or_else(|| Arc::from(""))).collect::<Vec<Arc<str>>>());
+    export::write_text_rows(Vec::new(), format, &header, rows).map(|b| String::from_utf8_lossy(&b).into_owned()).unwrap_or_default()
+}
+
+/// The rows covering a selection, empty for an empty one, plus the rows to keep (relative to its start) when it has gaps.
+fn selection_range(sel: &BTreeSet<usize>) -> (std::ops::Range<usize>, Option<BTreeSet<usize>>) {
+    let (Some(&first), Some(&last)) = (sel.first(), sel.last()) else { return (0..0, None) };
+    let filter = (last - first + 1 != sel.len()).then(|| sel.iter().map(|g| g - first).collect());
+    (first..last + 1, filter)
+}
+
+/// Click selects one row, Ctrl/Cmd-click toggles, Shift-click extends from the last clicked row.
+fn select_row(selected: &mut BTreeSet<usize>, anchor: &mut Option<usize>, row: usize, mods: egui::Modifiers) {
+    match *anchor {
+        Some(a) if mods.shift => {
+            if !mods.command { selected.clear(); }
+            selected.extend(a.min(row)..=a.max(row));
+        }
+        _ if mods.command => {
+            if !selected.remove(&row) { selected.insert(row); }
+            *anchor = Some(row);
+        }
+        _ => {
+            let only_this = selected.len() == 1 && selected.contains(&row);
+            selected.clear();
+            if !only_this { selected.insert(row); }
+            *anchor = Some(row);
+        }
+    }
+}
+
+/// CJK ideographs, Hangul, fullwidth forms and emoji render roughly twice as wide as Latin glyphs.
+fn is_wide_char(c: char) -> bool {
+    matches!(c as u32,
+        0x1100..=0x115F | 0x2E80..=0x303E | 0x3041..=0x33FF | 0x3400..=0x4DBF | 0x4E00..=0x9FFF |
+        0xA000..=0xA4CF | 0xAC00..=0xD7A3 | 0xF900..=0xFAFF | 0xFE30..=0xFE4F | 0xFF00..=0xFF60 |
+        0xFFE0..=0xFFE6 | 0x1F300..=0x1F64F | 0x1F900..=0x1F9FF | 0x20000..=0x3FFFD)
+}
+
+/// The column a DuckDB binder error says doesn't exist, as the query spelled it.
+fn unknown_column(error: &str) -> Option<&str> {
+    let (_, rest) = error.split_once("column \"")?;
+    let (name, rest) = rest.split_once('"')?;
+    (!name.is_empty() && rest.trim_start().starts_with("not found")).then_some(name)
+}
+
+/// The first column a sort names that isn't in `columns`. Only terms that are a bare or quoted name with
+/// ASC/DESC and NULLS FIRST/LAST are checked; anything else, such as an expression or a position, is left
+/// for DuckDB to judge. `rowid` counts as a column when the tab reads a table, which has one.
+fn unknown_sort_column(sort: &str, columns: &[String], has_rowid: bool) -> Option<String> {
+    // Keywords that read like a bare name but aren't columns
+    const NILADIC: [&str; 8] = ["current_date", "current_time", "current_timestamp", "localtime", "localtimestamp", "true", "false", "null"];
+    if columns.is_empty() || sort.trim().is_empty() { return None; }
+    backend::split_top_level(sort).into_iter().find_map(|term| {
+        let term = term.trim();
+        let (name, quoted, rest) = match term.strip_prefix('"') {
+            Some(q) => { let end = q.find('"')?; (&q[..end], true, &q[end + 1..]) }
+            None => { let end = term.find(char::is_whitespace).unwrap_or(term.len()); (&term[..end], false, &term[end..]) }
+        };
+        // A doubled quote is an escaped one, which this doesn't take apart
+        if quoted && rest.starts_with('"') { return None; }
+        if !quoted && (!name.starts_with(|c: char| c.is_alphabetic() || c == '_') || !name.chars().all(|c| c.is_alphanumeric() || c == '_') || NILADIC.contains(&name.to_lowercase().as_str())) { return None; }
+        // `ORDER BY ALL` sorts by every column in turn
+        if !quoted && name.eq_ignore_ascii_case("all") { return None; }
+        if has_rowid && name.eq_ignore_ascii_case("rowid") { return None; }
+        let words: Vec<String> = rest.split_whitespace().map(str::to_uppercase).collect();
+        let words: Vec<&str> = words.iter().map(String::as_str).collect();
+        let tail = match words.as_slice() { ["ASC" | "DESC", tail @ ..] => tail, tail => tail };
+        if !matches!(tail, [] | ["NULLS", "FIRST" | "LAST"]) { return None; }
+        // DuckDB matches identifiers without regard to case, quoted or not
+        (!columns.iter().any(|c| c.eq_ignore_ascii_case(name))).then(|| name.to_string())
+    })
+}
+
+/// Where `word` appears in `text` as a whole identifier, ignoring ASCII case; a quoted one includes its quotes.
+fn find_identifier(text: &str, word: &str) -> Option<std::ops::Range<usize>> {
+    if word.is_empty() { return None; }
+    let (lower, word) = (text.to_ascii_lowercase(), word.to_ascii_lowercase());
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+    let found = lower.match_indices(&word).map(|(i, _)| i..i + word.len())
+        .find(|r| !lower[..r.start].chars().next_back().is_some_and(is_ident) && !lower[r.end..].chars().next().is_some_and(is_ident))?;
+    Some(if text[..found.start].ends_with('"') && text[found.end..].starts_with('"') { found.start - 1..found.end + 1 } else { found })
+}
+
+/// The name in `names` closest to `word`, if it's near enough to be what a typo meant.
+fn closest_name<'n>(word: &str, names: &'n [String]) -> Option<&'n str> {
+    let word: Vec<char> = word.to_lowercase().chars().collect();
+    names.iter().map(|n| (edit_distance(&word, &n.to_lowercase().chars().collect::<Vec<_>>()), n))
+        .filter(|(d, _)| *d <= (word.len() / 3).max(2)).min_by_key(|(d, _)| *d).map(|(_, n)| n.as_str())
+}
+
+/// Levenshtein distance: single-character inserts, deletes and substitutions to turn `a` into `b`.
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut diagonal = row[0]; row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if ca == cb { diagonal } else { 1 + diagonal.min(above).min(row[j]) };
+            diagonal = above;
+        }
+    }
+    row[b.len()]
+}
+
+/// Lays out a query field with the identifier `mark` names underlined as the part DuckDB rejected.
+fn marked_layout(ui: &egui::Ui, text: &str, mark: Option<&str>, wrap_width: f32) -> Arc<egui::Galley> {
+    let plain = egui::TextFormat::simple(egui::TextStyle::Body.resolve(ui.style()), ui.visuals().text_color());
+    let mark = mark.and_then(|m| find_identifier(text, m));
+    let error = ui.visuals().error_fg_color;
+    let mut job = egui::text::LayoutJob::default();
+    for (range, token) in sql_tokens(text) {
+        let format = egui::TextFormat { color: token.color(ui.visuals()), ..plain.clone() };
+        // A token is cut where the mark starts and ends, so only the marked part is underlined
+        let mut cuts = vec![range.start, range.end];
+        if let Some(m) = &mark { cuts.extend([m.start, m.end].into_iter().filter(|c| range.contains(c))); }
+        cuts.sort_unstable(); cuts.dedup();
+        for w in cuts.windows(2) {
+            let marked = mark.as_ref().is_some_and(|m| m.start <= w[0] && w[1] <= m.end);
+            let format = if marked { egui::TextFormat { color: error, underline: egui::Stroke::new(1.5, error), ..format.clone() } } else { format.clone() };
+            job.append(&text[w[0]..w[1]], 0.0, format);
+        }
+    }
+    job.wrap.max_width = wrap_width;
+    ui.fonts_mut(|f| f.layout_job(job))
+}
+
+/// What a piece of a filter or sort is, for colouring it.
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum SqlToken { Keyword, String, Number, Operator, Other }
+
+impl SqlToken {
+    fn color(self, visuals: &egui::Visuals) -> egui::Color32 {
+        let dark = visuals.dark_mode;
+        match self {
+            SqlToken::Keyword => if dark { egui::Color32::from_rgb(86, 156, 214) } else { egui::Color32::from_rgb(0, 80, 200) },
+            SqlToken::String => if dark { egui::Color32::from_rgb(206, 145, 120) } else { egui::Color32::from_rgb(163, 21, 21) },
+            SqlToken::Number => if dark { egui::Color32::from_rgb(181, 206, 168) } else { egui::Color32::from_rgb(9, 134, 88) },
+            SqlToken::Operator => visuals.strong_text_color(),
+            SqlToken::Other => visuals.text_color(),
+        }
+    }
+}
+
+/// Splits SQL into coloured pieces covering all of `text`. It only looks at one token at a time, which is
+/// plenty for a WHERE or ORDER BY clause; an unclosed string runs to the end.
+fn sql_tokens(text: &str) -> Vec<(std::ops::Range<usize>, SqlToken)> {
+    const KEYWORDS: [&str; 34] = ["AND", "OR", "NOT", "IN", "IS", "NULL", "LIKE", "ILIKE", "GLOB", "SIMILAR", "BETWEEN", "CASE", "WHEN", "THEN", "ELSE", "END", "AS", "CAST", "TRY_CAST",
+        "ASC", "DESC", "NULLS", "FIRST", "LAST", "TRUE", "FALSE", "EXISTS", "SELECT", "FROM", "WHERE", "DISTINCT", "INTERVAL", "COLLATE", "ESCAPE"];
+    let bytes = text.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < text.len() {
+        let c = text[i..].chars().next().unwrap_or(' ');
+        let start = i;
+        let kind = match c {
+            '\'' | '"' => {
+                // A doubled quote inside is an escaped one
+                i += 1;
+                while i < text.len() {
+                    if bytes[i] == c as u8 { if bytes.get(i + 1) == Some(&(c as u8)) { i += 2; continue; } i += 1; break; }
+                    i += text[i..].chars().next().map_or(1, char::len_utf8);
+                }
+                if c == '\'' { SqlToken::String } else { SqlToken::Other }
+            }
+            '0'..='9' => {
+                while i < text.len() && (bytes[i].is_ascii_digit() || bytes[i] == b'.' || bytes[i] == b'_') { i += 1; }
+                if matches!(bytes.get(i), Some(b'e' | b'E')) && bytes.get(i + 1).is_some_and(u8::is_ascii_digit) { i += 2; while i < text.len() && bytes[i].is_ascii_digit() { i += 1; } }
+                SqlToken::Number
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                while i < text.len() { let ch = text[i..].chars().next().unwrap_or(' '); if !(ch.is_alphanumeric() || ch == '_') { break; } i += ch.len_utf8(); }
+                if KEYWORDS.contains(&text[start..i].to_uppercase().as_str()) { SqlToken::Keyword } else { SqlToken::Other }
+            }
+            '=' | '<' | '>' | '!' | '+' | '-' | '*' | '/' | '%' | '|' | '(' | ')' | ',' | ':' | '~' | '^' | '&' => { i += 1; SqlToken::Operator }
+            c => { i += c.len_utf8(); SqlToken::Other }
+        };
+        tokens.push((start..i, kind));
+    }
+    tokens
+}
+
+/// Hebrew, Arabic, Syriac, Thaana, N'Ko and their presentation forms are written right to left.
+fn is_rtl_char(c: char) -> bool {
+    matches!(c as u32, 0x0590..=0x08FF | 0xFB1D..=0xFDFF | 0xFE70..=0xFEFC | 0x10800..=0x10FFF | 0x1E800..=0x1EFFF)
+}
+
+/// Most of the letters in `text` come from right-to-left scripts.
+fn is_rtl(text: &str) -> bool {
+    let (rtl, letters) = text.chars().filter(|c| c.is_alphabetic()).fold((0, 0), |(r, n), c| (r + is_rtl_char(c) as usize, n + 1));
+    rtl * 2 > letters
+}
+
+/// Hebrew points and Arabic vowel marks, which sit on the letter before them.
+fn is_combining_mark(c: char) -> bool {
+    matches!(c as u32, 0x0300..=0x036F | 0x0591..=0x05BD | 0x05BF | 0x05C1..=0x05C2 | 0x05C4..=0x05C5 | 0x05C7 | 0x0610..=0x061A | 0x064B..=0x065F | 0x0670 | 0x06D6..=0x06DC | 0x06DF..=0x06E4 | 0x06E7..=0x06E8 | 0x06EA..=0x06ED)
+}
+
+/// The bidi classes `rtl_visual` tells apart, a reduced set of Unicode's.
+#[derive(Clone, Copy, PartialEq)]
+enum Bidi { Rtl, Ltr, Number, Separator, Terminator, Neutral }
+
+/// `text` in the order its glyphs appear on screen when read right to left, since egui lays every string out
+/// left to right. This is the Unicode bidi algorithm cut down to one right-to-left paragraph: Latin words and
+/// numbers (with their separators, signs and units) keep their order, everything else is reversed and its
+/// brackets mirrored, and combining marks stay on their letter.
+fn rtl_visual(text: &str) -> String {
+    let mut clusters: Vec<String> = Vec::new();
+    for c in text.chars() {
+        match clusters.last_mut() {
+            Some(last) if is_combining_mark(c) => last.push(c),
+            _ => clusters.push(c.to_string()),
+        }
+    }
+    let mut class: Vec<Bidi> = clusters.iter().map(|s| match s.chars().next().unwrap_or(' ') {
+        // Before the script check, so Arabic-Indic digits read as numbers
+        c if c.is_numeric() => Bidi::Number,
+        c if is_rtl_char(c) => Bidi::Rtl,
+        c if c.is_alphabetic() => Bidi::Ltr,
+        '.' | ',' | ':' | '/' => Bidi::Separator,
+        '%' | '#' | '$' | '\u{b0}' | '\u{a2}' | '\u{a3}' | '\u{a5}' | '\u{20aa}' | '\u{20ac}' => Bidi::Terminator,
+        _ => Bidi::Neutral,
+    }).collect();
+    // A separator between two digits belongs to the number, e.g. `1,250.50` or `12:30`
+    for i in 1..class.len().saturating_sub(1) {
+        if class[i] == Bidi::Separator && class[i - 1] == Bidi::Number && class[i + 1] == Bidi::Number { class[i] = Bidi::Number; }
+    }
+    // So do units and currency signs touching it, e.g. `10%`
+    let mut i = 0;
+    while i < class.len() {
+        let end = (i..class.len()).find(|&j| class[j] != Bidi::Terminator).unwrap_or(class.len());
+        if end > i && ((i > 0 && class[i - 1] == Bidi::Number) || class.get(end) == Some(&Bidi::Number)) { class[i..end].fill(Bidi::Number); }
+        i = end.max(i + 1);
+    }
+    // Numbers after a Latin word read with it; only a neutral between two left-to-right runs joins them
+    let mut strong = Bidi::Rtl;
+    for c in class.iter_mut() {
+        match *c {
+            Bidi::Separator | Bidi::Terminator => *c = Bidi::Neutral,
+            Bidi::Number if strong == Bidi::Ltr => *c = Bidi::Ltr,
+            Bidi::Rtl | Bidi::Ltr => strong = *c,
+            _ => {}
+        }
+    }
+    let ltr = |c: Option<&Bidi>| c == Some(&Bidi::Ltr);
+    let mut i = 0;
+    while i < class.len() {
+        let end = (i..class.len()).find(|&j| class[j] != Bidi::Neutral).unwrap_or(class.len());
+        let side = if i > 0 && ltr(class.get(i - 1)) && ltr(class.get(end)) { Bidi::Ltr } else { Bidi::Rtl };
+        class[i..end].fill(side);
+        i = end.max(i + 1);
+    }
+    let mut runs: Vec<String> = Vec::new();
+    for (i, cluster) in clusters.into_iter().enumerate() {
+        if class[i] == Bidi::Rtl {
+            let mirrored: String = cluster.chars().map(|c| match c { '(' => ')', ')' => '(', '[' => ']', ']' => '[', '{' => '}', '}' => '{', '<' => '>', '>' => '<', c => c }).collect();
+            runs.push(mirrored);
+        } else if i > 0 && class[i - 1] != Bidi::Rtl {
+            runs.last_mut().expect("a run before a left-to-right cluster").push_str(&cluster);
+        } else {
+            runs.push(cluster);
+        }
+    }
+    runs.into_iter().rev().collect()
+}
+
+/// Ids for grid loads, shared by every tab so a reopened tab never reuses one still in flight.
+fn next_request() -> RequestId {
+    static NEXT: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(1);
+    NEXT.fetch_add(1, std::sync::atomic::Ordering::Relaxed)
+}
+
+/// Indices into `columns` in display order: the saved names first, then any column they don't mention.
+fn display_order(columns: &[String], saved: &[String]) -> Vec<usize> {
+    let mut order: Vec<usize> = saved.iter().filter_map(|n| columns.iter().position(|c| c == n)).collect();
+    let rest: Vec<usize> = (0..columns.len()).filter(|i| !order.contains(i)).collect();
+    order.extend(rest);
+    order
+}
+
+/// Best match for a typed column name: exact, then case-insensitive, then prefix, then substring.
+fn find_column(schema: &[String], query: &str) -> Option<usize> {
+    let q = query.trim().to_lowercase();
+    if q.is_empty() { return None; }
+    schema.iter().position(|c| c == query.trim())
+        .or_else(|| schema.iter().position(|c| c.to_lowercase() == q))
+        .or_else(|| schema.iter().position(|c| c.to_lowercase().starts_with(&q)))
+        .or_else(|| schema.iter().position(|c| c.to_lowercase().contains(&q)))
+}
+
+/// A channel whose receiver is woken by `wake` after each message is ready on it. The wake comes only after the
+/// message is passed on, so a frame it starts always finds the message.
+fn waking_channel<T: Send + 'static>(wake: impl Fn() + Send + 'static) -> (mpsc::Sender<T>, mpsc::Receiver<T>) {
+    let (tx, relayed) = mpsc::channel();
+    let (relay, rx) = mpsc::channel();
+    std::thread::spawn(move || for msg in relayed {
+        if relay.send(msg).is_err() { break; }
+        wake();
+    });
+    (tx, rx)
+}
+
+/// Columns overlapping the horizontal span `min..max`, given each column's left edge and width in the scrolled content.
+fn visible_columns(lefts: &[f32], widths: &[f32], min: f32, max: f32) -> std::ops::Range<usize> {
+    let first = lefts.iter().zip(widths).position(|(l, w)| l + w >= min).unwrap_or(lefts.len());
+    first..lefts.iter().position(|l| *l > max).unwrap_or(lefts.len()).max(first)
+}
+
+/// Default column widths, widened for columns whose sampled cells contain wide characters.
+fn initial_col_widths(ui: &egui::Ui, schema: &[String], data: &PageData) -> Vec<f32> {
+    const SAMPLE_ROWS: usize = 100;
+    let font_id = egui::TextStyle::Body.resolve(ui.style());
+    (0..schema.len()).map(|i| {
+        let cells: Vec<&Arc<str>> = data.iter().take(SAMPLE_ROWS).filter_map(|r| r.get(i)).collect();
+        if !cells.iter().any(|c| c.chars().any(is_wide_char)) { return 150.0; }
+        let widest = cells.iter().map(|c| ui.fonts_mut(|f| f.layout_no_wrap(c.to_string(), font_id.clone(), egui::Color32::WHITE).size().x)).fold(0.0, f32::max);
+        (widest + 16.0).clamp(150.0, 400.0)
+    }).collect()
 }
 
 fn setup_fonts(ctx: &egui::Context) {
@@ -389,3 +3124,170 @@ fn setup_fonts(ctx: &egui::Context) {
 
     ctx.set_fonts(fonts);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn visible_columns_of_a_wide_file() {
+        let widths: Vec<f32> = (0..3000).map(|i| 100.0 + (i % 7) as f32 * 10.0).collect();
+        let lefts: Vec<f32> = widths.iter().scan(40.0, |x, w| { let left = *x; *x += w; Some(left) }).collect();
+        assert_eq!(visible_columns(&lefts, &widths, 0.0, 800.0), 0..7);
+        let mid = visible_columns(&lefts, &widths, lefts[1500] + 1.0, lefts[1500] + 800.0);
+        assert_eq!(mid.start, 1500);
+        assert!(mid.len() <= 9);
+        let end = lefts[2999] + widths[2999];
+        assert_eq!(visible_columns(&lefts, &widths, end - 50.0, end).start, 2999);
+        assert_eq!(visible_columns(&lefts, &widths, end + 10.0, end + 800.0), 3000..3000);
+    }
+
+    #[test]
+    fn rtl_cells_keep_numbers_and_latin_words_in_order() {
+        // Hebrew "price: 1,250.50 ₪", shown right to left as "₪ 1,250.50 :ריחמ" when read on screen left to right
+        let price = "\u{5de}\u{5d7}\u{5d9}\u{5e8}: 1,250.50 \u{20aa}";
+        assert!(is_rtl(price));
+        assert_eq!(rtl_visual(price), "\u{20aa} 1,250.50 :\u{5e8}\u{5d9}\u{5d7}\u{5de}");
+        // "שלום world 123" and a Latin word ahead of the Hebrew
+        assert_eq!(rtl_visual("\u{5e9}\u{5dc}\u{5d5}\u{5dd} world 123"), "world 123 \u{5dd}\u{5d5}\u{5dc}\u{5e9}");
+        assert_eq!(rtl_visual("abc (\u{5e9}\u{5dc}\u{5d5}\u{5dd})"), "(\u{5dd}\u{5d5}\u{5dc}\u{5e9}) abc");
+        // Units stay on their number, Arabic-Indic digits aren't reversed, and vowel marks stay on their letter
+        assert_eq!(rtl_visual("\u{5e9}\u{5dc}\u{5d5}\u{5dd} 10%"), "10% \u{5dd}\u{5d5}\u{5dc}\u{5e9}");
+        assert_eq!(rtl_visual("\u{627}\u{644}\u{639}\u{62f}\u{62f} \u{661}\u{662}\u{663}"), "\u{661}\u{662}\u{663} \u{62f}\u{62f}\u{639}\u{644}\u{627}");
+        assert_eq!(rtl_visual("\u{628}\u{64e}\u{627}\u{628}"), "\u{628}\u{627}\u{628}\u{64e}");
+        assert!(!is_rtl("hello \u{5e9}"));
+    }
+
+    #[test]
+    fn waking_channel_wakes_after_each_message() {
+        let (woke, wakes) = mpsc::channel();
+        let (tx, rx) = waking_channel::<usize>(move || { let _ = woke.send(()); });
+        for i in 0..100 { tx.send(i).unwrap(); }
+        for i in 0..100 {
+            wakes.recv_timeout(std::time::Duration::from_secs(5)).expect("no wake for a message");
+            // A wake never runs ahead of its message
+            assert_eq!(rx.try_recv(), Ok(i));
+        }
+        drop(tx);
+        assert!(wakes.recv_timeout(std::time::Duration::from_secs(5)).is_err(), "a wake without a message");
+    }
+
+    #[test]
+    fn page_text_keeps_only_the_shown_columns_in_their_order() {
+        let batch = duckdb::Connection::open_in_memory().unwrap().prepare("SELECT 1 AS a, 'x' AS b, 2 AS c").unwrap().query_arrow([]).unwrap().next().unwrap();
+        let columns = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        let text = page_text(&columns, &PageData::new(Arc::new(batch)), &["c".to_string(), "a".to_string()], ExportFormat::Csv);
+        assert_eq!(text.lines().collect::<Vec<_>>(), ["c,a", "2,1"]);
+    }
+
+    #[test]
+    fn selection_range_covers_the_selected_rows() {
+        assert_eq!(selection_range(&BTreeSet::new()), (0..0, None));
+        assert_eq!(selection_range(&BTreeSet::from([7])), (7..8, None));
+        assert_eq!(selection_range(&BTreeSet::from([3, 4, 5])), (3..6, None));
+        assert_eq!(selection_range(&BTreeSet::from([3, 5, 9])), (3..10, Some(BTreeSet::from([0, 2, 6]))));
+    }
+
+    #[test]
+    fn unknown_sort_column_ignores_case_of_quoted_names() {
+        let columns = vec!["Price".to_string(), "id".to_string()];
+        assert_eq!(unknown_sort_column("\"price\" DESC, ID", &columns, false), None);
+        assert_eq!(unknown_sort_column("\"Cost\" DESC", &columns, false), Some("Cost".to_string()));
+        assert_eq!(unknown_sort_column("ALL", &columns, false), None);
+        assert_eq!(unknown_sort_column("all DESC NULLS LAST", &columns, false), None);
+        // Quoted, it names a column like any other
+        assert_eq!(unknown_sort_column("\"all\"", &columns, false), Some("all".to_string()));
+        assert_eq!(unknown_sort_column("rowid DESC", &columns, true), None);
+        assert_eq!(unknown_sort_column("rowid DESC", &columns, false), Some("rowid".to_string()));
+    }
+
+    #[test]
+    fn file_stem_names_merged_and_dataset_tabs() {
+        assert_eq!(file_stem("/data/sales.parquet"), Some("sales"));
+        let files = vec!["/data/part-1.parquet".to_string(), "/data/part-2.parquet".to_string()];
+        assert_eq!(file_stem(&merged_path(&files)), Some("part-1"));
+        assert_eq!(file_stem(&dataset_path(std::path::Path::new("/data/events"))), Some("events"));
+    }
+
+    #[test]
+    fn page_count_keeps_one_empty_page() {
+        assert_eq!(page_count(0, 100), 1);
+        assert_eq!(page_count(1, 100), 1);
+        assert_eq!(page_count(100, 100), 1);
+        assert_eq!(page_count(101, 100), 2);
+        assert_eq!(page_count(5, 0), 5);
+    }
+
+    #[test]
+    fn clamp_page_stays_within_the_result() {
+        assert_eq!(clamp_page(7, 250, 100), 3);
+        assert_eq!(clamp_page(2, 250, 100), 2);
+        assert_eq!(clamp_page(0, 250, 100), 1);
+        assert_eq!(clamp_page(4, 0, 100), 1);
+    }
+
+    const LOAD_STATES: [LoadState; 10] = [LoadState::Idle, LoadState::Opening, LoadState::Reopening, LoadState::FileBusy { attempt: 1, attempts: 5 }, LoadState::ReadingSchema,
+        LoadState::Counting, LoadState::FirstPage, LoadState::LoadingPage { page: 2 }, LoadState::Applying, LoadState::Failed];
+
+    #[test]
+    fn load_state_failed_and_page_loaded_end_every_state() {
+        for state in LOAD_STATES {
+            assert_eq!(state.next(LoadEvent::Failed), LoadState::Failed, "{:?}", state);
+            assert_eq!(state.next(LoadEvent::PageLoaded), LoadState::Idle, "{:?}", state);
+            assert!(!state.next(LoadEvent::Failed).is_loading() && !state.next(LoadEvent::PageLoaded).is_loading());
+        }
+    }
+
+    #[test]
+    fn load_state_opening_only_moves_forward() {
+        use LoadState::*;
+        let busy = FileBusy { attempt: 1, attempts: 5 };
+        for start in [Opening, Reopening, busy] {
+            assert_eq!(start.next(LoadEvent::FileOpened), ReadingSchema);
+            // A step that answers early skips the ones before it
+            assert_eq!(start.next(LoadEvent::Schema), Counting);
+            assert_eq!(start.next(LoadEvent::Counted), FirstPage);
+        }
+        assert_eq!(ReadingSchema.next(LoadEvent::Schema), Counting);
+        assert_eq!(ReadingSchema.next(LoadEvent::Counted), FirstPage);
+        assert_eq!(Counting.next(LoadEvent::Counted), FirstPage);
+        // Late answers never move a load back
+        assert_eq!(ReadingSchema.next(LoadEvent::FileOpened), ReadingSchema);
+        assert_eq!(Counting.next(LoadEvent::Schema), Counting);
+        assert_eq!(FirstPage.next(LoadEvent::Counted), FirstPage);
+        for state in [Idle, LoadingPage { page: 2 }, Applying, Failed] {
+            for event in [LoadEvent::FileOpened, LoadEvent::Schema, LoadEvent::Counted] { assert_eq!(state.next(event), state, "{:?} {:?}", state, event); }
+        }
+        assert_eq!(LOAD_STATES.iter().filter(|s| s.open_step().is_some()).count(), 6);
+    }
+
+    #[test]
+    fn rows_shown_on_empty_full_and_ragged_pages() {
+        assert_eq!(rows_shown(page_rows(1, 100, 0), "0"), "Showing 0 of 0");
+        assert_eq!(rows_shown(page_rows(3, 100, 0), "200"), "Showing 0 of 200");
+        assert_eq!(rows_shown(page_rows(1, 100, 100), "100"), "Showing 1-100 of 100");
+        assert_eq!(rows_shown(page_rows(3, 100, 50), "250"), "Showing 201-250 of 250");
+        assert_eq!(rows_shown(page_rows(2, 100, 1), "101"), "Showing 101-101 of 101");
+    }
+
+    #[test]
+    fn scroll_blocks_drop_loads_cleared_in_flight() {
+        let mut blocks = ScrollBlocks { loading: Some((7, 3)), ..Default::default() };
+        blocks.clear();
+        assert!(blocks.loading.is_none());
+        assert!(blocks.was_dropped(7));
+        assert!(!blocks.was_dropped(7));
+    }
+
+    #[test]
+    fn scroll_blocks_failed_block_waits_for_retry() {
+        let mut blocks = ScrollBlocks { loading: Some((7, 3)), ..Default::default() };
+        blocks.failed();
+        assert!(!blocks.missing(3) && blocks.missing(4));
+        blocks.failed.clear();
+        assert!(blocks.missing(3));
+        blocks.loading = Some((8, 3));
+        blocks.loaded(PageData::default());
+        assert!(!blocks.missing(3));
+    }
+}
@@ -0,0 +1,143 @@
+// ParquetGrip - A high-performance Parquet file viewer.
+// Copyright (c) 2026 Edward (wansanai)
+// SPDX-License-Identifier: MIT
+
+use eframe::egui::Color32;
+use serde::{Deserialize, Serialize};
+
+use crate::backend::FieldPath;
+use crate::export::NULL_DISPLAY;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum RuleOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Contains,
+    IsNull,
+    NotNull,
+}
+
+impl RuleOp {
+    pub const ALL: [RuleOp; 9] = [RuleOp::Eq, RuleOp::Ne, RuleOp::Lt, RuleOp::Le, RuleOp::Gt, RuleOp::Ge, RuleOp::Contains, RuleOp::IsNull, RuleOp::NotNull];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            RuleOp::Eq => "=",
+            RuleOp::Ne => "≠",
+            RuleOp::Lt => "<",
+            RuleOp::Le => "≤",
+            RuleOp::Gt => ">",
+            RuleOp::Ge => "≥",
+            RuleOp::Contains => "contains",
+            RuleOp::IsNull => "is null",
+            RuleOp::NotNull => "is not null",
+        }
+    }
+
+    pub fn takes_value(&self) -> bool {
+        !matches!(self, RuleOp::IsNull | RuleOp::NotNull)
+    }
+
+    /// The SQL comparison operator, for the ops that are one.
+    fn sql(&self) -> Option<&'static str> {
+        match self {
+            RuleOp::Eq => Some("="),
+            RuleOp::Ne => Some("<>"),
+            RuleOp::Lt => Some("<"),
+            RuleOp::Le => Some("<="),
+            RuleOp::Gt => Some(">"),
+            RuleOp::Ge => Some(">="),
+            _ => None,
+        }
+    }
+}
+
+/// Tints every row whose `column` cell satisfies `op value`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RowRule {
+    pub enabled: bool,
+    pub column: String,
+    pub op: RuleOp,
+    pub value: String,
+    pub color: Color32,
+}
+
+impl RowRule {
+    pub fn new(column: String) -> Self {
+        Self { enabled: true, column, op: RuleOp::Eq, value: String::new(), color: Color32::from_rgb(220, 80, 80) }
+    }
+
+    /// Compares numerically when both sides parse as numbers, otherwise as strings. NULL only matches the null checks.
+    pub fn matches(&self, cell: &str) -> bool {
+        let is_null = cell == NULL_DISPLAY;
+        match self.op {
+            RuleOp::IsNull => return is_null,
+            RuleOp::NotNull => return !is_null,
+            _ if is_null => return false,
+            RuleOp::Contains => return cell.contains(self.value.as_str()),
+            _ => {}
+        }
+        let ord = match (cell.trim().parse::<f64>(), self.value.trim().parse::<f64>()) {
+            (Ok(a), Ok(b)) => a.partial_cmp(&b),
+            _ => Some(cell.cmp(self.value.as_str())),
+        };
+        let Some(ord) = ord else { return false };
+        match self.op {
+            RuleOp::Eq => ord.is_eq(),
+            RuleOp::Ne => ord.is_ne(),
+            RuleOp::Lt => ord.is_lt(),
+            RuleOp::Le => ord.is_le(),
+            RuleOp::Gt => ord.is_gt(),
+            RuleOp::Ge => ord.is_ge(),
+            _ => false,
+        }
+    }
+}
+
+/// Right-hand side of a filter-builder condition: a typed literal or another column or nested field.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Operand {
+    Value(String),
+    Column(FieldPath),
+}
+
+/// One condition put together in the filter builder, added to the WHERE box as SQL.
+#[derive(Clone, Debug)]
+pub struct FilterTerm {
+    pub column: FieldPath,
+    pub op: RuleOp,
+    pub rhs: Operand,
+}
+
+impl FilterTerm {
+    pub fn new(column: FieldPath) -> Self {
+        Self { column, op: RuleOp::Eq, rhs: Operand::Value(String::new()) }
+    }
+
+    /// Column names are always quoted; a literal is left bare only when `numeric` and it parses as a number.
+    pub fn sql(&self, numeric: bool) -> String {
+        let lhs = &self.column.sql;
+        let rhs = match &self.rhs {
+            Operand::Column(c) => c.sql.clone(),
+            Operand::Value(v) if numeric && v.trim().parse::<f64>().is_ok() => v.trim().to_string(),
+            Operand::Value(v) => format!("'{}'", v.replace('\'', "''")),
+        };
+        match (self.op, self.op.sql()) {
+            (RuleOp::IsNull, _) => format!("{} IS NULL", lhs),
+            (RuleOp::NotNull, _) => format!("{} IS NOT NULL", lhs),
+            (_, Some(cmp)) => format!("{} {} {}", lhs, cmp, rhs),
+            _ => format!("contains(CAST({} AS VARCHAR), CAST({} AS VARCHAR))", lhs, rhs),
+        }
+    }
+}
+
+/// The color of the first enabled rule the row matches.
+pub fn row_color(rules: &[RowRule], schema: &[String], row: &[impl AsRef<str>]) -> Option<Color32> {
+    rules.iter().filter(|r| r.enabled).find(|r| {
+        schema.iter().position(|c| *c == r.column).and_then(|i| row.get(i)).is_some_and(|cell| r.matches(cell.as_ref()))
+    }).map(|r| r.color)
+}
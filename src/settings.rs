@@ -0,0 +1,168 @@
+// ParquetGrip - A high-performance Parquet file viewer.
+// Copyright (c) 2026 Edward (wansanai)
+// SPDX-License-Identifier: MIT
+
+use serde::{Deserialize, Serialize};
+
+use crate::backend::{EngineConfig, DEFAULT_ROW_CAP};
+
+/// App-wide preferences, persisted with the session.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct Settings {
+    /// Copy column values as a quoted, comma-separated SQL `IN (...)` list instead of one per line.
+    pub copy_as_in_list: bool,
+    pub copy_skip_nulls: bool,
+    /// Copying an entire column asks for confirmation above this many rows.
+    pub copy_confirm_rows: usize,
+    /// Copying every row of a result asks for confirmation above this many rows.
+    #[serde(alias = "copy_rows_confirm")]
+    pub copy_all_rows_confirm: usize,
+    pub row_density: RowDensity,
+    /// Draw vertical separators between grid columns.
+    pub grid_lines: bool,
+    /// Prefix each column header with its position in the file, e.g. `[3] price`.
+    pub column_ordinals: bool,
+    /// Count those positions from 0 rather than 1.
+    pub ordinals_from_zero: bool,
+    /// Group the digits of numbers in the grid with thousands separators; a column set to plain opts out.
+    pub group_digits: bool,
+    /// Open files without counting their rows; the count runs when asked for from the footer.
+    pub skip_open_count: bool,
+    /// Fold each tab's WHERE/ORDER BY toolbar down to a one-line summary.
+    pub toolbar_collapsed: bool,
+    /// Cells longer than this are cut short with an ellipsis where full text doesn't fit (e.g. HTML export).
+    pub max_cell_chars: usize,
+    /// Turn `snake_case` column names into `camelCase` keys in JSON output.
+    pub json_camel_case: bool,
+    /// Draw a strip beside the grid marking Parquet row-group boundaries and where the current page falls.
+    pub show_row_groups: bool,
+    /// Apply the filter and sort by themselves once typing pauses, if they're valid.
+    pub live_filter: bool,
+    pub live_filter_delay_ms: u64,
+    /// Recently viewed pages kept per tab so paging back is instant, capped by count and by size.
+    pub page_cache_pages: usize,
+    pub page_cache_mb: usize,
+    /// Re-query a page served from the cache in the background and swap in the fresh rows.
+    pub page_cache_revalidate: bool,
+    /// Grid queries running longer than this are stopped; 0 turns the limit off.
+    pub query_timeout_secs: u64,
+    /// The running-time counter of a page load turns into a warning past this many seconds.
+    pub slow_query_secs: u64,
+    /// Page queries cut text longer than this short and mark its full length; 0 fetches every value whole.
+    pub cell_fetch_chars: usize,
+    /// Most rows a grid page or clipboard copy holds in memory; COPY-based exports stream and ignore it.
+    pub max_result_rows: usize,
+    /// DuckDB engine limits; empty or 0 leaves DuckDB's own default.
+    pub duckdb_memory_limit: String,
+    pub duckdb_threads: usize,
+    pub duckdb_temp_directory: String,
+    /// SET/RESET/PRAGMA statements every new connection runs, e.g. `SET TimeZone = 'UTC'`.
+    pub duckdb_session_sql: String,
+    /// DuckDB extensions installed and loaded at startup, separated by commas or spaces.
+    pub duckdb_extensions: String,
+    /// File name endings the open dialog lists and drag-and-drop accepts, separated by commas or spaces.
+    pub open_file_types: String,
+}
+
+/// Table row height preset from the View menu.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug, Default)]
+pub enum RowDensity {
+    Compact,
+    #[default]
+    Normal,
+    Comfortable,
+}
+
+impl RowDensity {
+    pub const ALL: [RowDensity; 3] = [RowDensity::Compact, RowDensity::Normal, RowDensity::Comfortable];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            RowDensity::Compact => "Compact",
+            RowDensity::Normal => "Normal",
+            RowDensity::Comfortable => "Comfortable",
+        }
+    }
+
+    pub fn row_height(&self) -> f32 {
+        match self {
+            RowDensity::Compact => 20.0,
+            RowDensity::Normal => 26.0,
+            RowDensity::Comfortable => 34.0,
+        }
+    }
+
+    /// Multiplier applied to the table's text sizes so glyphs stay proportional to the rows.
+    pub fn font_scale(&self) -> f32 {
+        match self {
+            RowDensity::Compact => 0.92,
+            RowDensity::Normal => 1.0,
+            RowDensity::Comfortable => 1.1,
+        }
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            copy_as_in_list: false,
+            copy_skip_nulls: true,
+            copy_confirm_rows: 100_000,
+            copy_all_rows_confirm: 10_000,
+            row_density: RowDensity::Normal,
+            grid_lines: false,
+            column_ordinals: false,
+            ordinals_from_zero: false,
+            group_digits: false,
+            skip_open_count: false,
+            toolbar_collapsed: false,
+            max_cell_chars: 200,
+            json_camel_case: false,
+            show_row_groups: false,
+            live_filter: false,
+            live_filter_delay_ms: 400,
+            page_cache_pages: 5,
+            page_cache_mb: 256,
+            page_cache_revalidate: false,
+            query_timeout_secs: 60,
+            slow_query_secs: 10,
+            cell_fetch_chars: 4096,
+            max_result_rows: DEFAULT_ROW_CAP,
+            duckdb_memory_limit: String::new(),
+            duckdb_threads: 0,
+            duckdb_temp_directory: String::new(),
+            duckdb_session_sql: String::new(),
+            duckdb_extensions: "parquet".to_string(),
+            open_file_types: "parquet, pqt, parq, csv, json, gz".to_string(),
+        }
+    }
+}
+
+impl Settings {
+    pub fn engine(&self) -> EngineConfig {
+        let text = |s: &str| Some(s.trim().to_string()).filter(|s| !s.is_empty());
+        EngineConfig { memory_limit: text(&self.duckdb_memory_limit), threads: Some(self.duckdb_threads).filter(|&t| t > 0), temp_directory: text(&self.duckdb_temp_directory), session_sql: text(&self.duckdb_session_sql) }
+    }
+
+    /// The number the first column's position label starts at, or `None` when positions are hidden.
+    pub fn ordinal_base(&self) -> Option<usize> {
+        self.column_ordinals.then_some(usize::from(!self.ordinals_from_zero))
+    }
+
+    pub fn extensions(&self) -> Vec<String> {
+        self.duckdb_extensions.split([',', ' ']).map(str::trim).filter(|e| !e.is_empty()).map(str::to_string).collect()
+    }
+
+    /// The open file types without leading dots, lowercased; an empty list falls back to the defaults.
+    pub fn open_file_types(&self) -> Vec<String> {
+        let types: Vec<String> = self.open_file_types.split([',', ' ']).map(|t| t.trim().trim_start_matches("*.").trim_start_matches('.').to_lowercase()).filter(|t| !t.is_empty()).collect();
+        if types.is_empty() { Settings::default().open_file_types() } else { types }
+    }
+
+    /// Whether a file's name ends in one of the open file types, so `data.parquet.gz` matches `parquet.gz`.
+    pub fn accepts_file(&self, path: &std::path::Path) -> bool {
+        let name = path.file_name().map(|n| n.to_string_lossy().to_lowercase()).unwrap_or_default();
+        self.open_file_types().iter().any(|t| name.len() > t.len() && name.ends_with(t.as_str()) && name[..name.len() - t.len()].ends_with('.'))
+    }
+}